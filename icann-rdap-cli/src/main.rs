@@ -17,7 +17,7 @@ use write::PagerWrite;
 
 use clap::{ArgGroup, Parser, ValueEnum};
 use error::CliError;
-use icann_rdap_client::query::qtype::QueryType;
+use icann_rdap_client::query::qtype::{FieldSet, QueryType, RelatedEntity};
 use icann_rdap_common::VERSION;
 use query::OutputType;
 use reqwest::Client;
@@ -106,6 +106,13 @@ struct Cli {
     #[arg(short = 'B', long, required = false, env = "RDAP_BASE_URL")]
     base_url: Option<String>,
 
+    /// Field set of search results.
+    ///
+    /// Asks the server for an RFC 8982 field set of the objects in search results,
+    /// so that fewer of their fields are given. This is not used with lookups.
+    #[arg(long, required = false, env = "RDAP_FIELD_SET", value_enum)]
+    field_set: Option<FieldSetArg>,
+
     /// Output format.
     ///
     /// This option determines the format of the result.
@@ -270,10 +277,32 @@ enum QtypeArg {
     /// Nameserver IP Address Search
     NsIp,
 
+    /// Domain Reverse Search by Entity (e.g. "handle=XXXX&role=registrant")
+    DomainsByEntity,
+
+    /// Nameserver Reverse Search by Entity (e.g. "fn=Bob*&role=technical")
+    NsByEntity,
+
+    /// Entity Reverse Search by Entity (e.g. "email=abuse@example.com")
+    EntitiesByEntity,
+
     /// RDAP URL
     Url,
 }
 
+/// Represents the RFC 8982 field sets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum FieldSetArg {
+    /// Only the key of each object, such as the name of a domain.
+    Id,
+
+    /// The key, handle, status and self link of each object.
+    Brief,
+
+    /// All the fields of each object.
+    Full,
+}
+
 /// Represents the output type possibilities.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum OtypeArg {
@@ -371,7 +400,7 @@ pub async fn main() -> anyhow::Result<()> {
 
     let level = LevelFilter::from(&cli.log_level);
 
-    let query_type = query_type_from_cli(&cli);
+    let query_type = query_type_from_cli(&cli)?;
 
     let use_pager = match cli.page_output {
         PagerType::Embedded => true,
@@ -426,6 +455,11 @@ pub async fn main() -> anyhow::Result<()> {
         error_on_checks: cli.error_on_checks,
         no_cache: cli.no_cache,
         max_cache_age: cli.max_cache_age,
+        field_set: cli.field_set.map(|field_set| match field_set {
+            FieldSetArg::Id => FieldSet::Id,
+            FieldSetArg::Brief => FieldSet::Brief,
+            FieldSetArg::Full => FieldSet::Full,
+        }),
     };
 
     let client_config = ClientConfig::builder()
@@ -513,8 +547,8 @@ async fn exec<W: std::io::Write>(
     }
 }
 
-fn query_type_from_cli(cli: &Cli) -> QueryType {
-    if let Some(query_value) = cli.query_value.clone() {
+fn query_type_from_cli(cli: &Cli) -> Result<QueryType, CliError> {
+    let query_type = if let Some(query_value) = cli.query_value.clone() {
         if let Some(query_type) = cli.query_type {
             match query_type {
                 QtypeArg::V4 => QueryType::IpV4Addr(query_value),
@@ -533,6 +567,15 @@ fn query_type_from_cli(cli: &Cli) -> QueryType {
                 QtypeArg::DomainNsIp => QueryType::DomainNsIpSearch(query_value),
                 QtypeArg::NsName => QueryType::NameserverNameSearch(query_value),
                 QtypeArg::NsIp => QueryType::NameserverIpSearch(query_value),
+                QtypeArg::DomainsByEntity => {
+                    QueryType::DomainsByEntity(RelatedEntity::from_str(&query_value)?)
+                }
+                QtypeArg::NsByEntity => {
+                    QueryType::NameserversByEntity(RelatedEntity::from_str(&query_value)?)
+                }
+                QtypeArg::EntitiesByEntity => {
+                    QueryType::EntitiesByEntity(RelatedEntity::from_str(&query_value)?)
+                }
                 QtypeArg::Url => QueryType::Url(query_value),
             }
         } else {
@@ -540,7 +583,8 @@ fn query_type_from_cli(cli: &Cli) -> QueryType {
         }
     } else {
         QueryType::Help
-    }
+    };
+    Ok(query_type)
 }
 
 #[cfg(test)]
@@ -7,7 +7,10 @@ use tracing::info;
 
 use icann_rdap_client::{
     md::{MdOptions, MdParams, ToMd},
-    query::{qtype::QueryType, request::ResponseData},
+    query::{
+        qtype::{FieldSet, QueryType},
+        request::ResponseData,
+    },
     request::{RequestData, RequestResponse, RequestResponses, SourceType},
 };
 use icann_rdap_common::{media_types::RDAP_MEDIA_TYPE, response::RdapResponse};
@@ -44,6 +47,7 @@ pub(crate) struct ProcessingParams {
     pub error_on_checks: bool,
     pub no_cache: bool,
     pub max_cache_age: u32,
+    pub field_set: Option<FieldSet>,
 }
 
 pub(crate) async fn do_query<'a, W: std::io::Write>(
@@ -24,7 +24,7 @@ pub(crate) async fn do_request(
     if processing_params.no_cache {
         info!("Cache has been disabled.")
     }
-    let query_url = query_type.query_url(base_url)?;
+    let query_url = query_url(base_url, query_type, processing_params)?;
     debug!("Requestion RDAP URL {query_url}");
     if !processing_params.no_cache {
         let file_name = format!(
@@ -56,7 +56,6 @@ pub(crate) async fn do_request(
             if response.http_data.should_cache() {
                 let data = serde_json::to_string_pretty(&response)?;
                 let cache_contents = response.http_data.to_lines(&data)?;
-                let query_url = query_type.query_url(base_url)?;
                 let file_name = format!(
                     "{}.cache",
                     PctString::encode(query_url.chars(), URIReserved)
@@ -85,3 +84,19 @@ pub(crate) async fn do_request(
     }
     Ok(response)
 }
+
+/// Gets the URL of the query, asking for the field set of the processing parameters with
+/// searches. Lookups cannot ask for a field set.
+fn query_url(
+    base_url: &str,
+    query_type: &QueryType,
+    processing_params: &ProcessingParams,
+) -> Result<String, CliError> {
+    let query_url = match processing_params.field_set {
+        Some(field_set) if query_type.is_search() => {
+            query_type.field_set_query_url(base_url, field_set)?
+        }
+        _ => query_type.query_url(base_url)?,
+    };
+    Ok(query_url)
+}
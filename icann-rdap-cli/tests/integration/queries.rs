@@ -1,7 +1,9 @@
 #![allow(non_snake_case)]
 
+use icann_rdap_client::request::RequestResponseOwned;
 use icann_rdap_common::response::{
     autnum::Autnum, domain::Domain, entity::Entity, nameserver::Nameserver, network::Network,
+    RdapResponse,
 };
 use icann_rdap_srv::storage::StoreOps;
 use rstest::rstest;
@@ -158,6 +160,43 @@ async fn GIVEN_url_WHEN_query_THEN_success() {
     assert.success();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn GIVEN_domain_WHEN_search_with_id_field_set_THEN_only_names_returned() {
+    // GIVEN
+    let mut test_jig = TestJig::new().await;
+    let mut tx = test_jig.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .handle("foo-example-1")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    test_jig
+        .cmd
+        .arg("--field-set")
+        .arg("id")
+        .arg("-t")
+        .arg("domain-name")
+        .arg("*.example");
+
+    // THEN
+    let output = test_jig.cmd.output().expect("executing domain search");
+    let responses: Vec<RequestResponseOwned> =
+        serde_json::from_slice(&output.stdout).expect("parsing stdout");
+    let rdap = &responses.first().expect("response is empty").res_data.rdap;
+    let RdapResponse::DomainSearchResults(results) = rdap else {
+        panic!("not domain search results")
+    };
+    let domain = results.results.first().expect("no domains");
+    assert_eq!(domain.ldh_name.as_deref(), Some("foo.example"));
+    assert!(domain.object_common.handle.is_none());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn GIVEN_idn_WHEN_query_a_label_THEN_success() {
     // GIVEN
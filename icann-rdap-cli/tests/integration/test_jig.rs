@@ -1,5 +1,9 @@
 use assert_cmd::Command;
 use icann_rdap_srv::config::ListenConfig;
+use icann_rdap_srv::config::{
+    CacheConfig, RateLimitConfig, DEFAULT_MAX_SEARCH_RESULTS, DEFAULT_SEARCH_PAGE_SIZE,
+};
+use icann_rdap_srv::health::Health;
 use icann_rdap_srv::server::AppState;
 use icann_rdap_srv::server::Listener;
 use icann_rdap_srv::storage::mem::ops::Mem;
@@ -22,6 +26,15 @@ impl TestJig {
         let app_state = AppState {
             storage: mem.clone(),
             bootstrap: false,
+            max_search_results: DEFAULT_MAX_SEARCH_RESULTS,
+            search_page_size: DEFAULT_SEARCH_PAGE_SIZE,
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            credentials: None,
+            redaction_policy: None,
+            openidc: None,
+            health: Health::default(),
+            admin: None,
         };
         let _ = tracing_subscriber::fmt().try_init();
         let listener = Listener::listen(&ListenConfig::default())
@@ -17,10 +17,14 @@ pub mod request;
 #[doc(inline)]
 pub use crate::query::bootstrap::MemoryBootstrapStore;
 #[doc(inline)]
+pub use crate::query::qtype::FieldSet;
+#[doc(inline)]
 pub use crate::query::qtype::QueryType;
 #[doc(inline)]
 pub use crate::query::request::rdap_bootstrapped_request;
 #[doc(inline)]
+pub use crate::query::request::rdap_paged_request;
+#[doc(inline)]
 pub use crate::query::request::rdap_request;
 #[doc(inline)]
 pub use crate::query::request::rdap_url_request;
@@ -54,6 +58,8 @@ pub enum RdapClientError {
     IanaResponse(#[from] IanaResponseError),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    UrlParse(#[from] url::ParseError),
 }
 
 impl<T> From<PoisonError<T>> for RdapClientError {
@@ -60,6 +60,15 @@ pub enum QueryType {
     #[strum(serialize = "Nameserver IP Address Search")]
     NameserverIpSearch(String),
 
+    #[strum(serialize = "Domain Reverse Search by Entity")]
+    DomainsByEntity(RelatedEntity),
+
+    #[strum(serialize = "Nameserver Reverse Search by Entity")]
+    NameserversByEntity(RelatedEntity),
+
+    #[strum(serialize = "Entity Reverse Search by Entity")]
+    EntitiesByEntity(RelatedEntity),
+
     #[strum(serialize = "Server Help Lookup")]
     Help,
 
@@ -112,13 +121,75 @@ impl QueryType {
             }
             QueryType::DomainNsIpSearch(value) => search_query(value, "domains?nsIp", base_url),
             QueryType::NameserverNameSearch(value) => {
-                search_query(value, "nameserver?name=", base_url)
+                search_query(value, "nameservers?name", base_url)
             }
             QueryType::NameserverIpSearch(value) => search_query(value, "nameservers?ip", base_url),
+            QueryType::DomainsByEntity(related) => {
+                reverse_search_query(related, "domains", base_url)
+            }
+            QueryType::NameserversByEntity(related) => {
+                reverse_search_query(related, "nameservers", base_url)
+            }
+            QueryType::EntitiesByEntity(related) => {
+                reverse_search_query(related, "entities", base_url)
+            }
             QueryType::Help => Ok(format!("{base_url}/help")),
             QueryType::Url(url) => Ok(url.to_owned()),
         }
     }
+
+    /// Gets the URL of a search asking for the results with an RFC 8982 field set.
+    /// Field sets may only be asked for with searches.
+    ///
+    /// This is separate from [QueryType::query_url] because a field set is not part of
+    /// the query: it only changes how much of each result is given, and lookups, which
+    /// are also made with [QueryType::query_url], cannot ask for one. Code making a
+    /// search with an optional field set should use this when one is given, as
+    /// [crate::query::request::rdap_paged_request] does.
+    pub fn field_set_query_url(
+        &self,
+        base_url: &str,
+        field_set: FieldSet,
+    ) -> Result<String, RdapClientError> {
+        if !self.is_search() {
+            return Err(RdapClientError::InvalidQueryValue);
+        }
+        let query_url = self.query_url(base_url)?;
+        Ok(format!("{query_url}&fieldSet={field_set}"))
+    }
+
+    /// True if the query is a search giving search results.
+    pub fn is_search(&self) -> bool {
+        matches!(
+            self,
+            QueryType::EntityNameSearch(_)
+                | QueryType::EntityHandleSearch(_)
+                | QueryType::DomainNameSearch(_)
+                | QueryType::DomainNsNameSearch(_)
+                | QueryType::DomainNsIpSearch(_)
+                | QueryType::NameserverNameSearch(_)
+                | QueryType::NameserverIpSearch(_)
+                | QueryType::DomainsByEntity(_)
+                | QueryType::NameserversByEntity(_)
+                | QueryType::EntitiesByEntity(_)
+        )
+    }
+}
+
+/// The field sets of RFC 8982 partial responses.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldSet {
+    /// Only the key of each object, such as the name of a domain.
+    #[strum(serialize = "id")]
+    Id,
+
+    /// The key, handle, status and self link of each object.
+    #[strum(serialize = "brief")]
+    Brief,
+
+    /// All the fields of each object.
+    #[strum(serialize = "full")]
+    Full,
 }
 
 fn a_label_query(value: &str, base_url: &str) -> Result<String, RdapClientError> {
@@ -147,6 +218,77 @@ fn search_query(value: &str, path_query: &str, base_url: &str) -> Result<String,
     ))
 }
 
+fn reverse_search_query(
+    related: &RelatedEntity,
+    searchable: &str,
+    base_url: &str,
+) -> Result<String, RdapClientError> {
+    let properties = related.properties();
+    if properties.is_empty() {
+        return Err(RdapClientError::InvalidQueryValue);
+    }
+    let query = properties
+        .iter()
+        .map(|(name, value)| format!("{name}={}", PctString::encode(value.chars(), URIReserved)))
+        .collect::<Vec<String>>()
+        .join("&");
+    Ok(format!(
+        "{base_url}/{searchable}/reverse_search/entity?{query}"
+    ))
+}
+
+/// The properties of a related entity used to find objects in an RFC 9536 reverse search.
+///
+/// Objects are found when an entity embedded in them has all of the given properties.
+/// The handle, full name and email may use the `*` wildcard for partial matches.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelatedEntity {
+    pub handle: Option<String>,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub role: Option<String>,
+}
+
+impl RelatedEntity {
+    /// Gets the given properties as pairs of the RFC 9536 property name and value.
+    fn properties(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("handle", &self.handle),
+            ("fn", &self.full_name),
+            ("email", &self.email),
+            ("role", &self.role),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_deref().map(|value| (name, value)))
+        .collect()
+    }
+}
+
+impl FromStr for RelatedEntity {
+    type Err = RdapClientError;
+
+    /// Parses the properties from `name=value` pairs separated by `&`,
+    /// such as `handle=XXXX&role=registrant`. The property names are
+    /// `handle`, `fn`, `email` and `role`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut related = RelatedEntity::default();
+        for pair in s.split('&') {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or(RdapClientError::InvalidQueryValue)?;
+            let value = Some(value.trim().to_string());
+            match name.trim() {
+                "handle" => related.handle = value,
+                "fn" => related.full_name = value,
+                "email" => related.email = value,
+                "role" => related.role = value,
+                _ => return Err(RdapClientError::InvalidQueryValue),
+            }
+        }
+        Ok(related)
+    }
+}
+
 impl FromStr for QueryType {
     type Err = RdapClientError;
 
@@ -225,7 +367,7 @@ mod tests {
 
     use rstest::rstest;
 
-    use super::QueryType;
+    use super::{FieldSet, QueryType, RelatedEntity};
 
     #[test]
     fn GIVEN_ipv4_WHEN_query_type_from_str_THEN_query_is_ipv4() {
@@ -367,4 +509,90 @@ mod tests {
         // THEN
         assert!(q.is_err());
     }
+
+    #[test]
+    fn GIVEN_nameserver_name_search_WHEN_query_url_THEN_nameservers_search_url() {
+        // GIVEN
+        let q = QueryType::NameserverNameSearch("ns1.*".to_string());
+
+        // WHEN
+        let actual = q.query_url("https://example.com/rdap").expect("query url");
+
+        // THEN
+        assert_eq!(actual, "https://example.com/rdap/nameservers?name=ns1.%2A")
+    }
+
+    #[rstest]
+    #[case("handle=XXXX", "handle=XXXX")]
+    #[case("fn=Bob*&role=registrant", "fn=Bob%2A&role=registrant")]
+    #[case(
+        "role=abuse&email=abuse@example.com",
+        "email=abuse%40example.com&role=abuse"
+    )]
+    fn GIVEN_related_entity_WHEN_domains_by_entity_query_url_THEN_reverse_search_url(
+        #[case] input: &str,
+        #[case] expected_query: &str,
+    ) {
+        // GIVEN
+        let q = QueryType::DomainsByEntity(RelatedEntity::from_str(input).expect("related entity"));
+
+        // WHEN
+        let actual = q.query_url("https://example.com/rdap").expect("query url");
+
+        // THEN
+        assert_eq!(
+            actual,
+            format!("https://example.com/rdap/domains/reverse_search/entity?{expected_query}")
+        )
+    }
+
+    #[rstest]
+    #[case(
+        FieldSet::Id,
+        "https://example.com/rdap/domains?name=%2A.example&fieldSet=id"
+    )]
+    #[case(
+        FieldSet::Brief,
+        "https://example.com/rdap/domains?name=%2A.example&fieldSet=brief"
+    )]
+    fn GIVEN_domain_name_search_WHEN_field_set_query_url_THEN_field_set_param(
+        #[case] field_set: FieldSet,
+        #[case] expected: &str,
+    ) {
+        // GIVEN
+        let q = QueryType::DomainNameSearch("*.example".to_string());
+
+        // WHEN
+        let actual = q
+            .field_set_query_url("https://example.com/rdap", field_set)
+            .expect("query url");
+
+        // THEN
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn GIVEN_lookup_WHEN_field_set_query_url_THEN_error() {
+        // GIVEN
+        let q = QueryType::Domain("foo.example".to_string());
+
+        // WHEN
+        let actual = q.field_set_query_url("https://example.com/rdap", FieldSet::Id);
+
+        // THEN
+        assert!(actual.is_err());
+    }
+
+    #[rstest]
+    #[case("XXXX")]
+    #[case("name=XXXX")]
+    fn GIVEN_bad_related_entity_WHEN_from_str_THEN_error(#[case] input: &str) {
+        // GIVEN case input
+
+        // WHEN
+        let related = RelatedEntity::from_str(input);
+
+        // THEN
+        assert!(related.is_err());
+    }
 }
@@ -1,16 +1,23 @@
-use icann_rdap_common::{cache::HttpData, iana::IanaRegistryType, response::RdapResponse};
+use std::collections::HashSet;
+
+use icann_rdap_common::{
+    cache::HttpData,
+    iana::IanaRegistryType,
+    response::{search::PagingMetadata, RdapResponse},
+};
 use reqwest::{
     header::{CACHE_CONTROL, CONTENT_TYPE, EXPIRES, LOCATION},
     Client,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use url::Url;
 
 use crate::RdapClientError;
 
 use super::{
     bootstrap::{qtype_to_bootstrap_url, BootstrapStore},
-    qtype::QueryType,
+    qtype::{FieldSet, QueryType},
 };
 
 /// Makes an RDAP request with a full RDAP URL.
@@ -210,9 +217,177 @@ where
     rdap_request(&base_url, query_type, client).await
 }
 
+/// Makes RDAP search requests for all the pages of search results with a base URL.
+///
+/// This function takes the following parameters:
+/// * base_url - a string reference of the base URL
+/// * query_type - a reference to the RDAP query.
+/// * field_set - the RFC 8982 field set of the results, if one is asked for.
+/// * client - a reference to a [reqwest::Client].
+///
+/// Search results may be split into pages according to RFC 8977. Each page is requested
+/// by following the `next` link in the paging metadata of the previous page, and the
+/// responses of all the pages are returned in order. Search results without paging
+/// metadata have one page. Use [SearchPages] to request the pages one at a time.
+///
+/// ```no_run
+/// use icann_rdap_common::client::ClientConfig;
+/// use icann_rdap_common::client::create_client;
+/// use icann_rdap_client::query::request::rdap_paged_request;
+/// use icann_rdap_client::query::qtype::QueryType;
+/// use icann_rdap_client::RdapClientError;
+/// use tokio::main;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), RdapClientError> {
+///
+///     // create a query
+///     let query = QueryType::DomainNameSearch("*.example".to_string());
+///
+///     // create a client (from icann-rdap-common)
+///     let config = ClientConfig::default();
+///     let client = create_client(&config)?;
+///
+///     // issue the RDAP query for all the pages
+///     let pages =
+///         rdap_paged_request(
+///             "https://rdap.example.com/rdap",
+///             &query,
+///             None,
+///             &client,
+///     ).await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub async fn rdap_paged_request(
+    base_url: &str,
+    query_type: &QueryType,
+    field_set: Option<FieldSet>,
+    client: &Client,
+) -> Result<Vec<ResponseData>, RdapClientError> {
+    let url = match field_set {
+        Some(field_set) => query_type.field_set_query_url(base_url, field_set)?,
+        None => query_type.query_url(base_url)?,
+    };
+    let mut pages = SearchPages::new(url);
+    let mut responses = vec![];
+    while let Some(response) = pages.next_page(client).await? {
+        responses.push(response);
+    }
+    Ok(responses)
+}
+
+/// Requests the pages of RFC 8977 paged search results one at a time, following the
+/// `next` link in the paging metadata of each page. A `next` link to a page already
+/// requested ends the paging.
+pub struct SearchPages {
+    next_url: Option<String>,
+    requested: HashSet<String>,
+}
+
+impl SearchPages {
+    /// Creates the pages of the search results at the URL.
+    pub fn new(url: String) -> Self {
+        Self {
+            next_url: Some(url),
+            requested: HashSet::new(),
+        }
+    }
+
+    /// Requests the next page, giving `None` when there are no more pages.
+    pub async fn next_page(
+        &mut self,
+        client: &Client,
+    ) -> Result<Option<ResponseData>, RdapClientError> {
+        let Some(url) = self.next_url.take() else {
+            return Ok(None);
+        };
+        let response = rdap_url_request(&url, client).await?;
+        self.requested.insert(url.clone());
+        self.next_url = next_page_url(&url, &response.rdap)?
+            .filter(|next_url| !self.requested.contains(next_url));
+        Ok(Some(response))
+    }
+}
+
+/// Gets the URL of the next page of search results from the `next` link of the paging
+/// metadata, resolving it against the URL of the current page.
+pub fn next_page_url(url: &str, rdap: &RdapResponse) -> Result<Option<String>, RdapClientError> {
+    let paging_metadata = match rdap {
+        RdapResponse::DomainSearchResults(results) => results.paging_metadata.as_ref(),
+        RdapResponse::NameserverSearchResults(results) => results.paging_metadata.as_ref(),
+        RdapResponse::EntitySearchResults(results) => results.paging_metadata.as_ref(),
+        _ => None,
+    };
+    let Some(href) = paging_metadata.and_then(PagingMetadata::next_href) else {
+        return Ok(None);
+    };
+    Ok(Some(Url::parse(url)?.join(href)?.to_string()))
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ResponseData {
     pub rdap: RdapResponse,
     pub rdap_type: String,
     pub http_data: HttpData,
 }
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use icann_rdap_common::response::{
+        search::{DomainSearchResults, PagingMetadata},
+        types::{Common, Link},
+        RdapResponse,
+    };
+
+    use super::next_page_url;
+
+    #[test]
+    fn GIVEN_relative_next_link_WHEN_next_page_url_THEN_resolved_against_url() {
+        // GIVEN
+        let rdap = RdapResponse::DomainSearchResults(
+            DomainSearchResults::builder()
+                .common(Common::level0().build())
+                .paging_metadata(
+                    PagingMetadata::builder()
+                        .links(vec![Link::builder()
+                            .rel("next")
+                            .href("/rdap/domains?name=*.example&cursor=b2Zmc2V0PTI1")
+                            .build()])
+                        .build(),
+                )
+                .results(vec![])
+                .build(),
+        );
+
+        // WHEN
+        let actual = next_page_url("https://example.com/rdap/domains?name=*.example", &rdap)
+            .expect("next page url");
+
+        // THEN
+        assert_eq!(
+            actual.as_deref(),
+            Some("https://example.com/rdap/domains?name=*.example&cursor=b2Zmc2V0PTI1")
+        );
+    }
+
+    #[test]
+    fn GIVEN_no_paging_metadata_WHEN_next_page_url_THEN_none() {
+        // GIVEN
+        let rdap = RdapResponse::DomainSearchResults(
+            DomainSearchResults::builder()
+                .common(Common::level0().build())
+                .results(vec![])
+                .build(),
+        );
+
+        // WHEN
+        let actual = next_page_url("https://example.com/rdap/domains?name=*.example", &rdap)
+            .expect("next page url");
+
+        // THEN
+        assert!(actual.is_none());
+    }
+}
@@ -2,13 +2,24 @@ use crate::response::RdapResponseError;
 use buildstructor::Builder;
 use serde::{Deserialize, Serialize};
 
-use super::types::Common;
+use super::{
+    openidc::{OpenIdcConfiguration, OpenIdcProvider},
+    types::Common,
+};
 
 /// Represents an RDAP help response.
 #[derive(Serialize, Deserialize, Builder, Clone, Debug, PartialEq, Eq)]
 pub struct Help {
     #[serde(flatten)]
     pub common: Common,
+
+    #[serde(rename = "roidc1_openidcConfiguration")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub openidc_configuration: Option<OpenIdcConfiguration>,
+
+    #[serde(rename = "roidc1_openidcProviders")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub openidc_providers: Option<Vec<OpenIdcProvider>>,
 }
 
 #[buildstructor::buildstructor]
@@ -27,6 +38,8 @@ impl Help {
     ) -> Result<Self, RdapResponseError> {
         Ok(Self {
             common: Common::level0_with_options().and_notices(notices).build(),
+            openidc_configuration: None,
+            openidc_providers: None,
         })
     }
 }
@@ -25,6 +25,7 @@ pub mod error;
 pub mod help;
 pub mod nameserver;
 pub mod network;
+pub mod openidc;
 pub mod redacted;
 pub mod search;
 pub mod types;
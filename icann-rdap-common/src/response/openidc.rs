@@ -0,0 +1,116 @@
+use buildstructor::Builder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::types::Common;
+
+/// Represents the RFC 9560 OpenID Connect configuration of a server given in help responses.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct OpenIdcConfiguration {
+    /// True if the server supports the `roidc1_dnt` (do not track) query parameter.
+    #[serde(rename = "dntSupported")]
+    pub dnt_supported: bool,
+
+    /// True if the server supports discovery of the OpenID provider of an end-user identifier.
+    #[serde(rename = "endUserIdentifierDiscoverySupported")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_user_identifier_discovery_supported: Option<bool>,
+
+    /// True if the server supports the `roidc1_iss` query parameter naming an OpenID provider.
+    #[serde(rename = "issuerIdentifierSupported")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer_identifier_supported: Option<bool>,
+}
+
+/// Represents an OpenID provider that may be used to log in to a server.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct OpenIdcProvider {
+    /// The issuer identifier of the provider, given in the `roidc1_iss` query parameter.
+    pub iss: String,
+
+    /// The name of the provider.
+    pub name: String,
+
+    /// True if the provider is used when none is asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+}
+
+/// Represents the RFC 9560 session of a logged in end-user.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct Session {
+    /// The claims of the end-user given by the OpenID provider.
+    #[serde(rename = "userClaims")]
+    pub user_claims: Map<String, Value>,
+
+    /// The state of the tokens of the session.
+    #[serde(rename = "sessionInfo")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_info: Option<SessionInfo>,
+}
+
+/// Represents the state of the tokens of a session.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct SessionInfo {
+    /// The number of seconds until the access token of the session expires.
+    #[serde(rename = "tokenExpiration")]
+    pub token_expiration: u64,
+
+    /// True if the session may be refreshed.
+    #[serde(rename = "tokenRefresh")]
+    pub token_refresh: bool,
+}
+
+/// Represents the response of the RFC 9560 login, session and logout paths.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub common: Common,
+
+    #[serde(rename = "roidc1_session")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<Session>,
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use super::SessionResponse;
+
+    #[test]
+    fn GIVEN_login_response_WHEN_deserialize_THEN_session_parsed() {
+        // GIVEN
+        let json = r#"
+            {
+              "rdapConformance": ["rdap_level_0", "roidc1"],
+              "notices": [
+                {
+                  "title": "Login Result",
+                  "description": ["Login succeeded"]
+                }
+              ],
+              "roidc1_session": {
+                "userClaims": {
+                  "sub": "103892603076825016132",
+                  "name": "User Person"
+                },
+                "sessionInfo": {
+                  "tokenExpiration": 3599,
+                  "tokenRefresh": true
+                }
+              }
+            }
+        "#;
+
+        // WHEN
+        let actual = serde_json::from_str::<SessionResponse>(json);
+
+        // THEN
+        let actual = actual.expect("parsing session response");
+        let session = actual.session.expect("no session");
+        assert_eq!(session.user_claims["name"], "User Person");
+        let session_info = session.session_info.expect("no session info");
+        assert_eq!(session_info.token_expiration, 3599);
+        assert!(session_info.token_refresh);
+    }
+}
@@ -1,7 +1,12 @@
 use buildstructor::Builder;
 use serde::{Deserialize, Serialize};
 
-use super::{domain::Domain, entity::Entity, nameserver::Nameserver, types::Common};
+use super::{
+    domain::Domain,
+    entity::Entity,
+    nameserver::Nameserver,
+    types::{Common, Links},
+};
 
 /// Represents RDAP domain search results.
 #[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
@@ -9,6 +14,18 @@ pub struct DomainSearchResults {
     #[serde(flatten)]
     pub common: Common,
 
+    #[serde(rename = "paging_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paging_metadata: Option<PagingMetadata>,
+
+    #[serde(rename = "sorting_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sorting_metadata: Option<SortingMetadata>,
+
+    #[serde(rename = "subsetting_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsetting_metadata: Option<SubsettingMetadata>,
+
     #[serde(rename = "domainSearchResults")]
     pub results: Vec<Domain>,
 }
@@ -19,6 +36,9 @@ impl DomainSearchResults {
     pub fn new_empty() -> Self {
         Self {
             common: Common::builder().build(),
+            paging_metadata: None,
+            sorting_metadata: None,
+            subsetting_metadata: None,
             results: Vec::new(),
         }
     }
@@ -30,6 +50,18 @@ pub struct NameserverSearchResults {
     #[serde(flatten)]
     pub common: Common,
 
+    #[serde(rename = "paging_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paging_metadata: Option<PagingMetadata>,
+
+    #[serde(rename = "sorting_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sorting_metadata: Option<SortingMetadata>,
+
+    #[serde(rename = "subsetting_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsetting_metadata: Option<SubsettingMetadata>,
+
     #[serde(rename = "nameserverSearchResults")]
     pub results: Vec<Nameserver>,
 }
@@ -40,6 +72,9 @@ impl NameserverSearchResults {
     pub fn new_empty() -> Self {
         Self {
             common: Common::builder().build(),
+            paging_metadata: None,
+            sorting_metadata: None,
+            subsetting_metadata: None,
             results: Vec::new(),
         }
     }
@@ -51,6 +86,18 @@ pub struct EntitySearchResults {
     #[serde(flatten)]
     pub common: Common,
 
+    #[serde(rename = "paging_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paging_metadata: Option<PagingMetadata>,
+
+    #[serde(rename = "sorting_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sorting_metadata: Option<SortingMetadata>,
+
+    #[serde(rename = "subsetting_metadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsetting_metadata: Option<SubsettingMetadata>,
+
     #[serde(rename = "entitySearchResults")]
     pub results: Vec<Entity>,
 }
@@ -61,7 +108,228 @@ impl EntitySearchResults {
     pub fn new_empty() -> Self {
         Self {
             common: Common::builder().build(),
+            paging_metadata: None,
+            sorting_metadata: None,
+            subsetting_metadata: None,
             results: Vec::new(),
         }
     }
 }
+
+/// Represents the RFC 8977 paging metadata of search results.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct PagingMetadata {
+    /// The total number of objects found by the search, given when asked for with `count=true`.
+    #[serde(rename = "totalCount")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<u64>,
+
+    /// The number of objects in each page.
+    #[serde(rename = "pageSize")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u64>,
+
+    /// The number of this page, starting at 1.
+    #[serde(rename = "pageNumber")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_number: Option<u64>,
+
+    /// Links to other pages. The link to the next page has the relationship `next`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Links>,
+}
+
+impl PagingMetadata {
+    /// Gets the URL of the next page, if there is one.
+    pub fn next_href(&self) -> Option<&str> {
+        self.links
+            .iter()
+            .flatten()
+            .find(|link| link.is_relation("next"))
+            .map(|link| link.href.as_str())
+    }
+}
+
+/// Represents the RFC 8977 sorting metadata of search results.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct SortingMetadata {
+    /// The sort applied to the results, such as `name:d`.
+    #[serde(rename = "currentSort")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_sort: Option<String>,
+
+    /// The sorts that may be asked for.
+    #[serde(rename = "availableSorts")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_sorts: Option<Vec<AvailableSort>>,
+}
+
+/// Represents a sort that may be asked for in RFC 8977 sorting metadata.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct AvailableSort {
+    /// The name of the sorting property.
+    pub property: String,
+
+    /// The JSONPath of the value sorted on.
+    #[serde(rename = "jsonPath")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+
+    /// True if the results are sorted on this property when no sort is asked for.
+    pub default: bool,
+
+    /// Links to the search results sorted on this property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Links>,
+}
+
+/// Represents the RFC 8982 subsetting metadata of search results.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct SubsettingMetadata {
+    /// The name of the field set of the results.
+    #[serde(rename = "currentFieldSet")]
+    pub current_field_set: String,
+
+    /// The field sets that may be asked for.
+    #[serde(rename = "availableFieldSets")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_field_sets: Option<Vec<AvailableFieldSet>>,
+}
+
+/// Represents a field set that may be asked for in RFC 8982 subsetting metadata.
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug, Eq)]
+pub struct AvailableFieldSet {
+    /// The name of the field set.
+    pub name: String,
+
+    /// True if the results are given with this field set when none is asked for.
+    pub default: bool,
+
+    /// A description of the fields of the field set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Links to the search results with this field set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Links>,
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+
+    use super::DomainSearchResults;
+
+    #[test]
+    fn GIVEN_paged_and_sorted_results_WHEN_deserialize_THEN_metadata_parsed() {
+        // GIVEN
+        let json = r#"
+            {
+              "rdapConformance": ["rdap_level_0", "paging", "sorting"],
+              "sorting_metadata": {
+                "currentSort": "name:d",
+                "availableSorts": [
+                  {
+                    "property": "name",
+                    "jsonPath": "$.domainSearchResults[*].unicodeName",
+                    "default": true,
+                    "links": [
+                      {
+                        "value": "https://example.com/rdap/domains?name=*.example",
+                        "rel": "alternate",
+                        "href": "https://example.com/rdap/domains?name=*.example&sort=name",
+                        "type": "application/rdap+json"
+                      }
+                    ]
+                  }
+                ]
+              },
+              "paging_metadata": {
+                "totalCount": 43,
+                "pageSize": 2,
+                "pageNumber": 1,
+                "links": [
+                  {
+                    "value": "https://example.com/rdap/domains?name=*.example",
+                    "rel": "next",
+                    "href": "https://example.com/rdap/domains?name=*.example&cursor=b2Zmc2V0PTI",
+                    "type": "application/rdap+json"
+                  }
+                ]
+              },
+              "domainSearchResults": []
+            }
+        "#;
+
+        // WHEN
+        let actual = serde_json::from_str::<DomainSearchResults>(json);
+
+        // THEN
+        let actual = actual.expect("parsing search results");
+        let paging = actual.paging_metadata.expect("no paging metadata");
+        assert_eq!(paging.total_count, Some(43));
+        assert_eq!(paging.page_size, Some(2));
+        assert_eq!(
+            paging.next_href(),
+            Some("https://example.com/rdap/domains?name=*.example&cursor=b2Zmc2V0PTI")
+        );
+        let sorting = actual.sorting_metadata.expect("no sorting metadata");
+        assert_eq!(sorting.current_sort.as_deref(), Some("name:d"));
+        assert_eq!(
+            sorting.available_sorts.expect("no available sorts").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn GIVEN_subsetted_results_WHEN_deserialize_THEN_metadata_parsed() {
+        // GIVEN
+        let json = r#"
+            {
+              "rdapConformance": ["rdap_level_0", "subsetting"],
+              "subsetting_metadata": {
+                "currentFieldSet": "id",
+                "availableFieldSets": [
+                  {
+                    "name": "id",
+                    "default": false,
+                    "description": "Only the key fields of each object.",
+                    "links": [
+                      {
+                        "value": "https://example.com/rdap/domains?name=*.example",
+                        "rel": "alternate",
+                        "href": "https://example.com/rdap/domains?name=*.example&fieldSet=id",
+                        "type": "application/rdap+json"
+                      }
+                    ]
+                  },
+                  {
+                    "name": "full",
+                    "default": true
+                  }
+                ]
+              },
+              "domainSearchResults": [
+                {
+                  "objectClassName": "domain",
+                  "ldhName": "foo.example"
+                }
+              ]
+            }
+        "#;
+
+        // WHEN
+        let actual = serde_json::from_str::<DomainSearchResults>(json);
+
+        // THEN
+        let actual = actual.expect("parsing search results");
+        let subsetting = actual.subsetting_metadata.expect("no subsetting metadata");
+        assert_eq!(subsetting.current_field_set, "id");
+        let available = subsetting
+            .available_field_sets
+            .expect("no available field sets");
+        assert_eq!(available.len(), 2);
+        assert!(available[1].default);
+        assert_eq!(actual.results.len(), 1);
+    }
+}
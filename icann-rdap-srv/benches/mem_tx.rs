@@ -0,0 +1,87 @@
+//! Benchmarks of updates to the in-memory store as it holds more objects.
+//!
+//! Run with `cargo bench -p icann-rdap-srv --bench mem_tx`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use icann_rdap_common::response::{domain::Domain, nameserver::Nameserver, network::Network};
+use icann_rdap_srv::storage::{mem::ops::Mem, StoreOps};
+use tokio::runtime::Runtime;
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+
+fn domain(n: usize) -> Domain {
+    Domain::basic()
+        .ldh_name(format!("domain-{n}.example"))
+        .nameservers(vec![Nameserver::basic()
+            .ldh_name(format!("ns-{}.example", n % 1000))
+            .build()
+            .expect("building nameserver")])
+        .build()
+}
+
+/// Creates a store holding the number of domains, and a network for every thousand of them.
+async fn filled_mem(size: usize) -> Mem {
+    let mem = Mem::default();
+    let mut tx = mem.new_truncate_tx().await.expect("new truncate tx");
+    for n in 0..size {
+        tx.add_domain(&domain(n)).await.expect("adding domain");
+    }
+    for n in 0..size / 1000 {
+        let network = Network::basic()
+            .cidr(format!("10.{}.{}.0/24", n / 256 % 256, n % 256))
+            .build()
+            .expect("building network");
+        tx.add_network(&network).await.expect("adding network");
+    }
+    tx.commit().await.expect("committing");
+    mem
+}
+
+fn update_domain(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("building runtime");
+    let mut group = c.benchmark_group("update_domain");
+    for size in SIZES {
+        let mem = runtime.block_on(filled_mem(size));
+        let mut n = 0;
+        group.bench_with_input(BenchmarkId::from_parameter(size), &mem, |b, mem| {
+            b.to_async(&runtime).iter(|| {
+                // replace an existing domain so the store stays the same size.
+                n = (n + 1) % size;
+                let domain = domain(n);
+                async move {
+                    let mut tx = mem.new_tx().await.expect("new tx");
+                    tx.add_domain(&domain).await.expect("adding domain");
+                    tx.commit().await.expect("committing");
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn update_network(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("building runtime");
+    let mut group = c.benchmark_group("update_network");
+    for size in SIZES {
+        let mem = runtime.block_on(filled_mem(size));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &mem, |b, mem| {
+            b.to_async(&runtime).iter(|| async {
+                let network = Network::basic()
+                    .cidr("192.0.2.0/24")
+                    .build()
+                    .expect("building network");
+                let mut tx = mem.new_tx().await.expect("new tx");
+                tx.add_network(&network).await.expect("adding network");
+                tx.commit().await.expect("committing");
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(50);
+    targets = update_domain, update_network
+}
+criterion_main!(benches);
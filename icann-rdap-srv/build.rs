@@ -0,0 +1,4 @@
+fn main() {
+    // the PostgreSQL schema migrations are embedded at compile time.
+    println!("cargo:rerun-if-changed=src/storage/pg/migrations");
+}
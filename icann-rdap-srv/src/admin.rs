@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::{from_fn_with_state, Next},
+    response::{IntoResponse, Response},
+    routing::{post, put},
+    Json, Router,
+};
+use buildstructor::Builder;
+use headers::{authorization::Bearer, Authorization, HeaderMapExt};
+use http::{header::WWW_AUTHENTICATE, HeaderValue, StatusCode};
+use icann_rdap_common::response::{error::Error, types::Common, RdapResponse};
+use serde_json::Value;
+
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::sha256_hex,
+        response::{ResponseUtil, NOT_IMPLEMENTED, UNAUTHORIZED},
+    },
+    server::DynServiceState,
+    storage::{
+        data::{
+            add_template, delete_ids, spec_errors, AutnumOrError, DomainOrError, EntityOrError,
+            NameserverOrError, NetworkOrError, ObjectIds, Template,
+        },
+        watch::reload_data,
+    },
+};
+
+/// The admin API, which changes the data in storage while the server is running.
+/// Callers must give the admin token as a bearer token.
+///
+/// Changes are not written to the data directory, so they last until the data is next
+/// reloaded. They are refused when the data directory is reloaded automatically, as any
+/// change to its files could then undo them.
+#[derive(Debug, Clone, Builder)]
+pub struct Admin {
+    /// The hex encoded SHA-256 digest of the admin token.
+    token_sha256: String,
+
+    /// The data directory, which is reloaded by a full reload.
+    data_dir: String,
+
+    /// If true, the data directory is reloaded automatically and changes are refused.
+    auto_reload: bool,
+}
+
+impl Admin {
+    fn is_admin(&self, headers: &http::HeaderMap) -> bool {
+        headers
+            .typed_get::<Authorization<Bearer>>()
+            .is_some_and(|Authorization(bearer)| {
+                sha256_hex(bearer.token()) == self.token_sha256.to_ascii_lowercase()
+            })
+    }
+}
+
+pub(crate) fn admin_router(admin: Admin) -> Router<DynServiceState> {
+    Router::new()
+        .route("/objects", put(put_object).delete(delete_objects))
+        .route("/redirects", put(put_redirect).delete(delete_objects))
+        .route("/reload", post(reload))
+        .route_layer(from_fn_with_state(Arc::new(admin), authorize))
+}
+
+/// Gives a 401 to callers not giving the admin token.
+async fn authorize(State(admin): State<Arc<Admin>>, request: Request, next: Next) -> Response {
+    if admin.is_admin(request.headers()) {
+        return next.run(request).await;
+    }
+    tracing::debug!("admin token not given");
+    let mut response = UNAUTHORIZED.response();
+    response.headers_mut().insert(
+        WWW_AUTHENTICATE,
+        HeaderValue::from_static(r#"Bearer realm="rdap-admin""#),
+    );
+    response
+}
+
+/// Gives a 400 response describing why the payload of a call is rejected.
+fn bad_request(title: &str, description: Vec<String>) -> Response {
+    error_response(400, title, description)
+}
+
+/// Gives a 409 response to calls making changes when the data directory is reloaded
+/// automatically, if it is.
+fn refuse_changes(state: &DynServiceState) -> Option<Response> {
+    state
+        .get_admin()
+        .filter(|admin| admin.auto_reload)
+        .map(|_| {
+            error_response(
+                409,
+                "Data is reloaded automatically",
+                vec!["Change the files of the data directory instead.".to_string()],
+            )
+        })
+}
+
+fn error_response(error_code: u16, title: &str, description: Vec<String>) -> Response {
+    RdapResponse::ErrorResponse(
+        Error::builder()
+            .error_code(error_code)
+            .title(title)
+            .description(description)
+            .common(Common::builder().build())
+            .build(),
+    )
+    .response()
+}
+
+/// Adds or replaces an RDAP object, which is a domain, entity, nameserver, autnum or IP network.
+async fn put_object(
+    state: State<DynServiceState>,
+    Json(json): Json<Value>,
+) -> Result<Response, RdapServerError> {
+    if let Some(response) = refuse_changes(&state) {
+        return Ok(response);
+    }
+    let Ok(rdap) = RdapResponse::try_from(json) else {
+        return Ok(bad_request("Not an RDAP object", vec![]));
+    };
+    let errors = spec_errors(&rdap);
+    if !errors.is_empty() {
+        return Ok(bad_request("RDAP object does not pass checks", errors));
+    }
+    let store = state.get_storage().await?;
+    let mut tx = store.new_tx().await?;
+    match &rdap {
+        RdapResponse::Domain(domain) => tx.add_domain(domain).await?,
+        RdapResponse::Entity(entity) => tx.add_entity(entity).await?,
+        RdapResponse::Nameserver(nameserver) => tx.add_nameserver(nameserver).await?,
+        RdapResponse::Autnum(autnum) => tx.add_autnum(autnum).await?,
+        RdapResponse::Network(network) => tx.add_network(network).await?,
+        _ => {
+            tx.rollback().await?;
+            return Ok(bad_request("Not an RDAP object", vec![]));
+        }
+    };
+    tx.commit().await?;
+    state.get_health().count_objects(store).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Adds or replaces the redirects given by a template of an error, such as:
+///
+/// ```json
+/// {
+///   "domain": { "error": { "errorCode": 307, "notices": [ ... ] } },
+///   "ids": [ { "ldhName": "example" } ]
+/// }
+/// ```
+async fn put_redirect(
+    state: State<DynServiceState>,
+    Json(json): Json<Value>,
+) -> Result<Response, RdapServerError> {
+    if let Some(response) = refuse_changes(&state) {
+        return Ok(response);
+    }
+    let Ok(template) = serde_json::from_value::<Template>(json) else {
+        return Ok(bad_request("Not a redirect template", vec![]));
+    };
+    let error = match &template {
+        Template::Domain {
+            domain: DomainOrError::ErrorResponse(error),
+            ..
+        }
+        | Template::Entity {
+            entity: EntityOrError::ErrorResponse(error),
+            ..
+        }
+        | Template::Nameserver {
+            nameserver: NameserverOrError::ErrorResponse(error),
+            ..
+        }
+        | Template::Autnum {
+            autnum: AutnumOrError::ErrorResponse(error),
+            ..
+        }
+        | Template::Network {
+            network: NetworkOrError::ErrorResponse(error),
+            ..
+        } => error,
+        _ => return Ok(bad_request("Not a redirect template", vec![])),
+    };
+    let errors = spec_errors(&RdapResponse::ErrorResponse(error.clone()));
+    if !errors.is_empty() {
+        return Ok(bad_request("Redirect does not pass checks", errors));
+    }
+    let store = state.get_storage().await?;
+    let mut tx = store.new_tx().await?;
+    add_template(template, &mut tx).await?;
+    tx.commit().await?;
+    state.get_health().count_objects(store).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Deletes the objects or redirects with the IDs given in the same form as a tombstone file:
+///
+/// ```json
+/// {
+///   "domains": [ { "ldhName": "foo.example" } ],
+///   "autnums": [ { "startAutnum": 64496, "endAutnum": 64511 } ]
+/// }
+/// ```
+async fn delete_objects(
+    state: State<DynServiceState>,
+    Json(json): Json<Value>,
+) -> Result<Response, RdapServerError> {
+    if let Some(response) = refuse_changes(&state) {
+        return Ok(response);
+    }
+    let Ok(ids) = serde_json::from_value::<ObjectIds>(json) else {
+        return Ok(bad_request("Not object IDs", vec![]));
+    };
+    let store = state.get_storage().await?;
+    let mut tx = store.new_tx().await?;
+    delete_ids(&ids, &mut tx).await?;
+    tx.commit().await?;
+    state.get_health().count_objects(store).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Reloads all the data of the data directory, undoing the changes made by the admin API,
+/// and responds once it is reloaded.
+async fn reload(state: State<DynServiceState>) -> Result<Response, RdapServerError> {
+    let Some(admin) = state.get_admin() else {
+        return Ok(NOT_IMPLEMENTED.response());
+    };
+    let store = state.get_storage().await?;
+    reload_data(&admin.data_dir, store, state.get_health()).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -1,27 +1,36 @@
-use std::{net::IpAddr, path::PathBuf};
+use std::{
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use icann_rdap_common::{check::CheckClass, response::RdapResponse, VERSION};
 use icann_rdap_srv::{
-    config::{data_dir, debug_config_vars, LOG},
+    config::{data_dir, debug_config_vars, StorageType, LOG},
     error::RdapServerError,
-    storage::data::{
-        trigger_reload, trigger_update, AutnumOrError, DomainOrError, EntityOrError,
-        NameserverOrError, NetworkIdType, NetworkOrError, Template,
+    storage::{
+        data::{
+            trigger_reload, trigger_update, AutnumOrError, DomainOrError, EntityOrError,
+            NameserverOrError, NetworkIdType, NetworkOrError, Template,
+        },
+        pg::ops::Pg,
     },
     util::bin::check::{check_rdap, to_check_classes, CheckArgs},
 };
 use ipnet::IpNet;
 use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tracing::{debug, error, warn};
 use tracing_subscriber::{
     fmt, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, EnvFilter,
 };
 
 #[derive(Parser, Debug)]
-#[command(author, version = VERSION, about, long_about)]
+#[command(author, version = VERSION, about, long_about, args_conflicts_with_subcommands = true)]
 /// This program moves RDAP files into storage. Files are checked for validity
 /// before moving them.
+///
+/// It can also apply and show the schema migrations of PostgreSQL storage.
 struct Cli {
     /// Directory containg RDAP JSON files.
     #[arg()]
@@ -41,6 +50,18 @@ struct Cli {
     /// If true, storage is completely reloaded.
     #[arg(long, required = false, conflicts_with = "update")]
     reload: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Applies any pending schema migrations to PostgreSQL storage.
+    Migrate,
+
+    /// Shows the status of the schema migrations of PostgreSQL storage.
+    Status,
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -54,6 +75,10 @@ async fn main() -> Result<(), RdapServerError> {
 
     debug_config_vars();
 
+    if let Some(command) = cli.command {
+        return do_migration_command(command).await;
+    }
+
     let check_types = to_check_classes(&cli.check_args);
 
     let data_dir = data_dir();
@@ -77,6 +102,34 @@ async fn main() -> Result<(), RdapServerError> {
     Ok(())
 }
 
+async fn do_migration_command(command: Commands) -> Result<(), RdapServerError> {
+    let StorageType::Postgres(pg_config) = StorageType::new_from_env()? else {
+        return Err(RdapServerError::InvalidArg(
+            "Schema migrations are only used with postgres storage.".to_string(),
+        ));
+    };
+    let pg = Pg::new(pg_config).await?;
+    match command {
+        Commands::Migrate => pg.migrate().await?,
+        Commands::Status => {
+            for status in pg.migration_status().await? {
+                let installed_on = status
+                    .installed_on
+                    .map(|installed_on| installed_on.to_rfc3339())
+                    .unwrap_or_default();
+                let line = format!(
+                    "{:>6}  {:<8}  {:<32}  {installed_on}",
+                    status.version,
+                    status.state.to_string(),
+                    status.description,
+                );
+                println!("{}", line.trim_end());
+            }
+        }
+    };
+    Ok(())
+}
+
 async fn do_validate_then_move(
     directory: &str,
     check_types: &[CheckClass],
@@ -98,6 +151,10 @@ async fn do_validate_then_move(
     let mut errors_found = false;
     while let Some(entry) = entries.next_entry().await? {
         let entry = entry.path();
+        if entry.extension().is_some_and(|ext| ext == "ndjson") {
+            errors_found |= verify_ndjson(&entry, check_types).await?;
+            continue;
+        }
         let contents = tokio::fs::read_to_string(&entry).await?;
         if entry.extension().map_or(false, |ext| ext == "template") {
             errors_found |= verify_rdap_template(&contents, &entry.to_string_lossy(), check_types)?;
@@ -156,6 +213,30 @@ fn verify_rdap(
     Ok(errors_found)
 }
 
+/// Verifies each line of the NDJSON files, which is either an RDAP object or a template.
+async fn verify_ndjson(path: &Path, check_types: &[CheckClass]) -> Result<bool, RdapServerError> {
+    let mut errors_found = false;
+    let path_name = path.to_string_lossy();
+    debug!("verifying {path_name}");
+    let mut lines = BufReader::new(tokio::fs::File::open(path).await?).lines();
+    let mut line_number = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_name = format!("{path_name}:{line_number}");
+        let is_template =
+            serde_json::from_str::<Value>(&line).is_ok_and(|value| value.get("ids").is_some());
+        errors_found |= if is_template {
+            verify_rdap_template(&line, &line_name, check_types)?
+        } else {
+            verify_rdap(&line, &line_name, check_types)?
+        };
+    }
+    Ok(errors_found)
+}
+
 /// Verifies the template files.
 fn verify_rdap_template(
     contents: &str,
@@ -1,10 +1,14 @@
-use envmnt::{get_or, get_parse_or, get_u16};
+use envmnt::{exists, get_or, get_parse_or, get_u16};
 use icann_rdap_srv::{
     config::{
-        data_dir, debug_config_vars, ListenConfig, ServiceConfig, StorageType, AUTO_RELOAD,
-        BOOTSTRAP, LISTEN_ADDR, LISTEN_PORT, LOG, UPDATE_ON_BOOTSTRAP,
+        data_dir, debug_config_vars, CacheConfig, ListenConfig, RateLimitConfig, ServiceConfig,
+        StorageType, TlsConfig, ADMIN_TOKEN_SHA256, AUTO_RELOAD, BOOTSTRAP, CREDENTIALS_FILE,
+        DEFAULT_MAX_SEARCH_RESULTS, DEFAULT_SEARCH_PAGE_SIZE, LISTEN_ADDR, LISTEN_PORT, LOG,
+        MAX_SEARCH_RESULTS, METRICS_LISTEN_ADDR, METRICS_LISTEN_PORT, OPENIDC_FILE,
+        REDACTION_POLICY_FILE, SEARCH_PAGE_SIZE, UPDATE_ON_BOOTSTRAP,
     },
     error::RdapServerError,
+    metrics::MetricsListener,
     server::Listener,
 };
 use tracing_subscriber::{
@@ -21,17 +25,40 @@ async fn main() -> Result<(), RdapServerError> {
 
     debug_config_vars();
 
+    let data_dir = data_dir();
     let listen_addr = get_or(LISTEN_ADDR, "127.0.0.1");
     let listen_port = get_u16(LISTEN_PORT, 3000);
+    let tls = TlsConfig::new_from_env()?;
     let storage_type = StorageType::new_from_env()?;
     let auto_reload: bool = get_parse_or(AUTO_RELOAD, true)?;
     let bootstrap: bool = get_parse_or(BOOTSTRAP, false)?;
     let update_on_bootstrap: bool = get_parse_or(UPDATE_ON_BOOTSTRAP, false)?;
+    let max_search_results: usize = get_parse_or(MAX_SEARCH_RESULTS, DEFAULT_MAX_SEARCH_RESULTS)?;
+    let search_page_size: usize = get_parse_or(SEARCH_PAGE_SIZE, DEFAULT_SEARCH_PAGE_SIZE)?;
+    let rate_limit = RateLimitConfig::new_from_env()?;
+    let cache = CacheConfig::new_from_env()?;
+    let credentials_file = exists(CREDENTIALS_FILE).then(|| get_or(CREDENTIALS_FILE, ""));
+    let redaction_policy_file =
+        exists(REDACTION_POLICY_FILE).then(|| get_or(REDACTION_POLICY_FILE, ""));
+    let openidc_file = exists(OPENIDC_FILE).then(|| get_or(OPENIDC_FILE, ""));
+    let admin_token_sha256 = exists(ADMIN_TOKEN_SHA256).then(|| get_or(ADMIN_TOKEN_SHA256, ""));
+
+    if exists(METRICS_LISTEN_PORT) {
+        let metrics_listener = MetricsListener::listen(
+            &ListenConfig::builder()
+                .ip_addr(get_or(METRICS_LISTEN_ADDR, "127.0.0.1"))
+                .port(get_u16(METRICS_LISTEN_PORT, 0))
+                .build(),
+        )
+        .await?;
+        tokio::spawn(metrics_listener.start());
+    }
 
     let listener = Listener::listen(
         &ListenConfig::builder()
             .ip_addr(listen_addr)
             .port(listen_port)
+            .and_tls(tls)
             .build(),
     )
     .await?;
@@ -39,10 +66,18 @@ async fn main() -> Result<(), RdapServerError> {
         .start_server(
             &ServiceConfig::builder()
                 .storage_type(storage_type)
-                .data_dir(data_dir())
+                .data_dir(data_dir)
                 .auto_reload(auto_reload)
                 .bootstrap(bootstrap)
                 .update_on_bootstrap(update_on_bootstrap)
+                .max_search_results(max_search_results)
+                .search_page_size(search_page_size)
+                .rate_limit(rate_limit)
+                .cache(cache)
+                .and_credentials_file(credentials_file)
+                .and_redaction_policy_file(redaction_policy_file)
+                .and_openidc_file(openidc_file)
+                .and_admin_token_sha256(admin_token_sha256)
                 .build(),
         )
         .await?;
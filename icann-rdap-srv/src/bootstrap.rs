@@ -16,6 +16,8 @@ use tracing::{debug, info};
 use crate::{
     config::ServiceConfig,
     error::RdapServerError,
+    health::Health,
+    metrics::record_bootstrap_fetch,
     storage::data::{
         trigger_reload, trigger_update, AutnumId, AutnumOrError, DomainId, DomainOrError, EntityId,
         EntityOrError, NetworkId, NetworkIdType, NetworkOrError, Template,
@@ -24,7 +26,10 @@ use crate::{
 
 const IANA_JSON_SUFFIX: &str = ".iana_cache";
 
-pub async fn init_bootstrap(config: &ServiceConfig) -> Result<(), RdapServerError> {
+pub async fn init_bootstrap(
+    config: &ServiceConfig,
+    health: &Health,
+) -> Result<(), RdapServerError> {
     if config.bootstrap {
         info!("Initializing IANA Bootstrap.");
         let client_config = ClientConfig::builder()
@@ -34,6 +39,7 @@ pub async fn init_bootstrap(config: &ServiceConfig) -> Result<(), RdapServerErro
 
         // do one run of the bootstrapping before starting the thread.
         process_bootstrap(config, &client).await?;
+        health.set_bootstrapped();
 
         // spawn bootstrap thread
         tokio::spawn(loop_bootstrap(config.clone(), client));
@@ -310,6 +316,17 @@ async fn fetch_iana_registry(
     reg_type: IanaRegistryType,
     client: &Client,
     data_dir: &str,
+) -> Result<Option<IanaRegistry>, RdapServerError> {
+    let registry = reg_type.file_name().to_string();
+    let result = get_iana_registry(reg_type, client, data_dir).await;
+    record_bootstrap_fetch(&registry, &result);
+    result
+}
+
+async fn get_iana_registry(
+    reg_type: IanaRegistryType,
+    client: &Client,
+    data_dir: &str,
 ) -> Result<Option<IanaRegistry>, RdapServerError> {
     let file_name = format!("{}{IANA_JSON_SUFFIX}", reg_type.file_name());
     let path: PathBuf = [data_dir, (file_name.as_str())].iter().collect();
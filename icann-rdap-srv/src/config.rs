@@ -1,11 +1,12 @@
+use axum_client_ip::SecureClientIpSource;
 use buildstructor::Builder;
-use envmnt::get_or;
+use envmnt::{get_or, get_parse_or};
 use strum_macros::Display;
 use tracing::debug;
 
 use crate::{
     error::RdapServerError,
-    storage::{mem::config::MemConfig, pg::config::PgConfig},
+    storage::{mem::config::MemConfig, pg::config::PgConfig, sqlite::config::SqliteConfig},
 };
 
 pub const LOG: &str = "RDAP_SRV_LOG";
@@ -14,9 +15,39 @@ pub const LISTEN_PORT: &str = "RDAP_SRV_LISTEN_PORT";
 pub const STORAGE: &str = "RDAP_SRV_STORAGE";
 pub const DB_URL: &str = "RDAP_SRV_DB_URL";
 pub const DATA_DIR: &str = "RDAP_SRV_DATA_DIR";
+pub const SNAPSHOT_FILE: &str = "RDAP_SRV_SNAPSHOT_FILE";
 pub const AUTO_RELOAD: &str = "RDAP_SRV_AUTO_RELOAD";
 pub const BOOTSTRAP: &str = "RDAP_SRV_BOOTSTRAP";
 pub const UPDATE_ON_BOOTSTRAP: &str = "RDAP_SRV_UPDATE_ON_BOOTSTRAP";
+pub const MAX_SEARCH_RESULTS: &str = "RDAP_SRV_MAX_SEARCH_RESULTS";
+pub const SEARCH_PAGE_SIZE: &str = "RDAP_SRV_SEARCH_PAGE_SIZE";
+pub const LOOKUP_RATE_LIMIT: &str = "RDAP_SRV_LOOKUP_RATE_LIMIT";
+pub const LOOKUP_RATE_BURST: &str = "RDAP_SRV_LOOKUP_RATE_BURST";
+pub const SEARCH_RATE_LIMIT: &str = "RDAP_SRV_SEARCH_RATE_LIMIT";
+pub const SEARCH_RATE_BURST: &str = "RDAP_SRV_SEARCH_RATE_BURST";
+pub const CLIENT_IP_SOURCE: &str = "RDAP_SRV_CLIENT_IP_SOURCE";
+pub const CREDENTIALS_FILE: &str = "RDAP_SRV_CREDENTIALS_FILE";
+pub const REDACTION_POLICY_FILE: &str = "RDAP_SRV_REDACTION_POLICY_FILE";
+pub const OPENIDC_FILE: &str = "RDAP_SRV_OPENIDC_FILE";
+pub const ADMIN_TOKEN_SHA256: &str = "RDAP_SRV_ADMIN_TOKEN_SHA256";
+pub const TLS_CERT: &str = "RDAP_SRV_TLS_CERT";
+pub const TLS_KEY: &str = "RDAP_SRV_TLS_KEY";
+pub const CACHE_MAX_AGE: &str = "RDAP_SRV_CACHE_MAX_AGE";
+pub const DOMAIN_CACHE_MAX_AGE: &str = "RDAP_SRV_DOMAIN_CACHE_MAX_AGE";
+pub const NAMESERVER_CACHE_MAX_AGE: &str = "RDAP_SRV_NAMESERVER_CACHE_MAX_AGE";
+pub const ENTITY_CACHE_MAX_AGE: &str = "RDAP_SRV_ENTITY_CACHE_MAX_AGE";
+pub const AUTNUM_CACHE_MAX_AGE: &str = "RDAP_SRV_AUTNUM_CACHE_MAX_AGE";
+pub const IP_CACHE_MAX_AGE: &str = "RDAP_SRV_IP_CACHE_MAX_AGE";
+pub const HELP_CACHE_MAX_AGE: &str = "RDAP_SRV_HELP_CACHE_MAX_AGE";
+pub const SEARCH_CACHE_MAX_AGE: &str = "RDAP_SRV_SEARCH_CACHE_MAX_AGE";
+pub const METRICS_LISTEN_ADDR: &str = "RDAP_SRV_METRICS_LISTEN_ADDR";
+pub const METRICS_LISTEN_PORT: &str = "RDAP_SRV_METRICS_LISTEN_PORT";
+
+/// The default maximum number of results returned by a search.
+pub const DEFAULT_MAX_SEARCH_RESULTS: usize = 100;
+
+/// The default number of search results in each page of search results.
+pub const DEFAULT_SEARCH_PAGE_SIZE: usize = 25;
 
 pub fn debug_config_vars() {
     let var_list = [
@@ -26,9 +57,33 @@ pub fn debug_config_vars() {
         STORAGE,
         DB_URL,
         DATA_DIR,
+        SNAPSHOT_FILE,
         AUTO_RELOAD,
         BOOTSTRAP,
         UPDATE_ON_BOOTSTRAP,
+        MAX_SEARCH_RESULTS,
+        SEARCH_PAGE_SIZE,
+        LOOKUP_RATE_LIMIT,
+        LOOKUP_RATE_BURST,
+        SEARCH_RATE_LIMIT,
+        SEARCH_RATE_BURST,
+        CLIENT_IP_SOURCE,
+        CREDENTIALS_FILE,
+        REDACTION_POLICY_FILE,
+        OPENIDC_FILE,
+        ADMIN_TOKEN_SHA256,
+        TLS_CERT,
+        TLS_KEY,
+        CACHE_MAX_AGE,
+        DOMAIN_CACHE_MAX_AGE,
+        NAMESERVER_CACHE_MAX_AGE,
+        ENTITY_CACHE_MAX_AGE,
+        AUTNUM_CACHE_MAX_AGE,
+        IP_CACHE_MAX_AGE,
+        HELP_CACHE_MAX_AGE,
+        SEARCH_CACHE_MAX_AGE,
+        METRICS_LISTEN_ADDR,
+        METRICS_LISTEN_PORT,
     ];
     envmnt::vars()
         .iter()
@@ -50,6 +105,35 @@ pub struct ListenConfig {
     /// If specified, determines the port number the server will bind to.
     /// If unspecified, the server let's the OS determine the port.
     pub port: Option<u16>,
+
+    /// If specified, the server uses HTTPS with the certificate and key.
+    /// If unspecified, the server uses HTTP.
+    pub tls: Option<TlsConfig>,
+}
+
+/// TLS certificate configuration. The certificate and key are reloaded when their files change.
+#[derive(Debug, Builder, Clone)]
+pub struct TlsConfig {
+    /// The path of the PEM file holding the certificate chain.
+    pub cert_file: String,
+
+    /// The path of the PEM file holding the private key.
+    pub key_file: String,
+}
+
+impl TlsConfig {
+    pub fn new_from_env() -> Result<Option<Self>, RdapServerError> {
+        match (envmnt::exists(TLS_CERT), envmnt::exists(TLS_KEY)) {
+            (true, true) => Ok(Some(Self {
+                cert_file: get_or(TLS_CERT, ""),
+                key_file: get_or(TLS_KEY, ""),
+            })),
+            (false, false) => Ok(None),
+            _ => Err(RdapServerError::Config(format!(
+                "both {TLS_CERT} and {TLS_KEY} must be given for TLS"
+            ))),
+        }
+    }
 }
 
 /// Determines the storage type.
@@ -61,16 +145,27 @@ pub enum StorageType {
 
     /// Uses a PostgreSQL database.
     Postgres(PgConfig),
+
+    /// Uses a SQLite database file.
+    Sqlite(SqliteConfig),
 }
 
 impl StorageType {
     pub fn new_from_env() -> Result<Self, RdapServerError> {
         let storage = get_or(STORAGE, "memory");
         let storage_type = if storage == "memory" {
-            StorageType::Memory(MemConfig::builder().build())
+            let snapshot_file = envmnt::exists(SNAPSHOT_FILE).then(|| get_or(SNAPSHOT_FILE, ""));
+            StorageType::Memory(
+                MemConfig::builder()
+                    .and_snapshot_file(snapshot_file)
+                    .build(),
+            )
         } else if storage == "postgres" {
             let db_url = get_or(DB_URL, "postgresql://127.0.0.1/rdap");
             StorageType::Postgres(PgConfig::builder().db_url(db_url).build())
+        } else if storage == "sqlite" {
+            let db_url = get_or(DB_URL, "sqlite://rdap.db");
+            StorageType::Sqlite(SqliteConfig::builder().db_url(db_url).build())
         } else {
             return Err(RdapServerError::Config(format!(
                 "storage type of '{storage}' is invalid"
@@ -80,6 +175,112 @@ impl StorageType {
     }
 }
 
+/// The rate at which a client may send requests to a class of routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of requests allowed each minute.
+    pub per_minute: u32,
+
+    /// The number of requests allowed at once before the client must slow down.
+    pub burst: u32,
+}
+
+impl RateLimit {
+    fn new_from_env(limit_var: &str, burst_var: &str) -> Result<Option<Self>, RdapServerError> {
+        let per_minute: u32 = get_parse_or(limit_var, 0)?;
+        if per_minute == 0 {
+            return Ok(None);
+        }
+        let burst: u32 = get_parse_or(burst_var, per_minute)?;
+        Ok(Some(Self {
+            per_minute,
+            burst: burst.max(1),
+        }))
+    }
+}
+
+/// Rate limiting configuration. Requests are limited for each client IP address,
+/// with lookups and searches limited separately. Routes without a rate limit are
+/// not limited.
+#[derive(Debug, Builder, Clone, Default)]
+pub struct RateLimitConfig {
+    /// The rate limit of lookups and help.
+    pub lookup: Option<RateLimit>,
+
+    /// The rate limit of searches.
+    pub search: Option<RateLimit>,
+
+    /// Where the IP address of the client is found. Defaults to the address of the connection,
+    /// which is the address of the proxy when the server is behind one.
+    pub client_ip_source: Option<SecureClientIpSource>,
+}
+
+impl RateLimitConfig {
+    pub fn new_from_env() -> Result<Self, RdapServerError> {
+        let lookup = RateLimit::new_from_env(LOOKUP_RATE_LIMIT, LOOKUP_RATE_BURST)?;
+        let search = RateLimit::new_from_env(SEARCH_RATE_LIMIT, SEARCH_RATE_BURST)?;
+        let source = get_or(CLIENT_IP_SOURCE, "ConnectInfo");
+        let client_ip_source = match source.as_str() {
+            "ConnectInfo" => SecureClientIpSource::ConnectInfo,
+            "RightmostForwarded" => SecureClientIpSource::RightmostForwarded,
+            "RightmostXForwardedFor" => SecureClientIpSource::RightmostXForwardedFor,
+            "XRealIp" => SecureClientIpSource::XRealIp,
+            "FlyClientIp" => SecureClientIpSource::FlyClientIp,
+            "TrueClientIp" => SecureClientIpSource::TrueClientIp,
+            "CfConnectingIp" => SecureClientIpSource::CfConnectingIp,
+            _ => {
+                return Err(RdapServerError::Config(format!(
+                    "client IP source of '{source}' is invalid"
+                )))
+            }
+        };
+        Ok(Self {
+            lookup,
+            search,
+            client_ip_source: Some(client_ip_source),
+        })
+    }
+}
+
+/// HTTP caching configuration, giving the `Cache-Control` max-age in seconds of
+/// successful responses of each object class. Responses of classes without a max-age
+/// are given no `Cache-Control` header.
+#[derive(Debug, Builder, Clone, Default)]
+pub struct CacheConfig {
+    pub domain: Option<u32>,
+    pub nameserver: Option<u32>,
+    pub entity: Option<u32>,
+    pub autnum: Option<u32>,
+    pub ip: Option<u32>,
+    pub help: Option<u32>,
+    pub search: Option<u32>,
+}
+
+impl CacheConfig {
+    /// Gets the max-age of each class from its own environment variable, or else from
+    /// the max-age given for all classes.
+    pub fn new_from_env() -> Result<Self, RdapServerError> {
+        let default = max_age_from_env(CACHE_MAX_AGE, None)?;
+        Ok(Self {
+            domain: max_age_from_env(DOMAIN_CACHE_MAX_AGE, default)?,
+            nameserver: max_age_from_env(NAMESERVER_CACHE_MAX_AGE, default)?,
+            entity: max_age_from_env(ENTITY_CACHE_MAX_AGE, default)?,
+            autnum: max_age_from_env(AUTNUM_CACHE_MAX_AGE, default)?,
+            ip: max_age_from_env(IP_CACHE_MAX_AGE, default)?,
+            help: max_age_from_env(HELP_CACHE_MAX_AGE, default)?,
+            search: max_age_from_env(SEARCH_CACHE_MAX_AGE, default)?,
+        })
+    }
+}
+
+fn max_age_from_env(var: &str, default: Option<u32>) -> Result<Option<u32>, RdapServerError> {
+    if envmnt::exists(var) {
+        Ok(Some(get_parse_or(var, 0)?))
+    } else {
+        Ok(default)
+    }
+}
+
 /// RDAP service configuration.
 #[derive(Debug, Builder, Clone)]
 pub struct ServiceConfig {
@@ -88,6 +289,26 @@ pub struct ServiceConfig {
     pub auto_reload: bool,
     pub bootstrap: bool,
     pub update_on_bootstrap: bool,
+    pub max_search_results: usize,
+    pub search_page_size: usize,
+    pub rate_limit: RateLimitConfig,
+    pub cache: CacheConfig,
+
+    /// If specified, the path of the credential file used to authenticate callers.
+    /// If unspecified, all callers are anonymous.
+    pub credentials_file: Option<String>,
+
+    /// If specified, the path of the redaction policy file used to redact domains and entities.
+    /// If unspecified, nothing is redacted.
+    pub redaction_policy_file: Option<String>,
+
+    /// If specified, the path of the OpenID Connect file used to log in end-users.
+    /// If unspecified, end-users cannot log in.
+    pub openidc_file: Option<String>,
+
+    /// If specified, the hex encoded SHA-256 digest of the token of the admin API.
+    /// If unspecified, the admin API is not available.
+    pub admin_token_sha256: Option<String>,
 }
 
 #[buildstructor::buildstructor]
@@ -108,6 +329,14 @@ impl ServiceConfig {
             auto_reload: false,
             bootstrap: false,
             update_on_bootstrap: false,
+            max_search_results: DEFAULT_MAX_SEARCH_RESULTS,
+            search_page_size: DEFAULT_SEARCH_PAGE_SIZE,
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            credentials_file: None,
+            redaction_policy_file: None,
+            openidc_file: None,
+            admin_token_sha256: None,
         })
     }
 }
@@ -28,6 +28,8 @@ pub enum RdapServerError {
     Config(String),
     #[error(transparent)]
     SqlDb(#[from] sqlx::Error),
+    #[error(transparent)]
+    Migrate(#[from] sqlx::migrate::MigrateError),
     #[error("index data for {0} is missing or empty")]
     EmptyIndexData(String),
     #[error("file at {0} is not JSON")]
@@ -58,6 +60,8 @@ pub enum RdapServerError {
     Iana(#[from] IanaResponseError),
     #[error("Bootstrap error: {0}")]
     Bootstrap(String),
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
 }
 
 impl IntoResponse for RdapServerError {
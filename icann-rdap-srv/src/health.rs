@@ -0,0 +1,119 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock,
+};
+
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use http::StatusCode;
+use serde::Serialize;
+
+use crate::{
+    error::RdapServerError,
+    metrics::record_object_counts,
+    server::DynServiceState,
+    storage::{ObjectCounts, StoreOps},
+};
+
+/// Tracks the progress of loading data and bootstrapping, which is reported
+/// by the readiness check.
+#[derive(Debug, Clone, Default)]
+pub struct Health {
+    last_reload: Arc<RwLock<Option<DateTime<Utc>>>>,
+    object_counts: Arc<RwLock<Option<ObjectCounts>>>,
+    bootstrapped: Arc<AtomicBool>,
+}
+
+impl Health {
+    /// Records that data has been loaded into storage. Servers started with
+    /// [crate::server::Listener::start_with_state] that load their own data should call this
+    /// once it is loaded, otherwise they are never ready.
+    pub fn set_data_loaded(&self) {
+        *self.last_reload.write().expect("health lock poisoned") = Some(Utc::now());
+    }
+
+    /// Records the number of objects of each class in storage, which is reported by the
+    /// readiness check so that it need not count them.
+    pub fn set_object_counts(&self, object_counts: ObjectCounts) {
+        *self.object_counts.write().expect("health lock poisoned") = Some(object_counts);
+    }
+
+    /// Counts the objects in storage after it has been changed, for the readiness check
+    /// and the metrics.
+    pub(crate) async fn count_objects(&self, store: &dyn StoreOps) -> Result<(), RdapServerError> {
+        self.set_object_counts(record_object_counts(store).await?);
+        Ok(())
+    }
+
+    /// Records that the first bootstrap has finished.
+    pub fn set_bootstrapped(&self) {
+        self.bootstrapped.store(true, Ordering::Relaxed);
+    }
+
+    fn last_reload(&self) -> Option<DateTime<Utc>> {
+        *self.last_reload.read().expect("health lock poisoned")
+    }
+
+    fn object_counts(&self) -> Option<ObjectCounts> {
+        *self.object_counts.read().expect("health lock poisoned")
+    }
+
+    fn bootstrapped(&self) -> bool {
+        self.bootstrapped.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    ready: bool,
+    data_loaded: bool,
+    storage_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bootstrapped: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_reload: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object_counts: Option<ObjectCounts>,
+}
+
+pub(crate) fn health_router() -> Router<DynServiceState> {
+    Router::new()
+        .route("/health/live", get(live))
+        .route("/health/ready", get(ready))
+}
+
+/// Passes whenever the server is answering requests.
+async fn live() -> Response {
+    Json(serde_json::json!({ "live": true })).into_response()
+}
+
+/// Passes once data has been loaded and, when bootstrapping, the first bootstrap
+/// has finished, for as long as the storage can be reached.
+async fn ready(State(state): State<DynServiceState>) -> Response {
+    let health = state.get_health();
+    let last_reload = health.last_reload();
+    let bootstrapped = state.get_bootstrap().then(|| health.bootstrapped());
+    let storage_available = match state.get_storage().await {
+        Ok(storage) => storage.ping().await.is_ok(),
+        Err(_) => false,
+    };
+    let readiness = Readiness {
+        ready: last_reload.is_some() && storage_available && bootstrapped.unwrap_or(true),
+        data_loaded: last_reload.is_some(),
+        storage_available,
+        bootstrapped,
+        last_reload,
+        object_counts: health.object_counts(),
+    };
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness)).into_response()
+}
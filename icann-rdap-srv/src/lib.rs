@@ -1,7 +1,11 @@
+pub mod admin;
 pub mod bootstrap;
 pub mod config;
 pub mod error;
+pub mod health;
+pub mod metrics;
 pub mod rdap;
 pub mod server;
 pub mod storage;
+pub mod tls;
 pub mod util;
@@ -0,0 +1,284 @@
+use std::{net::SocketAddr, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use http::{header::CONTENT_TYPE, StatusCode};
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tokio::net::TcpListener;
+
+use crate::{
+    config::ListenConfig,
+    error::RdapServerError,
+    storage::{ObjectCounts, StoreOps},
+};
+
+lazy_static! {
+    static ref REGISTRY: Registry = Registry::new();
+    static ref REQUESTS: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new("rdap_requests_total", "Number of RDAP requests."),
+            &["route", "status"]
+        )
+        .expect("requests metric")
+    );
+    static ref REQUEST_DURATION: HistogramVec = register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "rdap_request_duration_seconds",
+                "Duration of RDAP requests in seconds."
+            ),
+            &["route"]
+        )
+        .expect("request duration metric")
+    );
+    static ref REDIRECTS: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new(
+                "rdap_bootstrap_redirects_total",
+                "Number of redirects issued from bootstrap data."
+            ),
+            &["object"]
+        )
+        .expect("redirects metric")
+    );
+    static ref OBJECTS: IntGaugeVec = register(
+        IntGaugeVec::new(
+            Opts::new("rdap_objects", "Number of objects in storage."),
+            &["class"]
+        )
+        .expect("objects metric")
+    );
+    static ref RELOADS: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new("rdap_data_reloads_total", "Number of data loads."),
+            &["kind", "outcome"]
+        )
+        .expect("reloads metric")
+    );
+    static ref RELOAD_DURATION: HistogramVec = register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "rdap_data_reload_duration_seconds",
+                "Duration of data loads in seconds."
+            )
+            .buckets(vec![0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0]),
+            &["kind"]
+        )
+        .expect("reload duration metric")
+    );
+    static ref BOOTSTRAP_FETCHES: IntCounterVec = register(
+        IntCounterVec::new(
+            Opts::new(
+                "rdap_bootstrap_fetches_total",
+                "Number of IANA bootstrap registry fetches."
+            ),
+            &["registry", "outcome"]
+        )
+        .expect("bootstrap fetches metric")
+    );
+}
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(metric: T) -> T {
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("registering metric");
+    metric
+}
+
+/// Gets the name of a route used to label its metrics, which is its path without
+/// the `rdap` prefix or the path parameters, such as `domain` or `domains_reverse_search_entity`.
+fn route_name(path: Option<&MatchedPath>) -> String {
+    let Some(path) = path else {
+        return "unmatched".to_string();
+    };
+    path.as_str()
+        .split('/')
+        .filter(|segment| !segment.is_empty() && !segment.starts_with([':', '*']))
+        .skip_while(|segment| *segment == "rdap")
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Counts requests and their durations by route and status code.
+pub(crate) async fn track_requests(request: Request, next: Next) -> Response {
+    let route = route_name(request.extensions().get::<MatchedPath>());
+    let start = Instant::now();
+    let response = next.run(request).await;
+    REQUEST_DURATION
+        .with_label_values(&[&route])
+        .observe(start.elapsed().as_secs_f64());
+    REQUESTS
+        .with_label_values(&[&route, response.status().as_str()])
+        .inc();
+    response
+}
+
+/// Counts a redirect issued from bootstrap data for an object class, such as `domain`.
+pub(crate) fn record_redirect(object: &str) {
+    REDIRECTS.with_label_values(&[object]).inc();
+}
+
+/// Sets the number of objects of each class in storage, giving the counts.
+pub(crate) async fn record_object_counts(
+    store: &dyn StoreOps,
+) -> Result<ObjectCounts, RdapServerError> {
+    let counts = store.get_object_counts().await?;
+    let ObjectCounts {
+        domains,
+        entities,
+        nameservers,
+        autnums,
+        networks,
+    } = counts;
+    for (class, count) in [
+        ("domain", domains),
+        ("entity", entities),
+        ("nameserver", nameservers),
+        ("autnum", autnums),
+        ("ip network", networks),
+    ] {
+        OBJECTS.with_label_values(&[class]).set(count as i64);
+    }
+    Ok(counts)
+}
+
+/// Records the duration and outcome of a data load of a kind, either `update` or `reload`.
+pub(crate) fn record_reload<T>(kind: &str, start: Instant, result: &Result<T, RdapServerError>) {
+    let outcome = if result.is_ok() { "success" } else { "error" };
+    RELOADS.with_label_values(&[kind, outcome]).inc();
+    RELOAD_DURATION
+        .with_label_values(&[kind])
+        .observe(start.elapsed().as_secs_f64());
+}
+
+/// Records the outcome of fetching an IANA bootstrap registry, which is `new` when the
+/// registry was fetched, `unchanged` when the cached registry is still fresh or `error`.
+pub(crate) fn record_bootstrap_fetch<T>(
+    registry: &str,
+    result: &Result<Option<T>, RdapServerError>,
+) {
+    let outcome = match result {
+        Ok(Some(_)) => "new",
+        Ok(None) => "unchanged",
+        Err(_) => "error",
+    };
+    BOOTSTRAP_FETCHES
+        .with_label_values(&[registry, outcome])
+        .inc();
+}
+
+async fn metrics() -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&REGISTRY.gather(), &mut buffer) {
+        tracing::warn!("metrics not encoded: {e}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    ([(CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response()
+}
+
+/// Holds information on the metrics server listening. Metrics are served separately
+/// from RDAP so that they need not be exposed to RDAP clients.
+pub struct MetricsListener {
+    pub local_addr: SocketAddr,
+    tcp_listener: TcpListener,
+}
+
+impl MetricsListener {
+    /// Binds the metrics listener. TLS is not used by the metrics listener.
+    pub async fn listen(config: &ListenConfig) -> Result<Self, RdapServerError> {
+        let binding = format!(
+            "{}:{}",
+            config.ip_addr.as_ref().unwrap_or(&"[::]".to_string()),
+            config.port.as_ref().unwrap_or(&0)
+        );
+        tracing::debug!("metrics tcp binding to {}", binding);
+        let tcp_listener = TcpListener::bind(binding).await?;
+        let local_addr = tcp_listener.local_addr()?;
+        Ok(Self {
+            local_addr,
+            tcp_listener,
+        })
+    }
+
+    /// Serves `/metrics` in the Prometheus text format.
+    pub async fn start(self) -> Result<(), RdapServerError> {
+        let app = Router::new().route("/metrics", get(metrics));
+        tracing::debug!("metrics listening on {}", self.local_addr);
+        axum::serve(self.tcp_listener, app).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use axum::extract::MatchedPath;
+    use axum::{routing::get, Router};
+    use http::Request;
+    use tower::ServiceExt;
+
+    use super::route_name;
+
+    async fn matched_route(route: &str, uri: &str) -> String {
+        let app = Router::new().nest(
+            "/rdap",
+            Router::new().route(
+                route,
+                get(|path: MatchedPath| async move { route_name(Some(&path)) }),
+            ),
+        );
+        let response = app
+            .oneshot(Request::get(uri).body(axum::body::Body::empty()).unwrap())
+            .await
+            .expect("routing request");
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .expect("reading body");
+        String::from_utf8(body.to_vec()).expect("route name")
+    }
+
+    #[tokio::test]
+    async fn GIVEN_lookup_WHEN_route_name_THEN_class() {
+        // GIVEN
+        let uri = "/rdap/domain/foo.example";
+
+        // WHEN
+        let actual = matched_route("/domain/:domain", uri).await;
+
+        // THEN
+        assert_eq!(actual, "domain");
+    }
+
+    #[tokio::test]
+    async fn GIVEN_reverse_search_WHEN_route_name_THEN_path_joined() {
+        // GIVEN
+        let uri = "/rdap/domains/reverse_search/entity?handle=foo";
+
+        // WHEN
+        let actual = matched_route("/domains/reverse_search/entity", uri).await;
+
+        // THEN
+        assert_eq!(actual, "domains_reverse_search_entity");
+    }
+
+    #[test]
+    fn GIVEN_no_matched_path_WHEN_route_name_THEN_unmatched() {
+        // GIVEN
+        let path = None;
+
+        // WHEN
+        let actual = route_name(path);
+
+        // THEN
+        assert_eq!(actual, "unmatched");
+    }
+}
@@ -0,0 +1,252 @@
+use std::{collections::HashMap, convert::Infallible, fs, sync::Arc};
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use headers::{
+    authorization::{Basic, Bearer},
+    Authorization, HeaderMapExt,
+};
+use http::{
+    header::{AUTHORIZATION, WWW_AUTHENTICATE},
+    request::Parts,
+    HeaderValue,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use strum_macros::Display;
+
+use crate::{
+    error::RdapServerError,
+    rdap::response::{ResponseUtil, UNAUTHORIZED},
+};
+
+/// The view of the data given to a caller.
+///
+/// Anonymous callers are given the public view. Callers authenticated with the
+/// credentials in the credential file are given the view of the tier of their credentials.
+/// Handlers get the tier of the caller as an extractor.
+#[derive(Debug, Display, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AccessTier {
+    #[default]
+    Public,
+    Registrar,
+    LawEnforcement,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AccessTier
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<AccessTier>()
+            .copied()
+            .unwrap_or_default())
+    }
+}
+
+/// A credential in the credential file. Secrets are given as the hex encoded
+/// SHA-256 digest of the token or password so that the file does not hold them.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Credential {
+    Bearer {
+        token_sha256: String,
+        tier: AccessTier,
+    },
+    Basic {
+        username: String,
+        password_sha256: String,
+        tier: AccessTier,
+    },
+}
+
+/// The contents of the credential file.
+#[derive(Debug, Deserialize)]
+struct CredentialFile {
+    credentials: Vec<Credential>,
+}
+
+/// The credentials of the callers that are given more than the public view.
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    bearer: HashMap<String, AccessTier>,
+    basic: HashMap<String, (String, AccessTier)>,
+}
+
+impl Credentials {
+    /// Loads the credentials from a JSON credential file such as:
+    ///
+    /// ```json
+    /// {
+    ///   "credentials": [
+    ///     { "type": "bearer", "token_sha256": "9f86d0...", "tier": "registrar" },
+    ///     { "type": "basic", "username": "alice", "password_sha256": "2c26b4...", "tier": "law_enforcement" }
+    ///   ]
+    /// }
+    /// ```
+    pub fn load(path: &str) -> Result<Self, RdapServerError> {
+        let contents = fs::read_to_string(path)?;
+        let file = serde_json::from_str::<CredentialFile>(&contents).map_err(|e| {
+            RdapServerError::Config(format!("credential file {path} is invalid: {e}"))
+        })?;
+        let mut credentials = Self::default();
+        for credential in file.credentials {
+            match credential {
+                Credential::Bearer { token_sha256, tier } => {
+                    credentials
+                        .bearer
+                        .insert(token_sha256.to_ascii_lowercase(), tier);
+                }
+                Credential::Basic {
+                    username,
+                    password_sha256,
+                    tier,
+                } => {
+                    credentials
+                        .basic
+                        .insert(username, (password_sha256.to_ascii_lowercase(), tier));
+                }
+            }
+        }
+        Ok(credentials)
+    }
+
+    /// Gets the tier of a bearer token, or `None` if the token is unknown.
+    fn bearer_tier(&self, token: &str) -> Option<AccessTier> {
+        self.bearer.get(&sha256_hex(token)).copied()
+    }
+
+    /// Gets the tier of a username and password, or `None` if they are unknown.
+    fn basic_tier(&self, username: &str, password: &str) -> Option<AccessTier> {
+        self.basic
+            .get(username)
+            .filter(|(password_sha256, _)| *password_sha256 == sha256_hex(password))
+            .map(|(_, tier)| *tier)
+    }
+
+    /// Gets the tier of the caller from the `Authorization` header, giving `None`
+    /// for credentials that are unknown or are not bearer tokens or HTTP Basic credentials.
+    fn caller_tier(&self, headers: &http::HeaderMap) -> Option<AccessTier> {
+        if let Some(Authorization(bearer)) = headers.typed_get::<Authorization<Bearer>>() {
+            self.bearer_tier(bearer.token())
+        } else if let Some(Authorization(basic)) = headers.typed_get::<Authorization<Basic>>() {
+            self.basic_tier(basic.username(), basic.password())
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) fn sha256_hex(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Authenticates callers giving an `Authorization` header and adds the access tier of
+/// the caller to the request. A 401 is given for credentials that are not known.
+pub(crate) async fn authenticate(
+    State(credentials): State<Arc<Credentials>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !request.headers().contains_key(AUTHORIZATION) {
+        return next.run(request).await;
+    }
+    let Some(tier) = credentials.caller_tier(request.headers()) else {
+        tracing::debug!("unknown credentials");
+        let mut response = UNAUTHORIZED.response();
+        response.headers_mut().insert(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static(r#"Bearer realm="rdap", Basic realm="rdap""#),
+        );
+        return response;
+    };
+    request.extensions_mut().insert(tier);
+    next.run(request).await
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use headers::{Authorization, HeaderMapExt};
+    use http::HeaderMap;
+
+    use super::{sha256_hex, AccessTier, Credentials};
+
+    fn credentials() -> Credentials {
+        let mut credentials = Credentials::default();
+        credentials
+            .bearer
+            .insert(sha256_hex("registrar-token"), AccessTier::Registrar);
+        credentials.basic.insert(
+            "alice".to_string(),
+            (sha256_hex("secret"), AccessTier::LawEnforcement),
+        );
+        credentials
+    }
+
+    #[test]
+    fn GIVEN_unsupported_authorization_WHEN_caller_tier_THEN_none() {
+        // GIVEN
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::AUTHORIZATION,
+            http::HeaderValue::from_static("Digest username=\"alice\""),
+        );
+
+        // WHEN
+        let actual = credentials().caller_tier(&headers);
+
+        // THEN
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn GIVEN_known_bearer_token_WHEN_caller_tier_THEN_tier_of_token() {
+        // GIVEN
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(Authorization::bearer("registrar-token").expect("bearer"));
+
+        // WHEN
+        let actual = credentials().caller_tier(&headers);
+
+        // THEN
+        assert_eq!(actual, Some(AccessTier::Registrar));
+    }
+
+    #[test]
+    fn GIVEN_known_basic_credentials_WHEN_caller_tier_THEN_tier_of_user() {
+        // GIVEN
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(Authorization::basic("alice", "secret"));
+
+        // WHEN
+        let actual = credentials().caller_tier(&headers);
+
+        // THEN
+        assert_eq!(actual, Some(AccessTier::LawEnforcement));
+    }
+
+    #[test]
+    fn GIVEN_wrong_password_WHEN_caller_tier_THEN_none() {
+        // GIVEN
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(Authorization::basic("alice", "guess"));
+
+        // WHEN
+        let actual = credentials().caller_tier(&headers);
+
+        // THEN
+        assert!(actual.is_none());
+    }
+}
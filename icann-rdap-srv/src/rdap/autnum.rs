@@ -1,9 +1,14 @@
 use axum::{
     extract::{Path, State},
-    response::Response,
+    response::{IntoResponse, Response},
 };
 
-use crate::{error::RdapServerError, rdap::response::ResponseUtil, server::DynServiceState};
+use crate::{
+    error::RdapServerError,
+    rdap::{access::AccessTier, response::ResponseUtil},
+    server::DynServiceState,
+    storage::Lookup,
+};
 
 use super::ToBootStrap;
 
@@ -12,9 +17,13 @@ use super::ToBootStrap;
 #[tracing::instrument(level = "debug")]
 pub(crate) async fn autnum_by_num(
     Path(as_num): Path<u32>,
+    tier: AccessTier,
     state: State<DynServiceState>,
 ) -> Result<Response, RdapServerError> {
     let storage = state.get_storage().await?;
+    if let Some(autnum) = storage.get_serialized(Lookup::AutnumByNum(as_num)).await? {
+        return Ok(autnum.into_response());
+    }
     let autnum = storage.get_autnum_by_num(as_num).await?;
     if state.get_bootstrap() {
         Ok(autnum.to_autnum_bootstrap(as_num).response())
@@ -0,0 +1,308 @@
+use std::{sync::Arc, time::SystemTime};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::DateTime;
+use headers::{ETag, HeaderMapExt, IfModifiedSince, IfNoneMatch, LastModified};
+use http::{
+    header::{CACHE_CONTROL, VARY},
+    HeaderValue, Method, StatusCode,
+};
+use icann_rdap_common::response::{types::ObjectCommon, RdapResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{config::CacheConfig, rdap::access::AccessTier};
+
+/// The largest response body that is buffered to compute its `ETag`.
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+impl CacheConfig {
+    /// Gets the max-age of a response from its object class.
+    fn max_age(&self, json: &Value) -> Option<u32> {
+        match json.get("objectClassName").and_then(Value::as_str) {
+            Some(object_class) => self.object_max_age(object_class),
+            None => {
+                let is_search = json
+                    .as_object()
+                    .is_some_and(|object| object.keys().any(|key| key.ends_with("SearchResults")));
+                if is_search {
+                    self.search
+                } else {
+                    self.help
+                }
+            }
+        }
+    }
+
+    /// Gets the max-age of an object of the object class.
+    fn object_max_age(&self, object_class: &str) -> Option<u32> {
+        match object_class {
+            "domain" => self.domain,
+            "nameserver" => self.nameserver,
+            "entity" => self.entity,
+            "autnum" => self.autnum,
+            "ip network" => self.ip,
+            _ => None,
+        }
+    }
+}
+
+/// The cache headers of an object, worked out when the object is serialized so that
+/// they need not be worked out from the body each time it is served. Handlers serving
+/// serialized objects put it in the extensions of the response. Storage engines may keep
+/// it, serialized, alongside the JSON of the object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheInfo {
+    etag: String,
+    last_modified: Option<SystemTime>,
+    object_class: String,
+}
+
+impl CacheInfo {
+    /// Works out the cache headers of an object from the object and its JSON.
+    pub fn new(object: &RdapResponse, json: &[u8]) -> Self {
+        let object_common = object_common(object);
+        Self {
+            etag: etag(json),
+            last_modified: object_common
+                .and_then(|object_common| object_common.events.as_ref())
+                .and_then(|events| {
+                    events
+                        .iter()
+                        .find(|event| event.event_action == "last changed")
+                })
+                .and_then(|event| event.event_date.as_deref())
+                .and_then(parse_date),
+            object_class: object_common
+                .map(|object_common| object_common.object_class_name.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn object_common(object: &RdapResponse) -> Option<&ObjectCommon> {
+    match object {
+        RdapResponse::Entity(entity) => Some(&entity.object_common),
+        RdapResponse::Domain(domain) => Some(&domain.object_common),
+        RdapResponse::Nameserver(nameserver) => Some(&nameserver.object_common),
+        RdapResponse::Autnum(autnum) => Some(&autnum.object_common),
+        RdapResponse::Network(network) => Some(&network.object_common),
+        _ => None,
+    }
+}
+
+/// Gets the entity tag of a body, which is its quoted SHA-256 digest.
+fn etag(body: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(body))
+}
+
+fn parse_date(date: &str) -> Option<SystemTime> {
+    DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(SystemTime::from)
+}
+
+/// Gets the date of the "last changed" event of an object.
+fn last_changed(json: &Value) -> Option<SystemTime> {
+    json.get("events")?
+        .as_array()?
+        .iter()
+        .find(|event| event.get("eventAction").and_then(Value::as_str) == Some("last changed"))?
+        .get("eventDate")?
+        .as_str()
+        .and_then(parse_date)
+}
+
+/// Adds `ETag`, `Last-Modified` and `Cache-Control` headers to successful responses,
+/// and answers conditional requests whose precondition fails with a 304.
+///
+/// The headers of serialized objects are taken from the [CacheInfo] of the response
+/// rather than worked out from the body.
+///
+/// HEAD requests are served as GET requests with the body removed, so that they are
+/// given the same headers as GET requests.
+pub(crate) async fn cache_headers(
+    State(cache): State<Arc<CacheConfig>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let is_head = request.method() == Method::HEAD;
+    if is_head {
+        *request.method_mut() = Method::GET;
+    }
+    let if_none_match = request.headers().typed_get::<IfNoneMatch>();
+    let if_modified_since = request.headers().typed_get::<IfModifiedSince>();
+    let tier = request
+        .extensions()
+        .get::<AccessTier>()
+        .copied()
+        .unwrap_or_default();
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return strip_head(response, is_head);
+    }
+    let (mut parts, mut body) = response.into_parts();
+    let (etag, last_modified, max_age) = match parts.extensions.remove::<CacheInfo>() {
+        Some(info) => (
+            info.etag,
+            info.last_modified,
+            cache.object_max_age(&info.object_class),
+        ),
+        None => {
+            let Ok(bytes) = to_bytes(body, MAX_BODY_SIZE).await else {
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            };
+            let json = serde_json::from_slice::<Value>(&bytes).unwrap_or_default();
+            let headers = (etag(&bytes), last_changed(&json), cache.max_age(&json));
+            body = Body::from(bytes);
+            headers
+        }
+    };
+
+    let Ok(etag) = etag.parse::<ETag>() else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    parts.headers.typed_insert(etag.clone());
+    if let Some(last_modified) = last_modified {
+        parts
+            .headers
+            .typed_insert(LastModified::from(last_modified));
+    }
+    if let Some(max_age) = max_age {
+        let visibility = if tier == AccessTier::Public {
+            "public"
+        } else {
+            "private"
+        };
+        parts.headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_str(&format!("{visibility}, max-age={max_age}"))
+                .expect("cache control is a valid header value"),
+        );
+    }
+    parts
+        .headers
+        .insert(VARY, HeaderValue::from_static("Authorization, Cookie"));
+
+    // If-Modified-Since is only used when If-None-Match is not given (RFC 9110 section 13.1.3).
+    let not_modified = match (if_none_match, if_modified_since, last_modified) {
+        (Some(if_none_match), _, _) => !if_none_match.precondition_passes(&etag),
+        (None, Some(if_modified_since), Some(last_modified)) => {
+            !if_modified_since.is_modified(last_modified)
+        }
+        _ => false,
+    };
+    if not_modified {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(http::header::CONTENT_TYPE);
+        parts.headers.remove(http::header::CONTENT_LENGTH);
+        return Response::from_parts(parts, Body::empty());
+    }
+    if is_head {
+        return Response::from_parts(parts, Body::empty());
+    }
+    Response::from_parts(parts, body)
+}
+
+fn strip_head(response: Response, is_head: bool) -> Response {
+    if is_head {
+        let (parts, _) = response.into_parts();
+        Response::from_parts(parts, Body::empty())
+    } else {
+        response
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use icann_rdap_common::response::{domain::Domain, types::Event, RdapResponse};
+    use serde_json::{json, Value};
+
+    use crate::config::CacheConfig;
+
+    use super::{etag, last_changed, CacheInfo};
+
+    #[test]
+    fn GIVEN_last_changed_event_WHEN_last_changed_THEN_event_date() {
+        // GIVEN
+        let json = json!({
+            "objectClassName": "domain",
+            "events": [
+                { "eventAction": "registration", "eventDate": "1990-12-31T23:59:59Z" },
+                { "eventAction": "last changed", "eventDate": "2023-01-01T00:00:10Z" }
+            ]
+        });
+
+        // WHEN
+        let actual = last_changed(&json);
+
+        // THEN
+        assert_eq!(
+            actual,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_672_531_210))
+        );
+    }
+
+    #[test]
+    fn GIVEN_search_results_WHEN_max_age_THEN_search_max_age() {
+        // GIVEN
+        let cache = CacheConfig::builder().domain(60).search(10).build();
+        let json = json!({ "domainSearchResults": [] });
+
+        // WHEN
+        let actual = cache.max_age(&json);
+
+        // THEN
+        assert_eq!(actual, Some(10));
+    }
+
+    #[test]
+    fn GIVEN_class_without_max_age_WHEN_max_age_THEN_none() {
+        // GIVEN
+        let cache = CacheConfig::builder().domain(60).build();
+        let json = json!({ "objectClassName": "entity" });
+
+        // WHEN
+        let actual = cache.max_age(&json);
+
+        // THEN
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn GIVEN_domain_WHEN_new_cache_info_THEN_same_headers_as_from_body() {
+        // GIVEN
+        let domain = RdapResponse::Domain(
+            Domain::basic()
+                .ldh_name("foo.example")
+                .event(
+                    Event::builder()
+                        .event_action("last changed")
+                        .event_date("2023-01-01T00:00:10Z")
+                        .build(),
+                )
+                .build(),
+        );
+        let json = serde_json::to_vec(&domain).expect("serializing domain");
+
+        // WHEN
+        let actual = CacheInfo::new(&domain, &json);
+
+        // THEN
+        let value = serde_json::from_slice::<Value>(&json).expect("parsing domain");
+        assert_eq!(actual.etag, etag(&json));
+        assert_eq!(actual.last_modified, last_changed(&value));
+        assert!(actual.last_modified.is_some());
+        assert_eq!(actual.object_class, "domain");
+    }
+}
@@ -1,10 +1,19 @@
 use axum::{
     extract::{Path, State},
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use icann_rdap_common::response::RdapResponse;
 
-use crate::{error::RdapServerError, rdap::response::ResponseUtil, server::DynServiceState};
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        redaction::{redact, redacts},
+        response::ResponseUtil,
+    },
+    server::DynServiceState,
+    storage::Lookup,
+};
 
 use super::ToBootStrap;
 
@@ -14,6 +23,7 @@ use super::ToBootStrap;
 #[tracing::instrument(level = "debug")]
 pub(crate) async fn domain_by_name(
     Path(domain_name): Path<String>,
+    tier: AccessTier,
     state: State<DynServiceState>,
 ) -> Result<Response, RdapServerError> {
     // canonicalize the domain name by removing a trailing ".", trimming any whitespace,
@@ -27,6 +37,17 @@ pub(crate) async fn domain_by_name(
     // TODO add option to verify it looks like a domain name and return BAD REQUEST if it does not.
     // not all servers may want to enforce that it has multiple labels, such as an IANA server.
     let storage = state.get_storage().await?;
+
+    // domains which need not be redacted are served as they were serialized when stored.
+    if !redacts(state.get_redaction_policy(), tier) {
+        if let Some(domain) = storage
+            .get_serialized(Lookup::DomainByLdh(&domain_name))
+            .await?
+        {
+            return Ok(domain.into_response());
+        }
+    }
+
     let mut domain = storage.get_domain_by_ldh(&domain_name).await?;
 
     // if not found in domain names, check if it is an IDN
@@ -47,5 +68,6 @@ pub(crate) async fn domain_by_name(
         }
     }
 
+    let domain = redact(domain, state.get_redaction_policy(), tier);
     Ok(domain.response())
 }
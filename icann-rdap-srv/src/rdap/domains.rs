@@ -0,0 +1,82 @@
+use std::net::IpAddr;
+
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Response,
+};
+use serde::Deserialize;
+
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        paging::{page_search_results, PagingParams, ALL_RESULTS},
+        redaction::redact,
+        response::{ResponseUtil, BAD_REQUEST},
+        subsetting::{subset_search_results, SubsettingParams},
+    },
+    server::DynServiceState,
+};
+
+/// The query parameters of a domain search.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DomainsParams {
+    name: Option<String>,
+    ns_ldh_name: Option<String>,
+    ns_ip: Option<String>,
+}
+
+/// Searches for domains according to RFC 9082.
+/// The `name` parameter is matched against both the LDH and Unicode names of a domain,
+/// and the `nsLdhName` parameter against the names of a domain's nameservers. Both
+/// may use the `*` wildcard for partial matches. The `nsIp` parameter is matched
+/// against the IP addresses of a domain's nameservers.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn domains_search(
+    Query(params): Query<DomainsParams>,
+    Query(paging): Query<PagingParams>,
+    Query(subsetting): Query<SubsettingParams>,
+    OriginalUri(uri): OriginalUri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let storage = state.get_storage().await?;
+    let domains = match (params.name, params.ns_ldh_name, params.ns_ip) {
+        (Some(name), None, None) => {
+            let Some(name) = search_name(&name) else {
+                return Ok(BAD_REQUEST.response());
+            };
+            storage.search_domains_by_name(&name, ALL_RESULTS).await?
+        }
+        (None, Some(ns_ldh_name), None) => {
+            let Some(ns_ldh_name) = search_name(&ns_ldh_name) else {
+                return Ok(BAD_REQUEST.response());
+            };
+            storage
+                .search_domains_by_ns_ldh_name(&ns_ldh_name, ALL_RESULTS)
+                .await?
+        }
+        (None, None, Some(ns_ip)) => {
+            let ns_ip = ns_ip.trim();
+            if ns_ip.parse::<IpAddr>().is_err() {
+                return Ok(BAD_REQUEST.response());
+            }
+            storage.search_domains_by_ns_ip(ns_ip, ALL_RESULTS).await?
+        }
+        _ => return Ok(BAD_REQUEST.response()),
+    };
+    let page_size = state.get_search_page_size();
+    let max_results = state.get_max_search_results();
+    let domains = redact(domains, state.get_redaction_policy(), tier);
+    let results = page_search_results(domains, &paging, &uri, page_size, max_results);
+    Ok(subset_search_results(results, &subsetting, &uri).response())
+}
+
+/// Canonicalizes a name to search for the same as the name of a domain lookup,
+/// giving `None` if nothing is left to search for.
+fn search_name(name: &str) -> Option<String> {
+    let name = name.trim_end_matches('.').trim().to_lowercase();
+    (!name.is_empty()).then_some(name)
+}
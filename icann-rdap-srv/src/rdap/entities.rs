@@ -0,0 +1,77 @@
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Response,
+};
+use serde::Deserialize;
+
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        paging::{page_search_results, PagingParams, ALL_RESULTS},
+        redaction::{redact, redacts_property},
+        response::{ResponseUtil, BAD_REQUEST, REDACTED_SEARCH},
+        subsetting::{subset_search_results, SubsettingParams},
+    },
+    server::DynServiceState,
+};
+
+/// The query parameters of an entity search.
+#[derive(Debug, Deserialize)]
+pub(crate) struct EntitiesParams {
+    #[serde(rename = "fn")]
+    full_name: Option<String>,
+    handle: Option<String>,
+}
+
+/// Searches for entities according to RFC 9082.
+/// The `fn` parameter is matched against the full name of an entity's contact,
+/// and the `handle` parameter against the handle of an entity. Both may use
+/// the `*` wildcard for partial matches. Searches by a property redacted for the
+/// caller are refused.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn entities_search(
+    Query(params): Query<EntitiesParams>,
+    Query(paging): Query<PagingParams>,
+    Query(subsetting): Query<SubsettingParams>,
+    OriginalUri(uri): OriginalUri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let property = match (&params.full_name, &params.handle) {
+        (Some(_), None) => "fn",
+        (None, Some(_)) => "handle",
+        _ => return Ok(BAD_REQUEST.response()),
+    };
+    if redacts_property(state.get_redaction_policy(), tier, property) {
+        return Ok(REDACTED_SEARCH.response());
+    }
+    let storage = state.get_storage().await?;
+    let entities = match (params.full_name, params.handle) {
+        (Some(full_name), None) => {
+            let full_name = full_name.trim();
+            if full_name.is_empty() {
+                return Ok(BAD_REQUEST.response());
+            }
+            storage
+                .search_entities_by_name(full_name, ALL_RESULTS)
+                .await?
+        }
+        (None, Some(handle)) => {
+            let handle = handle.trim();
+            if handle.is_empty() {
+                return Ok(BAD_REQUEST.response());
+            }
+            storage
+                .search_entities_by_handle(handle, ALL_RESULTS)
+                .await?
+        }
+        _ => return Ok(BAD_REQUEST.response()),
+    };
+    let page_size = state.get_search_page_size();
+    let max_results = state.get_max_search_results();
+    let entities = redact(entities, state.get_redaction_policy(), tier);
+    let results = page_search_results(entities, &paging, &uri, page_size, max_results);
+    Ok(subset_search_results(results, &subsetting, &uri).response())
+}
@@ -1,10 +1,19 @@
 use axum::{
     extract::{Path, State},
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use icann_rdap_common::response::RdapResponse;
 
-use crate::{error::RdapServerError, rdap::response::ResponseUtil, server::DynServiceState};
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        redaction::{redact, redacts},
+        response::ResponseUtil,
+    },
+    server::DynServiceState,
+    storage::Lookup,
+};
 
 use super::ToBootStrap;
 
@@ -13,9 +22,21 @@ use super::ToBootStrap;
 #[tracing::instrument(level = "debug")]
 pub(crate) async fn entity_by_handle(
     Path(handle): Path<String>,
+    tier: AccessTier,
     state: State<DynServiceState>,
 ) -> Result<Response, RdapServerError> {
     let storage = state.get_storage().await?;
+
+    // entities which need not be redacted are served as they were serialized when stored.
+    if !redacts(state.get_redaction_policy(), tier) {
+        if let Some(entity) = storage
+            .get_serialized(Lookup::EntityByHandle(&handle))
+            .await?
+        {
+            return Ok(entity.into_response());
+        }
+    }
+
     let entity = storage.get_entity_by_handle(&handle).await?;
 
     if state.get_bootstrap() && !matches!(entity, RdapResponse::Entity(_)) && !entity.is_redirect()
@@ -30,5 +51,6 @@ pub(crate) async fn entity_by_handle(
         }
     }
 
+    let entity = redact(entity, state.get_redaction_policy(), tier);
     Ok(entity.response())
 }
@@ -2,7 +2,7 @@ use std::{net::IpAddr, str::FromStr};
 
 use axum::{
     extract::{Path, State},
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use cidr_utils::cidr::IpInet;
 use tracing::debug;
@@ -10,10 +10,12 @@ use tracing::debug;
 use crate::{
     error::RdapServerError,
     rdap::{
+        access::AccessTier,
         response::{ResponseUtil, BAD_REQUEST},
         ToBootStrap,
     },
     server::DynServiceState,
+    storage::Lookup,
 };
 
 /// Gets a network object by the address path.
@@ -21,13 +23,18 @@ use crate::{
 #[tracing::instrument(level = "debug")]
 pub(crate) async fn network_by_netid(
     Path(netid): Path<String>,
+    tier: AccessTier,
     state: State<DynServiceState>,
 ) -> Result<Response, RdapServerError> {
     if netid.contains('/') {
         debug!("getting network by cidr {netid}");
         if let Ok(cidr) = IpInet::from_str(&netid) {
             let storage = state.get_storage().await?;
-            let network = storage.get_network_by_cidr(&cidr.to_string()).await?;
+            let cidr = cidr.to_string();
+            if let Some(network) = storage.get_serialized(Lookup::NetworkByCidr(&cidr)).await? {
+                return Ok(network.into_response());
+            }
+            let network = storage.get_network_by_cidr(&cidr).await?;
             if state.get_bootstrap() {
                 Ok(network.to_ip_bootstrap(&netid).response())
             } else {
@@ -43,6 +50,12 @@ pub(crate) async fn network_by_netid(
             Ok(BAD_REQUEST.response())
         } else {
             let storage = state.get_storage().await?;
+            if let Some(network) = storage
+                .get_serialized(Lookup::NetworkByIpAddr(&netid))
+                .await?
+            {
+                return Ok(network.into_response());
+            }
             let network = storage.get_network_by_ipaddr(&netid).await?;
             if state.get_bootstrap() {
                 Ok(network.to_ip_bootstrap(&netid).response())
@@ -1,13 +1,26 @@
 use icann_rdap_common::response::{error::Error, RdapResponse};
 
+use crate::metrics::record_redirect;
+
+pub mod access;
 pub mod autnum;
+pub mod caching;
 pub mod domain;
+pub mod domains;
+pub mod entities;
 pub mod entity;
 pub mod ip;
 pub mod nameserver;
+pub mod nameservers;
+pub mod openidc;
+pub mod paging;
+pub mod rate_limit;
+pub mod redaction;
 pub mod response;
+pub mod reverse_search;
 pub mod router;
 pub mod srvhelp;
+pub mod subsetting;
 
 trait ToBootStrap {
     fn to_ip_bootstrap(self, ip_id: &str) -> RdapResponse;
@@ -70,6 +83,7 @@ fn bootstrap_redirect(error: Error, path: &str, id: &str) -> RdapResponse {
         return RdapResponse::ErrorResponse(error);
     };
     let href = format!("{}{path}/{id}", link.href);
+    record_redirect(path);
     let redirect = Error::redirect().url(href).build();
     RdapResponse::ErrorResponse(redirect)
 }
@@ -1,10 +1,19 @@
 use axum::{
     extract::{Path, State},
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use icann_rdap_common::response::RdapResponse;
 
-use crate::{error::RdapServerError, rdap::response::ResponseUtil, server::DynServiceState};
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        redaction::{redact, redacts},
+        response::ResponseUtil,
+    },
+    server::DynServiceState,
+    storage::Lookup,
+};
 
 use super::{response::BAD_REQUEST, ToBootStrap};
 
@@ -13,6 +22,7 @@ use super::{response::BAD_REQUEST, ToBootStrap};
 #[tracing::instrument(level = "debug")]
 pub(crate) async fn nameserver_by_name(
     Path(ns_name): Path<String>,
+    tier: AccessTier,
     state: State<DynServiceState>,
 ) -> Result<Response, RdapServerError> {
     let count = ns_name.chars().filter(|c| *c == '.').count();
@@ -21,6 +31,15 @@ pub(crate) async fn nameserver_by_name(
         Ok(BAD_REQUEST.response())
     } else {
         let storage = state.get_storage().await?;
+        // nameservers which need not be redacted are served as they were serialized when stored.
+        if !redacts(state.get_redaction_policy(), tier) {
+            if let Some(nameserver) = storage
+                .get_serialized(Lookup::NameserverByLdh(&ns_name))
+                .await?
+            {
+                return Ok(nameserver.into_response());
+            }
+        }
         let nameserver = storage.get_nameserver_by_ldh(&ns_name).await?;
 
         if state.get_bootstrap()
@@ -40,6 +59,7 @@ pub(crate) async fn nameserver_by_name(
             }
         }
 
+        let nameserver = redact(nameserver, state.get_redaction_policy(), tier);
         Ok(nameserver.response())
     }
 }
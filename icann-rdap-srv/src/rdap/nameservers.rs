@@ -0,0 +1,70 @@
+use std::{net::IpAddr, str::FromStr};
+
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Response,
+};
+use ipnet::IpNet;
+use serde::Deserialize;
+
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        paging::{page_search_results, PagingParams, ALL_RESULTS},
+        redaction::redact,
+        response::{ResponseUtil, BAD_REQUEST},
+        subsetting::{subset_search_results, SubsettingParams},
+    },
+    server::DynServiceState,
+};
+
+/// The query parameters of a nameserver search.
+#[derive(Debug, Deserialize)]
+pub(crate) struct NameserversParams {
+    name: Option<String>,
+    ip: Option<String>,
+}
+
+/// Searches for nameservers according to RFC 9082.
+/// The `name` parameter is matched against the LDH name of a nameserver and
+/// may use the `*` wildcard for partial matches. The `ip` parameter is matched
+/// against the IP addresses of a nameserver, and may also be a CIDR to match
+/// all the addresses within it.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn nameservers_search(
+    Query(params): Query<NameserversParams>,
+    Query(paging): Query<PagingParams>,
+    Query(subsetting): Query<SubsettingParams>,
+    OriginalUri(uri): OriginalUri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let storage = state.get_storage().await?;
+    let nameservers = match (params.name, params.ip) {
+        (Some(name), None) => {
+            // canonicalize the search the same as a domain search.
+            let name = name.trim_end_matches('.').trim().to_lowercase();
+            if name.is_empty() {
+                return Ok(BAD_REQUEST.response());
+            }
+            storage
+                .search_nameservers_by_name(&name, ALL_RESULTS)
+                .await?
+        }
+        (None, Some(ip)) => {
+            let ip = ip.trim();
+            if ip.parse::<IpAddr>().is_err() && IpNet::from_str(ip).is_err() {
+                return Ok(BAD_REQUEST.response());
+            }
+            storage.search_nameservers_by_ip(ip, ALL_RESULTS).await?
+        }
+        _ => return Ok(BAD_REQUEST.response()),
+    };
+    let page_size = state.get_search_page_size();
+    let max_results = state.get_max_search_results();
+    let nameservers = redact(nameservers, state.get_redaction_policy(), tier);
+    let results = page_search_results(nameservers, &paging, &uri, page_size, max_results);
+    Ok(subset_search_results(results, &subsetting, &uri).response())
+}
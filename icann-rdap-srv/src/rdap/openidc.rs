@@ -0,0 +1,963 @@
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use axum::{
+    extract::{Query, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use buildstructor::Builder;
+use headers::{Cookie, HeaderMapExt};
+use http::{
+    header::{LOCATION, SET_COOKIE},
+    HeaderMap, HeaderValue, StatusCode,
+};
+use icann_rdap_common::response::{
+    error::Error,
+    openidc::{OpenIdcConfiguration, OpenIdcProvider, Session, SessionInfo, SessionResponse},
+    types::{Common, Extension, Notice, NoticeOrRemark},
+    RdapResponse,
+};
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        response::{
+            ResponseUtil, BAD_REQUEST, NOT_FOUND, NOT_IMPLEMENTED, RDAP_HEADERS, UNAUTHORIZED,
+        },
+    },
+    server::DynServiceState,
+};
+
+/// The RDAP conformance value of RFC 9560 OpenID Connect.
+pub const ROIDC1: &str = "roidc1";
+
+/// The name of the cookie holding the session identifier.
+const SESSION_COOKIE: &str = "id";
+
+/// The name of the cookie holding the state of a login, which ties the return from the
+/// provider to the browser that started the login.
+const LOGIN_COOKIE: &str = "login";
+
+/// How long a login may take between the redirect to the provider and the return from it.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The most logins held while end-users are at their provider. Once reached, new logins
+/// are refused until others finish or time out, so that starting many logins does not
+/// stop those of other end-users from finishing.
+const MAX_PENDING_LOGINS: usize = 10_000;
+
+/// The most sessions held at once. The oldest are ended to make room for new ones.
+const MAX_SESSIONS: usize = 100_000;
+
+/// How long a session lasts from login, however often its tokens are refreshed.
+const MAX_SESSION_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An OpenID provider end-users may log in with.
+#[derive(Debug, Deserialize, Builder, Clone)]
+pub struct ProviderConfig {
+    /// The name of the provider given in help responses.
+    pub name: String,
+
+    /// The issuer identifier of the provider. The provider configuration is discovered
+    /// from `{issuer}/.well-known/openid-configuration`.
+    pub issuer: String,
+
+    /// The client identifier of this server registered with the provider.
+    pub client_id: String,
+
+    /// The client secret of this server registered with the provider.
+    pub client_secret: String,
+
+    /// True if the provider is used when the login does not name one.
+    #[serde(default)]
+    pub default: bool,
+
+    /// The access tier given to end-users logged in with the provider.
+    #[serde(default)]
+    pub tier: AccessTier,
+}
+
+/// The contents of the OpenID Connect configuration file.
+#[derive(Debug, Deserialize)]
+struct OpenIdcFile {
+    redirect_uri: String,
+    providers: Vec<ProviderConfig>,
+}
+
+/// The parts of the provider configuration used by the login flow.
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+/// The response of the token endpoint of a provider.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+/// A login that has been redirected to a provider and has not yet returned.
+#[derive(Debug)]
+struct PendingLogin {
+    issuer: String,
+    nonce: String,
+    /// The PKCE code verifier of RFC 7636, sent with the authorization code.
+    code_verifier: String,
+    started: SystemTime,
+}
+
+impl PendingLogin {
+    fn is_timed_out(&self) -> bool {
+        self.started.elapsed().unwrap_or_default() >= LOGIN_TIMEOUT
+    }
+}
+
+/// The session of a logged in end-user.
+#[derive(Debug, Clone)]
+struct UserSession {
+    issuer: String,
+    claims: Map<String, Value>,
+    refresh_token: Option<String>,
+    expires_at: SystemTime,
+    tier: AccessTier,
+    started: SystemTime,
+}
+
+impl UserSession {
+    fn is_expired(&self) -> bool {
+        self.expires_at <= SystemTime::now()
+    }
+
+    /// Determines if the session has ended, either because its tokens have expired and
+    /// cannot be refreshed or because it has reached its maximum lifetime.
+    fn is_ended(&self) -> bool {
+        (self.is_expired() && self.refresh_token.is_none())
+            || self.started.elapsed().unwrap_or_default() >= MAX_SESSION_LIFETIME
+    }
+
+    fn to_session(&self) -> Session {
+        let token_expiration = self
+            .expires_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+            .as_secs();
+        Session::builder()
+            .user_claims(self.claims.clone())
+            .session_info(
+                SessionInfo::builder()
+                    .token_expiration(token_expiration)
+                    .token_refresh(self.refresh_token.is_some())
+                    .build(),
+            )
+            .build()
+    }
+}
+
+/// RFC 9560 OpenID Connect login of end-users. Logins and sessions are held in memory.
+#[derive(Debug, Clone)]
+pub struct OpenIdc {
+    redirect_uri: String,
+    providers: Vec<ProviderConfig>,
+    client: reqwest::Client,
+    logins: Arc<Mutex<HashMap<String, PendingLogin>>>,
+    sessions: Arc<Mutex<HashMap<String, UserSession>>>,
+}
+
+#[buildstructor::buildstructor]
+impl OpenIdc {
+    /// Creates the login of end-users with a set of providers. The redirect URI is the
+    /// URL of the `login` path of this server, which must be registered with each provider.
+    #[builder]
+    pub fn new(redirect_uri: String, providers: Vec<ProviderConfig>) -> Self {
+        Self {
+            redirect_uri,
+            providers,
+            client: reqwest::Client::new(),
+            logins: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Loads the configuration from a JSON file such as:
+    ///
+    /// ```json
+    /// {
+    ///   "redirect_uri": "https://rdap.example.com/rdap/login",
+    ///   "providers": [
+    ///     {
+    ///       "name": "Example Provider",
+    ///       "issuer": "https://idp.example.com",
+    ///       "client_id": "rdap",
+    ///       "client_secret": "secret",
+    ///       "default": true,
+    ///       "tier": "registrar"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    pub fn load(path: &str) -> Result<Self, RdapServerError> {
+        let contents = fs::read_to_string(path)?;
+        let file = serde_json::from_str::<OpenIdcFile>(&contents).map_err(|e| {
+            RdapServerError::Config(format!("OpenID Connect file {path} is invalid: {e}"))
+        })?;
+        if file.providers.is_empty() {
+            return Err(RdapServerError::Config(format!(
+                "OpenID Connect file {path} has no providers"
+            )));
+        }
+        Ok(Self::new(file.redirect_uri, file.providers))
+    }
+
+    /// Gets the OpenID Connect configuration given in help responses.
+    pub fn configuration(&self) -> OpenIdcConfiguration {
+        OpenIdcConfiguration::builder()
+            .dnt_supported(false)
+            .end_user_identifier_discovery_supported(false)
+            .issuer_identifier_supported(true)
+            .build()
+    }
+
+    /// Gets the providers given in help responses.
+    pub fn help_providers(&self) -> Vec<OpenIdcProvider> {
+        self.providers
+            .iter()
+            .map(|provider| {
+                OpenIdcProvider::builder()
+                    .iss(provider.issuer.clone())
+                    .name(provider.name.clone())
+                    .default(provider.default)
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Gets the provider with an issuer identifier, or the default provider if none is given.
+    fn provider(&self, issuer: Option<&str>) -> Option<&ProviderConfig> {
+        match issuer {
+            Some(issuer) => self.providers.iter().find(|p| p.issuer == issuer),
+            None => self
+                .providers
+                .iter()
+                .find(|p| p.default)
+                .or_else(|| (self.providers.len() == 1).then(|| &self.providers[0])),
+        }
+    }
+
+    async fn metadata(
+        &self,
+        provider: &ProviderConfig,
+    ) -> Result<ProviderMetadata, RdapServerError> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            provider.issuer.trim_end_matches('/')
+        );
+        Ok(self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ProviderMetadata>()
+            .await?)
+    }
+
+    async fn token(
+        &self,
+        provider: &ProviderConfig,
+        form: &[(&str, &str)],
+    ) -> Result<Option<TokenResponse>, RdapServerError> {
+        let metadata = self.metadata(provider).await?;
+        let response = self
+            .client
+            .post(metadata.token_endpoint)
+            .basic_auth(&provider.client_id, Some(&provider.client_secret))
+            .form(form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            tracing::debug!("token endpoint gave {}", response.status());
+            return Ok(None);
+        }
+        Ok(Some(response.json::<TokenResponse>().await?))
+    }
+
+    /// Gets the URL of the authorization endpoint of the provider the end-user is sent to,
+    /// along with the state of the login set in the login cookie. `None` is given if too
+    /// many logins are in progress.
+    async fn start_login(
+        &self,
+        provider: &ProviderConfig,
+    ) -> Result<Option<(String, String)>, RdapServerError> {
+        let metadata = self.metadata(provider).await?;
+        let state = random_id();
+        let nonce = random_id();
+        let code_verifier = random_id();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        let url = reqwest::Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", provider.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("scope", "openid"),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| RdapServerError::Config(format!("authorization endpoint is invalid: {e}")))?;
+        let mut logins = self.logins.lock().expect("openidc logins lock");
+        if !remove_ended(&mut logins, MAX_PENDING_LOGINS, PendingLogin::is_timed_out) {
+            tracing::warn!("login refused as {MAX_PENDING_LOGINS} logins are in progress");
+            return Ok(None);
+        }
+        logins.insert(
+            state.clone(),
+            PendingLogin {
+                issuer: provider.issuer.clone(),
+                nonce,
+                code_verifier,
+                started: SystemTime::now(),
+            },
+        );
+        Ok(Some((url.to_string(), state)))
+    }
+
+    /// Exchanges the authorization code given by the provider for tokens and creates
+    /// a session, giving the session identifier. `None` is given if the login is unknown,
+    /// was not started by the browser returning from the provider, as known by the state
+    /// in its login cookie, or the provider does not give a valid ID token.
+    async fn finish_login(
+        &self,
+        code: &str,
+        state: &str,
+        login_cookie: Option<&str>,
+    ) -> Result<Option<(String, UserSession)>, RdapServerError> {
+        // the login is kept for its own browser when another returns with its state.
+        if login_cookie != Some(state) {
+            tracing::debug!("login state does not match the login cookie");
+            return Ok(None);
+        }
+        let login = self
+            .logins
+            .lock()
+            .expect("openidc logins lock")
+            .remove(state);
+        let Some(login) = login else {
+            tracing::debug!("unknown login state");
+            return Ok(None);
+        };
+        if login.is_timed_out() {
+            return Ok(None);
+        }
+        let Some(provider) = self.provider(Some(&login.issuer)) else {
+            return Ok(None);
+        };
+        let Some(tokens) = self
+            .token(
+                provider,
+                &[
+                    ("grant_type", "authorization_code"),
+                    ("code", code),
+                    ("redirect_uri", &self.redirect_uri),
+                    ("code_verifier", &login.code_verifier),
+                ],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(claims) = tokens
+            .id_token
+            .as_deref()
+            .and_then(|id_token| id_token_claims(id_token, provider, Some(&login.nonce)))
+        else {
+            return Ok(None);
+        };
+        let session = UserSession {
+            issuer: provider.issuer.clone(),
+            expires_at: expires_at(tokens.expires_in, &claims),
+            claims,
+            refresh_token: tokens.refresh_token,
+            tier: provider.tier,
+            started: SystemTime::now(),
+        };
+        let id = random_id();
+        let mut sessions = self.sessions.lock().expect("openidc sessions lock");
+        make_room(
+            &mut sessions,
+            MAX_SESSIONS,
+            UserSession::is_ended,
+            |session| session.started,
+        );
+        sessions.insert(id.clone(), session.clone());
+        Ok(Some((id, session)))
+    }
+
+    /// Gets the session of the caller, removing it if it has ended.
+    fn session(&self, headers: &HeaderMap) -> Option<(String, UserSession)> {
+        let id = headers
+            .typed_get::<Cookie>()?
+            .get(SESSION_COOKIE)?
+            .to_string();
+        let mut sessions = self.sessions.lock().expect("openidc sessions lock");
+        let session = sessions.get(&id)?.clone();
+        if session.is_ended() {
+            sessions.remove(&id);
+            return None;
+        }
+        Some((id, session))
+    }
+
+    /// Refreshes the tokens of a session, giving the refreshed session or `None` if the
+    /// provider refuses the refresh.
+    async fn refresh(
+        &self,
+        id: &str,
+        session: UserSession,
+    ) -> Result<Option<UserSession>, RdapServerError> {
+        let Some(refresh_token) = session.refresh_token.as_deref() else {
+            return Ok(None);
+        };
+        let Some(provider) = self.provider(Some(&session.issuer)) else {
+            return Ok(None);
+        };
+        let Some(tokens) = self
+            .token(
+                provider,
+                &[
+                    ("grant_type", "refresh_token"),
+                    ("refresh_token", refresh_token),
+                ],
+            )
+            .await?
+        else {
+            self.sessions
+                .lock()
+                .expect("openidc sessions lock")
+                .remove(id);
+            return Ok(None);
+        };
+        let claims = match tokens.id_token.as_deref() {
+            Some(id_token) => {
+                let Some(claims) = id_token_claims(id_token, provider, None) else {
+                    return Ok(None);
+                };
+                claims
+            }
+            None => session.claims.clone(),
+        };
+        let refreshed = UserSession {
+            expires_at: expires_at(tokens.expires_in, &claims),
+            claims,
+            refresh_token: tokens.refresh_token.or(session.refresh_token),
+            ..session
+        };
+        self.sessions
+            .lock()
+            .expect("openidc sessions lock")
+            .insert(id.to_string(), refreshed.clone());
+        Ok(Some(refreshed))
+    }
+
+    fn logout(&self, id: &str) {
+        self.sessions
+            .lock()
+            .expect("openidc sessions lock")
+            .remove(id);
+    }
+
+    /// Gets the `Set-Cookie` value of a cookie. SameSite=Lax cookies are sent when the
+    /// provider returns the end-user, which is a top-level navigation.
+    fn cookie(&self, name: &str, value: &str, max_age: Duration) -> HeaderValue {
+        let mut cookie = format!(
+            "{name}={value}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}",
+            max_age.as_secs()
+        );
+        if self.redirect_uri.starts_with("https:") {
+            cookie.push_str("; Secure");
+        }
+        HeaderValue::from_str(&cookie).expect("cookie is a valid header value")
+    }
+}
+
+/// Removes the entries which have ended from a map holding at most `max` entries once it
+/// is full, giving true if there is room for another entry.
+fn remove_ended<V>(
+    map: &mut HashMap<String, V>,
+    max: usize,
+    is_ended: impl Fn(&V) -> bool,
+) -> bool {
+    if map.len() >= max {
+        map.retain(|_, value| !is_ended(value));
+    }
+    map.len() < max
+}
+
+/// Makes room for another entry in a map holding at most `max` entries, by removing the
+/// entries which have ended and then, if there is still no room, the oldest entry.
+fn make_room<V>(
+    map: &mut HashMap<String, V>,
+    max: usize,
+    is_ended: impl Fn(&V) -> bool,
+    started: impl Fn(&V) -> SystemTime,
+) {
+    if !remove_ended(map, max, is_ended) {
+        let oldest = map
+            .iter()
+            .min_by_key(|(_, value)| started(value))
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            map.remove(&oldest);
+        }
+    }
+}
+
+/// Gets the claims of an ID token, or `None` if it is not valid for the provider.
+///
+/// The signature of the token is not checked. The token comes directly from the token
+/// endpoint of the provider over TLS, which OpenID Connect Core 1.0 section 3.1.3.7
+/// allows to be used in place of checking the signature.
+fn id_token_claims(
+    id_token: &str,
+    provider: &ProviderConfig,
+    nonce: Option<&str>,
+) -> Option<Map<String, Value>> {
+    let payload = id_token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims = serde_json::from_slice::<Map<String, Value>>(&payload).ok()?;
+    let iss_matches = claims.get("iss").and_then(Value::as_str) == Some(&provider.issuer);
+    let aud_matches = match claims.get("aud") {
+        Some(Value::String(aud)) => *aud == provider.client_id,
+        Some(Value::Array(auds)) => auds.iter().any(|aud| *aud == *provider.client_id),
+        _ => false,
+    };
+    let exp = claims
+        .get("exp")
+        .and_then(Value::as_u64)
+        .unwrap_or_default();
+    let not_expired = SystemTime::UNIX_EPOCH + Duration::from_secs(exp) > SystemTime::now();
+    let nonce_matches =
+        nonce.is_none_or(|nonce| claims.get("nonce").and_then(Value::as_str) == Some(nonce));
+    if !(iss_matches && aud_matches && not_expired && nonce_matches) {
+        tracing::debug!("id token is not valid for {}", provider.issuer);
+        return None;
+    }
+    Some(claims)
+}
+
+/// Gets when the tokens of a session expire, from the token response or else from
+/// the expiration of the ID token.
+fn expires_at(expires_in: Option<u64>, claims: &Map<String, Value>) -> SystemTime {
+    match expires_in {
+        Some(expires_in) => SystemTime::now() + Duration::from_secs(expires_in),
+        None => {
+            let exp = claims
+                .get("exp")
+                .and_then(Value::as_u64)
+                .unwrap_or_default();
+            SystemTime::UNIX_EPOCH + Duration::from_secs(exp)
+        }
+    }
+}
+
+fn random_id() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn too_many_logins() -> RdapResponse {
+    RdapResponse::ErrorResponse(
+        Error::builder()
+            .error_code(503)
+            .title("Service Unavailable")
+            .description(vec![
+                "Too many logins are in progress. Try again later.".to_string()
+            ])
+            .common(Common::builder().build())
+            .build(),
+    )
+}
+
+fn session_response(title: &str, description: &str, session: Option<Session>) -> Response {
+    let response = SessionResponse::builder()
+        .common(
+            Common::level0()
+                .extension(Extension::from(ROIDC1))
+                .notice(Notice(
+                    NoticeOrRemark::builder()
+                        .title(title)
+                        .description(vec![description.to_string()])
+                        .build(),
+                ))
+                .build(),
+        )
+        .and_session(session)
+        .build();
+    (StatusCode::OK, RDAP_HEADERS, Json(response)).into_response()
+}
+
+/// The query parameters of the `login` path, both when the end-user starts a login
+/// and when the provider returns the end-user.
+#[derive(Debug, Deserialize)]
+pub struct LoginParams {
+    roidc1_iss: Option<String>,
+    roidc1_id: Option<String>,
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+/// Logs in an end-user. Without an authorization code, the end-user is redirected to
+/// the authorization endpoint of the provider and the login cookie is set. With an
+/// authorization code, the login is finished, the session cookie is set and the login
+/// cookie is cleared.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn login(
+    Query(params): Query<LoginParams>,
+    headers: HeaderMap,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let Some(openidc) = state.get_openidc() else {
+        return Ok(NOT_IMPLEMENTED.response());
+    };
+    if let (Some(code), Some(login_state)) = (&params.code, &params.state) {
+        let cookie = headers.typed_get::<Cookie>();
+        let login_cookie = cookie.as_ref().and_then(|cookie| cookie.get(LOGIN_COOKIE));
+        let Some((id, session)) = openidc
+            .finish_login(code, login_state, login_cookie)
+            .await?
+        else {
+            return Ok(UNAUTHORIZED.response());
+        };
+        let mut response = session_response(
+            "Login Result",
+            "Login succeeded",
+            Some(session.to_session()),
+        );
+        let headers = response.headers_mut();
+        headers.insert(
+            SET_COOKIE,
+            openidc.cookie(SESSION_COOKIE, &id, MAX_SESSION_LIFETIME),
+        );
+        headers.append(SET_COOKIE, openidc.cookie(LOGIN_COOKIE, "", Duration::ZERO));
+        return Ok(response);
+    }
+    if let Some(error) = &params.error {
+        tracing::debug!("provider refused login: {error}");
+        return Ok(UNAUTHORIZED.response());
+    }
+    if params.roidc1_id.is_some() {
+        return Ok(NOT_IMPLEMENTED.response());
+    }
+    let Some(provider) = openidc.provider(params.roidc1_iss.as_deref()) else {
+        return Ok(BAD_REQUEST.response());
+    };
+    let Some((location, login_state)) = openidc.start_login(provider).await? else {
+        return Ok(too_many_logins().response());
+    };
+    let location = HeaderValue::from_str(&location)
+        .map_err(|e| RdapServerError::Config(format!("authorization endpoint is invalid: {e}")))?;
+    let login_cookie = openidc.cookie(LOGIN_COOKIE, &login_state, LOGIN_TIMEOUT);
+    Ok((
+        StatusCode::FOUND,
+        [(LOCATION, location), (SET_COOKIE, login_cookie)],
+    )
+        .into_response())
+}
+
+/// Gets the session of the caller.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn session_status(
+    headers: HeaderMap,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let Some(openidc) = state.get_openidc() else {
+        return Ok(NOT_IMPLEMENTED.response());
+    };
+    let Some((_, session)) = openidc.session(&headers) else {
+        return Ok(NOT_FOUND.response());
+    };
+    Ok(session_response(
+        "Session Status Result",
+        "Session status succeeded",
+        Some(session.to_session()),
+    ))
+}
+
+/// Refreshes the tokens of the session of the caller.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn session_refresh(
+    headers: HeaderMap,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let Some(openidc) = state.get_openidc() else {
+        return Ok(NOT_IMPLEMENTED.response());
+    };
+    let Some((id, session)) = openidc.session(&headers) else {
+        return Ok(NOT_FOUND.response());
+    };
+    if session.refresh_token.is_none() {
+        return Ok(BAD_REQUEST.response());
+    }
+    let Some(session) = openidc.refresh(&id, session).await? else {
+        return Ok(UNAUTHORIZED.response());
+    };
+    Ok(session_response(
+        "Session Refresh Result",
+        "Session refresh succeeded",
+        Some(session.to_session()),
+    ))
+}
+
+/// Logs out the caller, ending their session.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn logout(
+    headers: HeaderMap,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let Some(openidc) = state.get_openidc() else {
+        return Ok(NOT_IMPLEMENTED.response());
+    };
+    let Some((id, _)) = openidc.session(&headers) else {
+        return Ok(NOT_FOUND.response());
+    };
+    openidc.logout(&id);
+    let mut response = session_response("Logout Result", "Logout succeeded", None);
+    response.headers_mut().insert(
+        SET_COOKIE,
+        openidc.cookie(SESSION_COOKIE, "", Duration::ZERO),
+    );
+    Ok(response)
+}
+
+/// Adds the access tier of the session of the caller to the request, unless the caller
+/// has already been given a tier by their `Authorization` header.
+pub(crate) async fn session_tier(
+    State(openidc): State<OpenIdc>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<AccessTier>().is_none() {
+        if let Some((_, session)) = openidc.session(request.headers()) {
+            if !session.is_expired() {
+                request.extensions_mut().insert(session.tier);
+            }
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime},
+    };
+
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use serde_json::{json, Map};
+
+    use crate::rdap::access::AccessTier;
+
+    use super::{
+        id_token_claims, make_room, remove_ended, ProviderConfig, UserSession, MAX_SESSION_LIFETIME,
+    };
+
+    fn provider() -> ProviderConfig {
+        ProviderConfig::builder()
+            .name("Example")
+            .issuer("https://idp.example.com")
+            .client_id("rdap")
+            .client_secret("secret")
+            .default(true)
+            .tier(AccessTier::Registrar)
+            .build()
+    }
+
+    fn id_token(claims: serde_json::Value) -> String {
+        format!(
+            "{}.{}.",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    fn exp() -> u64 {
+        chrono::Utc::now().timestamp() as u64 + 300
+    }
+
+    #[test]
+    fn GIVEN_valid_id_token_WHEN_id_token_claims_THEN_claims() {
+        // GIVEN
+        let token = id_token(json!({
+            "iss": "https://idp.example.com",
+            "aud": ["rdap", "other"],
+            "exp": exp(),
+            "nonce": "n-0S6",
+            "sub": "alice"
+        }));
+
+        // WHEN
+        let actual = id_token_claims(&token, &provider(), Some("n-0S6"));
+
+        // THEN
+        let actual = actual.expect("no claims");
+        assert_eq!(actual["sub"], "alice");
+    }
+
+    #[test]
+    fn GIVEN_wrong_nonce_WHEN_id_token_claims_THEN_none() {
+        // GIVEN
+        let token = id_token(json!({
+            "iss": "https://idp.example.com",
+            "aud": "rdap",
+            "exp": exp(),
+            "nonce": "replayed"
+        }));
+
+        // WHEN
+        let actual = id_token_claims(&token, &provider(), Some("n-0S6"));
+
+        // THEN
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn GIVEN_other_audience_WHEN_id_token_claims_THEN_none() {
+        // GIVEN
+        let token = id_token(json!({
+            "iss": "https://idp.example.com",
+            "aud": "other",
+            "exp": exp()
+        }));
+
+        // WHEN
+        let actual = id_token_claims(&token, &provider(), None);
+
+        // THEN
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn GIVEN_expired_id_token_WHEN_id_token_claims_THEN_none() {
+        // GIVEN
+        let token = id_token(json!({
+            "iss": "https://idp.example.com",
+            "aud": "rdap",
+            "exp": 1
+        }));
+
+        // WHEN
+        let actual = id_token_claims(&token, &provider(), None);
+
+        // THEN
+        assert!(actual.is_none());
+    }
+
+    fn session(started: SystemTime, expires_at: SystemTime) -> UserSession {
+        UserSession {
+            issuer: "https://idp.example.com".to_string(),
+            claims: Map::new(),
+            refresh_token: Some("refresh".to_string()),
+            expires_at,
+            tier: AccessTier::Registrar,
+            started,
+        }
+    }
+
+    #[test]
+    fn GIVEN_refreshable_session_past_lifetime_WHEN_is_ended_THEN_true() {
+        // GIVEN
+        let now = SystemTime::now();
+        let session = session(now - MAX_SESSION_LIFETIME, now + Duration::from_secs(300));
+
+        // WHEN
+        let actual = session.is_ended();
+
+        // THEN
+        assert!(actual);
+    }
+
+    #[test]
+    fn GIVEN_expired_refreshable_session_WHEN_is_ended_THEN_false() {
+        // GIVEN
+        let now = SystemTime::now();
+        let session = session(now, now - Duration::from_secs(1));
+
+        // WHEN
+        let actual = session.is_ended();
+
+        // THEN
+        assert!(!actual);
+    }
+
+    #[test]
+    fn GIVEN_full_map_WHEN_make_room_THEN_ended_entries_removed() {
+        // GIVEN
+        let now = SystemTime::now();
+        let mut map = HashMap::from([
+            ("ended".to_string(), (true, now)),
+            ("older".to_string(), (false, now - Duration::from_secs(60))),
+            ("newer".to_string(), (false, now)),
+        ]);
+
+        // WHEN
+        make_room(&mut map, 3, |(ended, _)| *ended, |(_, started)| *started);
+
+        // THEN
+        assert_eq!(map.len(), 2);
+        assert!(!map.contains_key("ended"));
+    }
+
+    #[test]
+    fn GIVEN_full_map_of_live_entries_WHEN_make_room_THEN_oldest_removed() {
+        // GIVEN
+        let now = SystemTime::now();
+        let mut map = HashMap::from([
+            ("older".to_string(), (false, now - Duration::from_secs(60))),
+            ("newer".to_string(), (false, now)),
+        ]);
+
+        // WHEN
+        make_room(&mut map, 2, |(ended, _)| *ended, |(_, started)| *started);
+
+        // THEN
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["newer"]);
+    }
+
+    #[test]
+    fn GIVEN_full_map_of_live_entries_WHEN_remove_ended_THEN_no_room_and_none_removed() {
+        // GIVEN
+        let mut map = HashMap::from([("first".to_string(), false), ("second".to_string(), false)]);
+
+        // WHEN
+        let actual = remove_ended(&mut map, 2, |ended| *ended);
+
+        // THEN
+        assert!(!actual);
+        assert_eq!(map.len(), 2);
+    }
+}
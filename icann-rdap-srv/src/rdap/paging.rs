@@ -0,0 +1,374 @@
+use std::cmp::Ordering;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use http::Uri;
+use icann_rdap_common::response::{
+    domain::Domain,
+    entity::Entity,
+    nameserver::Nameserver,
+    search::{AvailableSort, PagingMetadata, SortingMetadata},
+    types::{Common, Extension, Link, ObjectCommon},
+    RdapResponse,
+};
+use serde::Deserialize;
+
+use crate::rdap::response::BAD_REQUEST;
+
+/// The RDAP conformance value of RFC 8977 paging.
+pub const PAGING: &str = "paging";
+
+/// The RDAP conformance value of RFC 8977 sorting.
+pub const SORTING: &str = "sorting";
+
+/// The maximum number of results of the storage searches that are paged, so that all the
+/// results of a search are sorted before the page at the cursor is taken.
+pub(crate) const ALL_RESULTS: usize = usize::MAX;
+
+/// The query parameters of RFC 8977 paging and sorting, which may be given with any search.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct PagingParams {
+    // parsed when paging so that an invalid value is given an RDAP error.
+    count: Option<String>,
+    sort: Option<String>,
+    cursor: Option<String>,
+}
+
+/// An object found by a search that can be sorted.
+trait Sortable {
+    /// The sorting properties and the JSONPath of their values. The first is the default sort.
+    const SORTS: &'static [(&'static str, &'static str)];
+
+    /// Gets the value of the object sorted on for a property.
+    fn sort_value(&self, property: &str) -> Option<String>;
+}
+
+/// Gets the date of the first event with the action.
+fn event_date(object_common: &ObjectCommon, action: &str) -> Option<String> {
+    object_common
+        .events
+        .iter()
+        .flatten()
+        .find(|event| event.event_action == action)
+        .and_then(|event| event.event_date.clone())
+}
+
+impl Sortable for Domain {
+    const SORTS: &'static [(&'static str, &'static str)] = &[
+        ("name", "$.domainSearchResults[*].ldhName"),
+        (
+            "registrationDate",
+            r#"$.domainSearchResults[*].events[?(@.eventAction=="registration")].eventDate"#,
+        ),
+        (
+            "expirationDate",
+            r#"$.domainSearchResults[*].events[?(@.eventAction=="expiration")].eventDate"#,
+        ),
+        (
+            "lastChangedDate",
+            r#"$.domainSearchResults[*].events[?(@.eventAction=="last changed")].eventDate"#,
+        ),
+    ];
+
+    fn sort_value(&self, property: &str) -> Option<String> {
+        match property {
+            "name" => self.ldh_name.as_ref().map(|name| name.to_lowercase()),
+            "registrationDate" => event_date(&self.object_common, "registration"),
+            "expirationDate" => event_date(&self.object_common, "expiration"),
+            "lastChangedDate" => event_date(&self.object_common, "last changed"),
+            _ => None,
+        }
+    }
+}
+
+impl Sortable for Nameserver {
+    const SORTS: &'static [(&'static str, &'static str)] =
+        &[("name", "$.nameserverSearchResults[*].ldhName")];
+
+    fn sort_value(&self, property: &str) -> Option<String> {
+        match property {
+            "name" => self.ldh_name.as_ref().map(|name| name.to_lowercase()),
+            _ => None,
+        }
+    }
+}
+
+impl Sortable for Entity {
+    const SORTS: &'static [(&'static str, &'static str)] = &[
+        ("handle", "$.entitySearchResults[*].handle"),
+        (
+            "fn",
+            r#"$.entitySearchResults[*].vcardArray[1][?(@[0]=="fn")][3]"#,
+        ),
+    ];
+
+    fn sort_value(&self, property: &str) -> Option<String> {
+        match property {
+            "handle" => self
+                .object_common
+                .handle
+                .as_ref()
+                .map(|handle| handle.to_lowercase()),
+            "fn" => self
+                .contact()
+                .and_then(|contact| contact.full_name)
+                .map(|full_name| full_name.to_lowercase()),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a sort parameter such as `name:d,registrationDate:a` into the properties
+/// and if they are descending. The order defaults to ascending.
+fn parse_sort<T: Sortable>(sort: &str) -> Option<Vec<(&str, bool)>> {
+    sort.split(',')
+        .map(|property| {
+            let (property, order) = property.split_once(':').unwrap_or((property, "a"));
+            let descending = match order {
+                "a" => false,
+                "d" => true,
+                _ => return None,
+            };
+            T::SORTS
+                .iter()
+                .any(|(name, _)| *name == property)
+                .then_some((property, descending))
+        })
+        .collect()
+}
+
+/// Compares sort values. Objects without a value are sorted last in either order.
+fn compare_values(a: Option<String>, b: Option<String>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Gets the search offset from a cursor.
+fn decode_cursor(cursor: &str) -> Option<usize> {
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(decoded)
+        .ok()?
+        .strip_prefix("offset=")?
+        .parse()
+        .ok()
+}
+
+/// Gets the cursor of a search offset.
+fn encode_cursor(offset: usize) -> String {
+    URL_SAFE_NO_PAD.encode(format!("offset={offset}"))
+}
+
+/// Gets the path and query of the request URI with the named query parameters removed and
+/// the parameter given appended.
+pub(crate) fn replace_params(uri: &Uri, remove: &[&str], append: Option<(&str, &str)>) -> String {
+    let mut params = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| {
+            let name = param.split_once('=').map_or(*param, |(name, _)| name);
+            !param.is_empty() && !remove.contains(&name)
+        })
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    if let Some((name, value)) = append {
+        params.push(format!("{name}={value}"));
+    }
+    format!("{}?{}", uri.path(), params.join("&"))
+}
+
+/// Gets an RDAP link from the request URI to a related search.
+pub(crate) fn rdap_link(value: &str, rel: &str, href: String) -> Link {
+    Link::builder()
+        .value(value)
+        .rel(rel)
+        .href(href)
+        .media_type("application/rdap+json")
+        .build()
+}
+
+/// Sorts and pages the found objects, giving the paging and sorting metadata, or `None`
+/// if the paging parameters are invalid.
+fn page<T: Sortable>(
+    results: &mut Vec<T>,
+    params: &PagingParams,
+    uri: &Uri,
+    page_size: usize,
+) -> Option<(PagingMetadata, SortingMetadata)> {
+    let count = match params.count.as_deref() {
+        Some(count) => count.parse::<bool>().ok()?,
+        None => false,
+    };
+    let sort = match params.sort.as_deref() {
+        Some(sort) => parse_sort::<T>(sort)?,
+        None => vec![(T::SORTS[0].0, false)],
+    };
+    let offset = match params.cursor.as_deref() {
+        Some(cursor) => decode_cursor(cursor)?,
+        None => 0,
+    };
+
+    results.sort_by(|a, b| {
+        sort.iter()
+            .map(|(property, descending)| {
+                compare_values(a.sort_value(property), b.sort_value(property), *descending)
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    let total_count = results.len();
+    let mut page = results.split_off(offset.min(total_count));
+    page.truncate(page_size);
+    *results = page;
+
+    let value = uri.to_string();
+    let mut links = vec![];
+    if offset + page_size < total_count {
+        let href = replace_params(
+            uri,
+            &["cursor"],
+            Some(("cursor", &encode_cursor(offset + page_size))),
+        );
+        links.push(rdap_link(&value, "next", href));
+    }
+    let paging_metadata = PagingMetadata::builder()
+        .and_total_count(count.then_some(total_count as u64))
+        .page_size(page_size as u64)
+        .page_number((offset / page_size + 1) as u64)
+        .and_links((!links.is_empty()).then_some(links))
+        .build();
+
+    let available_sorts = T::SORTS
+        .iter()
+        .enumerate()
+        .map(|(index, (property, json_path))| {
+            let href = replace_params(uri, &["cursor", "sort"], Some(("sort", property)));
+            AvailableSort::builder()
+                .property(*property)
+                .json_path(*json_path)
+                .default(index == 0)
+                .links(vec![rdap_link(&value, "alternate", href)])
+                .build()
+        })
+        .collect::<Vec<AvailableSort>>();
+    let sorting_metadata = SortingMetadata::builder()
+        .and_current_sort(params.sort.clone())
+        .available_sorts(available_sorts)
+        .build();
+
+    Some((paging_metadata, sorting_metadata))
+}
+
+/// Adds the paging and sorting RDAP conformance values.
+fn add_conformance(common: &mut Common) {
+    let conformance = common.rdap_conformance.get_or_insert_with(Vec::new);
+    conformance.push(Extension(PAGING.to_string()));
+    conformance.push(Extension(SORTING.to_string()));
+}
+
+/// Applies RFC 8977 paging and sorting to all the results of a search, as found with
+/// [ALL_RESULTS].
+///
+/// The results are sorted and only the page at the cursor is kept, with a `next` link
+/// in the paging metadata to the following page. Pages hold at most `max_results` results.
+/// As cursors are offsets into the search, the results of the search should be in a
+/// consistent order. The total count is only given when asked for. A 400 is given for an
+/// invalid `count`, an unknown sort property or an invalid cursor.
+pub(crate) fn page_search_results(
+    response: RdapResponse,
+    params: &PagingParams,
+    uri: &Uri,
+    page_size: usize,
+    max_results: usize,
+) -> RdapResponse {
+    let page_size = page_size.min(max_results).max(1);
+    match response {
+        RdapResponse::DomainSearchResults(mut results) => {
+            let Some((paging, sorting)) = page(&mut results.results, params, uri, page_size) else {
+                return BAD_REQUEST.clone();
+            };
+            add_conformance(&mut results.common);
+            results.paging_metadata = Some(paging);
+            results.sorting_metadata = Some(sorting);
+            RdapResponse::DomainSearchResults(results)
+        }
+        RdapResponse::NameserverSearchResults(mut results) => {
+            let Some((paging, sorting)) = page(&mut results.results, params, uri, page_size) else {
+                return BAD_REQUEST.clone();
+            };
+            add_conformance(&mut results.common);
+            results.paging_metadata = Some(paging);
+            results.sorting_metadata = Some(sorting);
+            RdapResponse::NameserverSearchResults(results)
+        }
+        RdapResponse::EntitySearchResults(mut results) => {
+            let Some((paging, sorting)) = page(&mut results.results, params, uri, page_size) else {
+                return BAD_REQUEST.clone();
+            };
+            add_conformance(&mut results.common);
+            results.paging_metadata = Some(paging);
+            results.sorting_metadata = Some(sorting);
+            RdapResponse::EntitySearchResults(results)
+        }
+        _ => response,
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+
+    use http::Uri;
+    use icann_rdap_common::response::domain::Domain;
+    use rstest::rstest;
+
+    use super::{decode_cursor, encode_cursor, parse_sort, replace_params};
+
+    #[test]
+    fn GIVEN_offset_WHEN_encode_cursor_THEN_decodes_to_offset() {
+        // GIVEN
+        let offset = 25;
+
+        // WHEN
+        let cursor = encode_cursor(offset);
+
+        // THEN
+        assert_eq!(decode_cursor(&cursor), Some(offset));
+    }
+
+    #[rstest]
+    #[case("name", Some(vec![("name", false)]))]
+    #[case("name:d,registrationDate:a", Some(vec![("name", true), ("registrationDate", false)]))]
+    #[case("name:x", None)]
+    #[case("handle", None)]
+    fn GIVEN_sort_WHEN_parse_sort_THEN_correct(
+        #[case] sort: &str,
+        #[case] expected: Option<Vec<(&str, bool)>>,
+    ) {
+        // GIVEN case input
+
+        // WHEN
+        let actual = parse_sort::<Domain>(sort);
+
+        // THEN
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn GIVEN_uri_with_cursor_WHEN_replace_params_THEN_cursor_replaced() {
+        // GIVEN
+        let uri = "/rdap/domains?name=*.example&cursor=abc&sort=name"
+            .parse::<Uri>()
+            .expect("parsing uri");
+
+        // WHEN
+        let actual = replace_params(&uri, &["cursor"], Some(("cursor", "def")));
+
+        // THEN
+        assert_eq!(actual, "/rdap/domains?name=*.example&sort=name&cursor=def");
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::{from_fn_with_state, Next},
+    response::Response,
+    Router,
+};
+use axum_client_ip::SecureClientIp;
+use http::{header::RETRY_AFTER, HeaderValue};
+use icann_rdap_common::response::{error::Error, types::Common, RdapResponse};
+
+use crate::{config::RateLimit, rdap::response::ResponseUtil, server::DynServiceState};
+
+/// The most clients whose buckets are kept. Once reached, the buckets which have refilled
+/// are dropped, and then if need be the buckets of the clients least recently seen, so
+/// that half as many are kept.
+const MAX_BUCKETS: usize = 10_000;
+
+/// The tokens of a client. A request takes a token, and tokens are added back at
+/// the rate limit up to the burst.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets of each client IP address for a class of routes.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    rate_limit: RateLimit,
+    buckets: Arc<Mutex<HashMap<IpAddr, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(rate_limit: RateLimit) -> Self {
+        Self {
+            rate_limit,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The number of tokens added back each second.
+    fn tokens_per_sec(&self) -> f64 {
+        f64::from(self.rate_limit.per_minute) / 60.0
+    }
+
+    /// Takes a token from the bucket of the client, giving the number of seconds
+    /// to wait before retrying if there are no tokens left.
+    fn take(&self, ip: IpAddr, now: Instant) -> Result<(), u64> {
+        let burst = f64::from(self.rate_limit.burst);
+        let tokens_per_sec = self.tokens_per_sec();
+        let client = client_key(ip);
+        let mut buckets = self.buckets.lock().expect("rate limit buckets poisoned");
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&client) {
+            buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed * tokens_per_sec < burst
+            });
+            if buckets.len() > MAX_BUCKETS / 2 {
+                let mut seen = buckets
+                    .values()
+                    .map(|bucket| bucket.updated)
+                    .collect::<Vec<_>>();
+                seen.sort_unstable();
+                let oldest_kept = seen[seen.len() - MAX_BUCKETS / 2];
+                buckets.retain(|_, bucket| bucket.updated >= oldest_kept);
+            }
+        }
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * tokens_per_sec).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let retry_after = ((1.0 - bucket.tokens) / tokens_per_sec).ceil() as u64;
+            Err(retry_after.max(1))
+        }
+    }
+}
+
+/// Gets the client whose bucket a request from the IP address takes a token from. IPv6
+/// clients are limited by their /64 network, as a host is commonly given a whole /64 and
+/// could otherwise take a bucket for each of its addresses.
+fn client_key(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & (u128::MAX << 64))),
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Limits the requests to the routes of a router with the rate limit, if there is one.
+pub(crate) fn rate_limited(
+    router: Router<DynServiceState>,
+    rate_limit: Option<RateLimit>,
+) -> Router<DynServiceState> {
+    match rate_limit {
+        Some(rate_limit) => router.route_layer(from_fn_with_state(
+            RateLimiter::new(rate_limit),
+            limit_requests,
+        )),
+        None => router,
+    }
+}
+
+/// Gives a 429 with a `Retry-After` header when the client has sent more requests
+/// than the rate limit allows. Requests from clients without a known IP address
+/// are not limited.
+async fn limit_requests(
+    State(limiter): State<RateLimiter>,
+    client_ip: Option<SecureClientIp>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(SecureClientIp(ip)) = client_ip else {
+        tracing::debug!("client IP address unknown, request not rate limited");
+        return next.run(request).await;
+    };
+    match limiter.take(ip, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            tracing::debug!("rate limit exceeded by {ip}");
+            let mut response = too_many_requests().response();
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
+
+fn too_many_requests() -> RdapResponse {
+    RdapResponse::ErrorResponse(
+        Error::builder()
+            .error_code(429)
+            .title("Too Many Requests")
+            .description(vec![
+                "The rate limit of requests has been exceeded. Retry after the time given."
+                    .to_string(),
+            ])
+            .common(Common::builder().build())
+            .build(),
+    )
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        time::{Duration, Instant},
+    };
+
+    use crate::config::RateLimit;
+
+    use super::{RateLimiter, MAX_BUCKETS};
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    #[test]
+    fn GIVEN_burst_taken_WHEN_take_THEN_retry_after_given() {
+        // GIVEN
+        let limiter = RateLimiter::new(RateLimit {
+            per_minute: 6,
+            burst: 2,
+        });
+        let now = Instant::now();
+        limiter.take(CLIENT, now).expect("first request");
+        limiter.take(CLIENT, now).expect("second request");
+
+        // WHEN
+        let actual = limiter.take(CLIENT, now);
+
+        // THEN
+        assert_eq!(actual, Err(10));
+    }
+
+    #[test]
+    fn GIVEN_burst_taken_WHEN_take_after_refill_THEN_ok() {
+        // GIVEN
+        let limiter = RateLimiter::new(RateLimit {
+            per_minute: 6,
+            burst: 1,
+        });
+        let now = Instant::now();
+        limiter.take(CLIENT, now).expect("first request");
+
+        // WHEN
+        let actual = limiter.take(CLIENT, now + Duration::from_secs(10));
+
+        // THEN
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn GIVEN_burst_taken_by_client_WHEN_take_by_other_client_THEN_ok() {
+        // GIVEN
+        let limiter = RateLimiter::new(RateLimit {
+            per_minute: 6,
+            burst: 1,
+        });
+        let now = Instant::now();
+        limiter.take(CLIENT, now).expect("first request");
+
+        // WHEN
+        let actual = limiter.take(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)), now);
+
+        // THEN
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn GIVEN_burst_taken_by_ipv6_client_WHEN_take_by_same_64_THEN_retry_after_given() {
+        // GIVEN
+        let limiter = RateLimiter::new(RateLimit {
+            per_minute: 6,
+            burst: 1,
+        });
+        let now = Instant::now();
+        let client = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 1, 0, 0, 0, 1));
+        limiter.take(client, now).expect("first request");
+
+        // WHEN
+        let other = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 1, 0xffff, 0, 0, 2));
+        let actual = limiter.take(other, now);
+
+        // THEN
+        assert_eq!(actual, Err(10));
+    }
+
+    #[test]
+    fn GIVEN_max_buckets_WHEN_take_by_new_client_THEN_least_recently_seen_dropped() {
+        // GIVEN
+        let limiter = RateLimiter::new(RateLimit {
+            per_minute: 6,
+            burst: 2,
+        });
+        let start = Instant::now();
+        for n in 0..MAX_BUCKETS as u32 {
+            let client = IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + n));
+            limiter
+                .take(client, start + Duration::from_millis(u64::from(n)))
+                .expect("request");
+        }
+
+        // WHEN
+        let now = start + Duration::from_millis(MAX_BUCKETS as u64);
+        limiter.take(CLIENT, now).expect("request");
+
+        // THEN
+        let buckets = limiter.buckets.lock().expect("rate limit buckets poisoned");
+        assert_eq!(buckets.len(), MAX_BUCKETS / 2 + 1);
+        assert!(!buckets.contains_key(&IpAddr::V4(Ipv4Addr::from(0x0a00_0000))));
+        assert!(buckets.contains_key(&CLIENT));
+    }
+}
@@ -0,0 +1,790 @@
+use std::fs;
+
+use icann_rdap_common::response::{
+    domain::Domain,
+    entity::Entity,
+    nameserver::Nameserver,
+    redacted::{Method, Name, Reason, Redacted},
+    types::{Common, Extension, ObjectCommon},
+    RdapResponse,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{error::RdapServerError, rdap::access::AccessTier};
+
+/// The RDAP conformance value of RFC 9537 redaction.
+pub const REDACTED: &str = "redacted";
+
+/// A property of an entity that is replaced by another in the `replacementValue` method,
+/// such as an email address replaced by a contact URI.
+#[derive(Debug, Clone, Deserialize)]
+struct Replacement {
+    /// The name of the vCard property put in place of the redacted property.
+    property: String,
+
+    /// The vCard value type of the property, such as `text` or `uri`.
+    #[serde(rename = "type")]
+    value_type: String,
+
+    /// The value of the property.
+    value: String,
+}
+
+/// A rule of the redaction policy, which redacts a property of the entities with a role
+/// for callers of the given access tiers.
+#[derive(Debug, Clone, Deserialize)]
+struct RedactionRule {
+    /// The name of the redacted field, such as `Registrant Email`.
+    name: String,
+
+    /// The access tiers of the callers given the redacted data.
+    tiers: Vec<AccessTier>,
+
+    /// A role of the entities redacted, such as `registrant`.
+    role: String,
+
+    /// Either `handle` or the name of a vCard property, such as `email` or `tel`.
+    property: String,
+
+    /// The method of redaction. The `partialValue` method is not supported.
+    method: Method,
+
+    /// The reason for the redaction, such as `Server policy`.
+    reason: Option<String>,
+
+    /// The replacement property of the `replacementValue` method.
+    replacement: Option<Replacement>,
+}
+
+/// The contents of the redaction policy file.
+#[derive(Debug, Deserialize)]
+struct RedactionPolicyFile {
+    rules: Vec<RedactionRule>,
+}
+
+/// The policy used to redact domains, nameservers and entities according to RFC 9537.
+///
+/// Entities of domain, nameserver and entity responses, including the entities of the
+/// nameservers of domains and the entities nested in them, are redacted at query time
+/// according to the access tier of the caller. Each redaction is described in the
+/// `redacted` member of the object, with JSONPath expressions relative to that object.
+/// Searches by a redacted property of entities are refused, so that what they find does
+/// not reveal the redacted values.
+#[derive(Debug, Clone, Default)]
+pub struct RedactionPolicy {
+    rules: Vec<RedactionRule>,
+}
+
+impl RedactionPolicy {
+    /// Loads the policy from a JSON policy file such as:
+    ///
+    /// ```json
+    /// {
+    ///   "rules": [
+    ///     {
+    ///       "name": "Registrant Email",
+    ///       "tiers": ["public"],
+    ///       "role": "registrant",
+    ///       "property": "email",
+    ///       "method": "removal",
+    ///       "reason": "Server policy"
+    ///     },
+    ///     {
+    ///       "name": "Tech Phone",
+    ///       "tiers": ["public", "registrar"],
+    ///       "role": "technical",
+    ///       "property": "tel",
+    ///       "method": "emptyValue"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    pub fn load(path: &str) -> Result<Self, RdapServerError> {
+        let contents = fs::read_to_string(path)?;
+        let file = serde_json::from_str::<RedactionPolicyFile>(&contents).map_err(|e| {
+            RdapServerError::Config(format!("redaction policy file {path} is invalid: {e}"))
+        })?;
+        for rule in &file.rules {
+            let valid = match rule.method {
+                Method::Removal | Method::EmptyValue => true,
+                Method::ReplacementValue => {
+                    rule.replacement.is_some() && !rule.property.eq_ignore_ascii_case("handle")
+                }
+                Method::PartialValue => false,
+            };
+            if !valid {
+                return Err(RdapServerError::Config(format!(
+                    "redaction rule '{}' cannot use the {} method",
+                    rule.name, rule.method
+                )));
+            }
+        }
+        Ok(Self { rules: file.rules })
+    }
+
+    /// Redacts the domains, nameservers and entities of the response for a caller of the
+    /// access tier.
+    pub(crate) fn redact(&self, response: RdapResponse, tier: AccessTier) -> RdapResponse {
+        let rules = self
+            .rules
+            .iter()
+            .filter(|rule| rule.tiers.contains(&tier))
+            .collect::<Vec<&RedactionRule>>();
+        if rules.is_empty() {
+            return response;
+        }
+        match response {
+            RdapResponse::Domain(mut domain) => {
+                if redact_domain(&mut domain, &rules) {
+                    add_conformance(&mut domain.common);
+                }
+                RdapResponse::Domain(domain)
+            }
+            RdapResponse::Entity(mut entity) => {
+                if redact_entity(&mut entity, &rules) {
+                    add_conformance(&mut entity.common);
+                }
+                RdapResponse::Entity(entity)
+            }
+            RdapResponse::Nameserver(mut nameserver) => {
+                if redact_nameserver(&mut nameserver, &rules) {
+                    add_conformance(&mut nameserver.common);
+                }
+                RdapResponse::Nameserver(nameserver)
+            }
+            RdapResponse::DomainSearchResults(mut results) => {
+                let mut redacted = false;
+                for domain in &mut results.results {
+                    redacted |= redact_domain(domain, &rules);
+                }
+                if redacted {
+                    add_conformance(&mut results.common);
+                }
+                RdapResponse::DomainSearchResults(results)
+            }
+            RdapResponse::NameserverSearchResults(mut results) => {
+                let mut redacted = false;
+                for nameserver in &mut results.results {
+                    redacted |= redact_nameserver(nameserver, &rules);
+                }
+                if redacted {
+                    add_conformance(&mut results.common);
+                }
+                RdapResponse::NameserverSearchResults(results)
+            }
+            RdapResponse::EntitySearchResults(mut results) => {
+                let mut redacted = false;
+                for entity in &mut results.results {
+                    redacted |= redact_entity(entity, &rules);
+                }
+                if redacted {
+                    add_conformance(&mut results.common);
+                }
+                RdapResponse::EntitySearchResults(results)
+            }
+            _ => response,
+        }
+    }
+}
+
+/// Gives true if the policy has rules for the tier, in which case domains, nameservers and
+/// entities served to the tier may be redacted.
+pub(crate) fn redacts(policy: Option<&RedactionPolicy>, tier: AccessTier) -> bool {
+    policy.is_some_and(|policy| policy.rules.iter().any(|rule| rule.tiers.contains(&tier)))
+}
+
+/// Gives true if the policy redacts the property, either `handle` or the name of a vCard
+/// property, of any entities served to the tier. Searching by such a property would
+/// reveal the redacted values by what is found, as warned of in RFC 9536.
+pub(crate) fn redacts_property(
+    policy: Option<&RedactionPolicy>,
+    tier: AccessTier,
+    property: &str,
+) -> bool {
+    policy.is_some_and(|policy| {
+        policy
+            .rules
+            .iter()
+            .any(|rule| rule.tiers.contains(&tier) && rule.property.eq_ignore_ascii_case(property))
+    })
+}
+
+/// Redacts the response if there is a redaction policy.
+pub(crate) fn redact(
+    response: RdapResponse,
+    policy: Option<&RedactionPolicy>,
+    tier: AccessTier,
+) -> RdapResponse {
+    match policy {
+        Some(policy) => policy.redact(response, tier),
+        None => response,
+    }
+}
+
+/// Redacts the entities of a domain and of its nameservers, giving true if anything
+/// was redacted.
+fn redact_domain(domain: &mut Domain, rules: &[&RedactionRule]) -> bool {
+    let mut redactions = vec![];
+    redact_entities(&mut domain.object_common, rules, "$", &mut redactions);
+    for (index, nameserver) in domain.nameservers.iter_mut().flatten().enumerate() {
+        let path = format!("$.nameservers[{}]", nameserver_selector(nameserver, index));
+        redact_entities(&mut nameserver.object_common, rules, &path, &mut redactions);
+    }
+    add_redactions(&mut domain.object_common, redactions)
+}
+
+/// Redacts the entities of a nameserver, giving true if anything was redacted.
+fn redact_nameserver(nameserver: &mut Nameserver, rules: &[&RedactionRule]) -> bool {
+    let mut redactions = vec![];
+    redact_entities(&mut nameserver.object_common, rules, "$", &mut redactions);
+    add_redactions(&mut nameserver.object_common, redactions)
+}
+
+/// Redacts an entity by the rules for any of its roles, and the entities nested in it,
+/// giving true if anything was redacted. An entity without roles is redacted by all the
+/// rules, as the roles it has in other objects are not known.
+fn redact_entity(entity: &mut Entity, rules: &[&RedactionRule]) -> bool {
+    let mut redactions = vec![];
+    let has_roles = entity.roles.as_ref().is_some_and(|roles| !roles.is_empty());
+    for rule in rules {
+        if !has_roles || has_role(entity, &rule.role) {
+            redactions.extend(redact_property(entity, rule, "$"));
+        }
+    }
+    redact_entities(&mut entity.object_common, rules, "$", &mut redactions);
+    add_redactions(&mut entity.object_common, redactions)
+}
+
+/// Redacts the entities of an object by the rules for any of their roles, along with the
+/// entities nested in them. The paths of the redactions start with the path of the object.
+fn redact_entities(
+    object_common: &mut ObjectCommon,
+    rules: &[&RedactionRule],
+    path: &str,
+    redactions: &mut Vec<Redacted>,
+) {
+    for (index, entity) in object_common.entities.iter_mut().flatten().enumerate() {
+        let path = format!("{path}.entities[{}]", entity_selector(entity, index));
+        for rule in rules {
+            if has_role(entity, &rule.role) {
+                redactions.extend(redact_property(entity, rule, &path));
+            }
+        }
+        redact_entities(&mut entity.object_common, rules, &path, redactions);
+    }
+}
+
+fn add_redactions(object_common: &mut ObjectCommon, redactions: Vec<Redacted>) -> bool {
+    if redactions.is_empty() {
+        return false;
+    }
+    object_common
+        .redacted
+        .get_or_insert_with(Vec::new)
+        .extend(redactions);
+    true
+}
+
+fn add_conformance(common: &mut Common) {
+    let conformance = common.rdap_conformance.get_or_insert_with(Vec::new);
+    let redacted = Extension(REDACTED.to_string());
+    if !conformance.contains(&redacted) {
+        conformance.push(redacted);
+    }
+}
+
+fn has_role(entity: &Entity, role: &str) -> bool {
+    entity
+        .roles
+        .iter()
+        .flatten()
+        .any(|entity_role| entity_role.eq_ignore_ascii_case(role))
+}
+
+/// Gets the JSONPath selector of an entity among the entities of an object, which selects
+/// it by its first role, or by its index if it has no roles.
+fn entity_selector(entity: &Entity, index: usize) -> String {
+    match entity.roles.as_ref().and_then(|roles| roles.first()) {
+        Some(role) => format!("?(@.roles[0]=='{role}')"),
+        None => index.to_string(),
+    }
+}
+
+/// Gets the JSONPath selector of a nameserver among the nameservers of a domain, which
+/// selects it by its LDH name, or by its index if it has none.
+fn nameserver_selector(nameserver: &Nameserver, index: usize) -> String {
+    match &nameserver.ldh_name {
+        Some(ldh_name) => format!("?(@.ldhName=='{ldh_name}')"),
+        None => index.to_string(),
+    }
+}
+
+/// Redacts the property of the rule from the entity, giving the redaction if the
+/// entity had the property. The paths of the redaction start with the prefix.
+fn redact_property(entity: &mut Entity, rule: &RedactionRule, prefix: &str) -> Option<Redacted> {
+    if rule.property.eq_ignore_ascii_case("handle") {
+        entity.object_common.handle.as_ref()?;
+        let path = format!("{prefix}.handle");
+        let redacted = match rule.method {
+            Method::EmptyValue => {
+                entity.object_common.handle = Some(String::new());
+                redaction(rule, None, Some(path), None)
+            }
+            _ => {
+                entity.object_common.handle = None;
+                redaction(rule, Some(path), None, None)
+            }
+        };
+        return Some(redacted);
+    }
+
+    let property = rule.property.to_ascii_lowercase();
+    let Some(Value::Array(properties)) = entity
+        .vcard_array
+        .as_mut()
+        .and_then(|vcard_array| vcard_array.get_mut(1))
+    else {
+        return None;
+    };
+    if !properties.iter().any(|p| is_property(p, &property)) {
+        return None;
+    }
+    let path = format!("{prefix}.vcardArray[1][?(@[0]=='{property}')]");
+    let redacted = match (&rule.method, &rule.replacement) {
+        (Method::EmptyValue, _) => {
+            for value in properties
+                .iter_mut()
+                .filter(|p| is_property(p, &property))
+                .filter_map(|p| p.get_mut(3))
+            {
+                *value = empty_value(value);
+            }
+            redaction(rule, None, Some(format!("{path}[3]")), None)
+        }
+        (Method::ReplacementValue, Some(replacement)) => {
+            properties.retain(|p| !is_property(p, &property));
+            let replacement_property = replacement.property.to_ascii_lowercase();
+            properties.push(Value::Array(vec![
+                Value::String(replacement_property.clone()),
+                Value::Object(Default::default()),
+                Value::String(replacement.value_type.clone()),
+                Value::String(replacement.value.clone()),
+            ]));
+            let replacement_path =
+                format!("{prefix}.vcardArray[1][?(@[0]=='{replacement_property}')]");
+            redaction(rule, Some(path), None, Some(replacement_path))
+        }
+        _ => {
+            properties.retain(|p| !is_property(p, &property));
+            redaction(rule, Some(path), None, None)
+        }
+    };
+    Some(redacted)
+}
+
+/// Gets the redaction of a rule with its paths.
+fn redaction(
+    rule: &RedactionRule,
+    pre_path: Option<String>,
+    post_path: Option<String>,
+    replacement_path: Option<String>,
+) -> Redacted {
+    Redacted {
+        name: Name {
+            description: None,
+            type_field: Some(rule.name.clone()),
+        },
+        reason: rule.reason.as_ref().map(|reason| Reason {
+            description: Some(reason.clone()),
+            type_field: None,
+        }),
+        pre_path,
+        post_path,
+        path_lang: Some("jsonpath".to_string()),
+        replacement_path,
+        method: Some(rule.method.clone()),
+    }
+}
+
+fn is_property(property: &Value, name: &str) -> bool {
+    property
+        .get(0)
+        .and_then(Value::as_str)
+        .is_some_and(|property_name| property_name.eq_ignore_ascii_case(name))
+}
+
+/// Gets the empty value of a vCard value, keeping the structure of structured values.
+fn empty_value(value: &Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.iter().map(empty_value).collect()),
+        _ => Value::String(String::new()),
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use icann_rdap_common::{
+        contact::{Contact, Email, Phone},
+        response::{
+            domain::Domain, entity::Entity, nameserver::Nameserver, redacted::Method,
+            types::Extension, RdapResponse,
+        },
+    };
+
+    use crate::rdap::access::AccessTier;
+
+    use super::{redacts_property, RedactionPolicy, RedactionRule, Replacement};
+
+    fn rule(role: &str, property: &str, method: Method) -> RedactionRule {
+        RedactionRule {
+            name: "Redacted Field".to_string(),
+            tiers: vec![AccessTier::Public],
+            role: role.to_string(),
+            property: property.to_string(),
+            method,
+            reason: Some("Server policy".to_string()),
+            replacement: None,
+        }
+    }
+
+    fn domain() -> RdapResponse {
+        let contact = Contact::builder()
+            .full_name("Alice Registrant")
+            .emails(vec![Email::builder().email("alice@example.com").build()])
+            .phones(vec![Phone::builder().phone("+1.5555551212").build()])
+            .build();
+        RdapResponse::Domain(
+            Domain::basic()
+                .ldh_name("foo.example")
+                .entity(
+                    Entity::basic()
+                        .handle("alice")
+                        .role("registrant")
+                        .contact(contact.clone())
+                        .build(),
+                )
+                .entity(
+                    Entity::basic()
+                        .handle("bob")
+                        .role("technical")
+                        .contact(contact)
+                        .build(),
+                )
+                .build(),
+        )
+    }
+
+    fn contact(domain: &Domain, role: &str) -> Contact {
+        domain
+            .object_common
+            .entities
+            .iter()
+            .flatten()
+            .find(|entity| entity.roles.as_ref().unwrap()[0] == role)
+            .and_then(|entity| entity.contact())
+            .expect("no contact")
+    }
+
+    #[test]
+    fn GIVEN_removal_rule_WHEN_redact_for_public_THEN_email_removed_and_redacted_added() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("registrant", "email", Method::Removal)],
+        };
+
+        // WHEN
+        let actual = policy.redact(domain(), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Domain(domain) = actual else {
+            panic!("not a domain")
+        };
+        assert!(contact(&domain, "registrant").emails.is_none());
+        assert!(contact(&domain, "technical").emails.is_some());
+        let redacted = domain.object_common.redacted.expect("no redacted");
+        assert_eq!(redacted.len(), 1);
+        assert_eq!(
+            redacted[0].pre_path.as_deref(),
+            Some("$.entities[?(@.roles[0]=='registrant')].vcardArray[1][?(@[0]=='email')]")
+        );
+        assert_eq!(redacted[0].method, Some(Method::Removal));
+        assert!(domain
+            .common
+            .rdap_conformance
+            .expect("no rdap conformance")
+            .contains(&Extension("redacted".to_string())));
+    }
+
+    #[test]
+    fn GIVEN_empty_value_rule_WHEN_redact_for_public_THEN_phone_emptied() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("technical", "tel", Method::EmptyValue)],
+        };
+
+        // WHEN
+        let actual = policy.redact(domain(), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Domain(domain) = actual else {
+            panic!("not a domain")
+        };
+        let phones = contact(&domain, "technical").phones.expect("no phones");
+        assert_eq!(phones[0].phone, "");
+        let redacted = domain.object_common.redacted.expect("no redacted");
+        assert_eq!(
+            redacted[0].post_path.as_deref(),
+            Some("$.entities[?(@.roles[0]=='technical')].vcardArray[1][?(@[0]=='tel')][3]")
+        );
+    }
+
+    #[test]
+    fn GIVEN_replacement_rule_WHEN_redact_entity_THEN_email_replaced_by_contact_uri() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![RedactionRule {
+                replacement: Some(Replacement {
+                    property: "contact-uri".to_string(),
+                    value_type: "uri".to_string(),
+                    value: "https://example.com/contact".to_string(),
+                }),
+                ..rule("registrant", "email", Method::ReplacementValue)
+            }],
+        };
+        let RdapResponse::Domain(domain) = domain() else {
+            panic!("not a domain")
+        };
+        let entity = domain.object_common.entities.unwrap().remove(0);
+
+        // WHEN
+        let actual = policy.redact(RdapResponse::Entity(entity), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Entity(entity) = actual else {
+            panic!("not an entity")
+        };
+        assert!(entity.contact().expect("no contact").emails.is_none());
+        let vcard = serde_json::to_string(&entity.vcard_array).expect("serializing vcard");
+        assert!(vcard.contains(r#"["contact-uri",{},"uri","https://example.com/contact"]"#));
+        let redacted = entity.object_common.redacted.expect("no redacted");
+        assert_eq!(
+            redacted[0].pre_path.as_deref(),
+            Some("$.vcardArray[1][?(@[0]=='email')]")
+        );
+        assert_eq!(
+            redacted[0].replacement_path.as_deref(),
+            Some("$.vcardArray[1][?(@[0]=='contact-uri')]")
+        );
+    }
+
+    #[test]
+    fn GIVEN_rule_for_public_WHEN_redact_for_registrar_THEN_unchanged() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("registrant", "email", Method::Removal)],
+        };
+
+        // WHEN
+        let actual = policy.redact(domain(), AccessTier::Registrar);
+
+        // THEN
+        assert_eq!(actual, domain());
+    }
+
+    fn email_contact() -> Contact {
+        Contact::builder()
+            .full_name("Alice")
+            .emails(vec![Email::builder().email("alice@example.com").build()])
+            .build()
+    }
+
+    #[test]
+    fn GIVEN_entity_with_role_not_first_WHEN_redact_for_public_THEN_email_removed() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("registrant", "email", Method::Removal)],
+        };
+        let domain = Domain::basic()
+            .ldh_name("foo.example")
+            .entity(
+                Entity::basic()
+                    .handle("alice")
+                    .role("technical")
+                    .role("registrant")
+                    .contact(email_contact())
+                    .build(),
+            )
+            .build();
+
+        // WHEN
+        let actual = policy.redact(RdapResponse::Domain(domain), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Domain(domain) = actual else {
+            panic!("not a domain")
+        };
+        assert!(contact(&domain, "technical").emails.is_none());
+        let redacted = domain.object_common.redacted.expect("no redacted");
+        assert_eq!(
+            redacted[0].pre_path.as_deref(),
+            Some("$.entities[?(@.roles[0]=='technical')].vcardArray[1][?(@[0]=='email')]")
+        );
+    }
+
+    #[test]
+    fn GIVEN_nested_entity_WHEN_redact_for_public_THEN_nested_email_removed() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("abuse", "email", Method::Removal)],
+        };
+        let domain = Domain::basic()
+            .ldh_name("foo.example")
+            .entity(
+                Entity::basic()
+                    .handle("registrar")
+                    .role("registrar")
+                    .entity(
+                        Entity::basic()
+                            .handle("abuse")
+                            .role("abuse")
+                            .contact(email_contact())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        // WHEN
+        let actual = policy.redact(RdapResponse::Domain(domain), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Domain(domain) = actual else {
+            panic!("not a domain")
+        };
+        let registrar = &domain.object_common.entities.as_ref().expect("no entities")[0];
+        let abuse = &registrar
+            .object_common
+            .entities
+            .as_ref()
+            .expect("no entities")[0];
+        assert!(abuse.contact().expect("no contact").emails.is_none());
+        let redacted = domain.object_common.redacted.expect("no redacted");
+        assert_eq!(
+            redacted[0].pre_path.as_deref(),
+            Some(
+                "$.entities[?(@.roles[0]=='registrar')].entities[?(@.roles[0]=='abuse')]\
+                 .vcardArray[1][?(@[0]=='email')]"
+            )
+        );
+    }
+
+    #[test]
+    fn GIVEN_entity_without_roles_WHEN_redact_entity_for_public_THEN_email_removed() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("registrant", "email", Method::Removal)],
+        };
+        let entity = Entity::basic()
+            .handle("alice")
+            .contact(email_contact())
+            .build();
+
+        // WHEN
+        let actual = policy.redact(RdapResponse::Entity(entity), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Entity(entity) = actual else {
+            panic!("not an entity")
+        };
+        assert!(entity.contact().expect("no contact").emails.is_none());
+        let redacted = entity.object_common.redacted.expect("no redacted");
+        assert_eq!(
+            redacted[0].pre_path.as_deref(),
+            Some("$.vcardArray[1][?(@[0]=='email')]")
+        );
+    }
+
+    #[test]
+    fn GIVEN_entity_with_other_role_WHEN_redact_entity_for_public_THEN_unchanged() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("registrant", "email", Method::Removal)],
+        };
+        let entity = Entity::basic()
+            .handle("bob")
+            .role("technical")
+            .contact(email_contact())
+            .build();
+
+        // WHEN
+        let actual = policy.redact(RdapResponse::Entity(entity.clone()), AccessTier::Public);
+
+        // THEN
+        assert_eq!(actual, RdapResponse::Entity(entity));
+    }
+
+    #[test]
+    fn GIVEN_nameserver_entity_WHEN_redact_domain_for_public_THEN_nameserver_email_removed() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("technical", "email", Method::Removal)],
+        };
+        let domain = Domain::basic()
+            .ldh_name("foo.example")
+            .nameservers(vec![Nameserver::basic()
+                .ldh_name("ns1.foo.example")
+                .entity(
+                    Entity::basic()
+                        .handle("bob")
+                        .role("technical")
+                        .contact(email_contact())
+                        .build(),
+                )
+                .build()
+                .expect("building nameserver")])
+            .build();
+
+        // WHEN
+        let actual = policy.redact(RdapResponse::Domain(domain), AccessTier::Public);
+
+        // THEN
+        let RdapResponse::Domain(domain) = actual else {
+            panic!("not a domain")
+        };
+        let nameserver = &domain.nameservers.as_ref().expect("no nameservers")[0];
+        let bob = &nameserver
+            .object_common
+            .entities
+            .as_ref()
+            .expect("no entities")[0];
+        assert!(bob.contact().expect("no contact").emails.is_none());
+        let redacted = domain.object_common.redacted.expect("no redacted");
+        assert_eq!(
+            redacted[0].pre_path.as_deref(),
+            Some(
+                "$.nameservers[?(@.ldhName=='ns1.foo.example')].entities[?(@.roles[0]=='technical')]\
+                 .vcardArray[1][?(@[0]=='email')]"
+            )
+        );
+    }
+
+    #[test]
+    fn GIVEN_email_rule_for_public_WHEN_redacts_property_THEN_only_email_for_public() {
+        // GIVEN
+        let policy = RedactionPolicy {
+            rules: vec![rule("registrant", "email", Method::Removal)],
+        };
+
+        // WHEN
+        let email_public = redacts_property(Some(&policy), AccessTier::Public, "EMAIL");
+        let email_registrar = redacts_property(Some(&policy), AccessTier::Registrar, "email");
+        let name_public = redacts_property(Some(&policy), AccessTier::Public, "fn");
+
+        // THEN
+        assert!(email_public);
+        assert!(!email_registrar);
+        assert!(!name_public);
+    }
+}
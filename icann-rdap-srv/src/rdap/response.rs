@@ -1,6 +1,6 @@
 use axum::{
     response::{IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
 use http::StatusCode;
 use icann_rdap_common::{
@@ -10,6 +10,8 @@ use icann_rdap_common::{
 use lazy_static::lazy_static;
 use tracing::warn;
 
+use crate::storage::SerializedObject;
+
 lazy_static! {
     pub static ref NOT_FOUND: RdapResponse = RdapResponse::ErrorResponse(
         Error::builder()
@@ -29,6 +31,23 @@ lazy_static! {
             .common(Common::builder().build())
             .build()
     );
+    pub static ref REDACTED_SEARCH: RdapResponse = RdapResponse::ErrorResponse(
+        Error::builder()
+            .error_code(403)
+            .title("Forbidden")
+            .description(vec![
+                "Searching by a property redacted from the results is not allowed.".to_string()
+            ])
+            .common(Common::builder().build())
+            .build()
+    );
+    pub static ref UNAUTHORIZED: RdapResponse = RdapResponse::ErrorResponse(
+        Error::builder()
+            .error_code(401)
+            .title("Unauthorized")
+            .common(Common::builder().build())
+            .build()
+    );
 }
 
 pub(crate) const RDAP_HEADERS: [(&str, &str); 1] = [("content-type", RDAP_MEDIA_TYPE)];
@@ -92,6 +111,18 @@ impl ResponseUtil for RdapResponse {
     }
 }
 
+impl IntoResponse for SerializedObject {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::OK,
+            RDAP_HEADERS,
+            Extension(self.cache_info),
+            self.json,
+        )
+            .into_response()
+    }
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
@@ -0,0 +1,167 @@
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Response,
+};
+use http::Uri;
+use serde::Deserialize;
+
+use crate::{
+    error::RdapServerError,
+    rdap::{
+        access::AccessTier,
+        paging::{page_search_results, PagingParams, ALL_RESULTS},
+        redaction::{redact, redacts_property},
+        response::{ResponseUtil, BAD_REQUEST, REDACTED_SEARCH},
+        subsetting::{subset_search_results, SubsettingParams},
+    },
+    server::DynServiceState,
+    storage::search::{RelatedEntitySearch, ReverseSearchType, SearchPattern},
+};
+
+/// The query parameters of a reverse search by related entity.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RelatedEntityParams {
+    handle: Option<String>,
+    #[serde(rename = "fn")]
+    full_name: Option<String>,
+    email: Option<String>,
+    role: Option<String>,
+}
+
+impl RelatedEntityParams {
+    /// Gets the related entity search, or `None` if there are no search properties
+    /// or any of them are blank.
+    fn related_entity_search(self) -> Option<RelatedEntitySearch> {
+        let mut blank = false;
+        let mut trimmed = |value: Option<String>| {
+            value.map(|value| {
+                let value = value.trim().to_string();
+                blank |= value.is_empty();
+                value
+            })
+        };
+        let search = RelatedEntitySearch {
+            handle: trimmed(self.handle).map(|handle| SearchPattern::new(&handle)),
+            full_name: trimmed(self.full_name).map(|full_name| SearchPattern::new(&full_name)),
+            email: trimmed(self.email).map(|email| SearchPattern::new(&email)),
+            role: trimmed(self.role),
+        };
+        (!blank && !search.is_empty()).then_some(search)
+    }
+}
+
+/// Gets the entity properties searched by, either `handle` or the names of vCard
+/// properties.
+fn searched_properties(related: &RelatedEntitySearch) -> Vec<&'static str> {
+    [
+        ("handle", related.handle.is_some()),
+        ("fn", related.full_name.is_some()),
+        ("email", related.email.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(property, searched)| searched.then_some(property))
+    .collect()
+}
+
+/// Searches for domains by related entity according to RFC 9536.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn domains_by_entity(
+    Query(params): Query<RelatedEntityParams>,
+    Query(paging): Query<PagingParams>,
+    Query(subsetting): Query<SubsettingParams>,
+    OriginalUri(uri): OriginalUri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    reverse_search(
+        ReverseSearchType::Domains,
+        params,
+        paging,
+        subsetting,
+        uri,
+        tier,
+        state,
+    )
+    .await
+}
+
+/// Searches for nameservers by related entity according to RFC 9536.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn nameservers_by_entity(
+    Query(params): Query<RelatedEntityParams>,
+    Query(paging): Query<PagingParams>,
+    Query(subsetting): Query<SubsettingParams>,
+    OriginalUri(uri): OriginalUri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    reverse_search(
+        ReverseSearchType::Nameservers,
+        params,
+        paging,
+        subsetting,
+        uri,
+        tier,
+        state,
+    )
+    .await
+}
+
+/// Searches for entities by related entity according to RFC 9536.
+#[axum_macros::debug_handler]
+#[tracing::instrument(level = "debug")]
+pub(crate) async fn entities_by_entity(
+    Query(params): Query<RelatedEntityParams>,
+    Query(paging): Query<PagingParams>,
+    Query(subsetting): Query<SubsettingParams>,
+    OriginalUri(uri): OriginalUri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    reverse_search(
+        ReverseSearchType::Entities,
+        params,
+        paging,
+        subsetting,
+        uri,
+        tier,
+        state,
+    )
+    .await
+}
+
+/// Does the reverse search. The `handle`, `fn`, `email` and `role` parameters
+/// are all properties of the same related entity. At least one must be given, and
+/// all but `role` may use the `*` wildcard for partial matches. Searches by a
+/// property redacted for the caller are refused.
+async fn reverse_search(
+    search_type: ReverseSearchType,
+    params: RelatedEntityParams,
+    paging: PagingParams,
+    subsetting: SubsettingParams,
+    uri: Uri,
+    tier: AccessTier,
+    state: State<DynServiceState>,
+) -> Result<Response, RdapServerError> {
+    let Some(related) = params.related_entity_search() else {
+        return Ok(BAD_REQUEST.response());
+    };
+    let policy = state.get_redaction_policy();
+    if searched_properties(&related)
+        .into_iter()
+        .any(|property| redacts_property(policy, tier, property))
+    {
+        return Ok(REDACTED_SEARCH.response());
+    }
+    let storage = state.get_storage().await?;
+    let results = storage
+        .reverse_search_by_entity(search_type, &related, ALL_RESULTS)
+        .await?;
+    let page_size = state.get_search_page_size();
+    let max_results = state.get_max_search_results();
+    let results = redact(results, state.get_redaction_policy(), tier);
+    let results = page_search_results(results, &paging, &uri, page_size, max_results);
+    Ok(subset_search_results(results, &subsetting, &uri).response())
+}
@@ -1,28 +1,85 @@
-use axum::{response::IntoResponse, routing::get, Router};
+use std::sync::Arc;
+
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::get,
+    Router,
+};
+
+use crate::{
+    config::{CacheConfig, RateLimitConfig},
+    metrics::track_requests,
+};
 
 use super::{
+    access::{authenticate, Credentials},
     autnum::autnum_by_num,
+    caching::cache_headers,
     domain::domain_by_name,
+    domains::domains_search,
+    entities::entities_search,
     entity::entity_by_handle,
     ip::network_by_netid,
     nameserver::nameserver_by_name,
-    response::{ResponseUtil, NOT_IMPLEMENTED},
+    nameservers::nameservers_search,
+    openidc::{login, logout, session_refresh, session_status, session_tier, OpenIdc},
+    rate_limit::rate_limited,
+    reverse_search::{domains_by_entity, entities_by_entity, nameservers_by_entity},
     srvhelp::srvhelp,
 };
 
-pub(crate) fn rdap_router() -> Router<crate::server::DynServiceState> {
-    Router::new()
+pub(crate) fn rdap_router(
+    rate_limit: &RateLimitConfig,
+    cache: &CacheConfig,
+    credentials: Option<Credentials>,
+    openidc: Option<OpenIdc>,
+) -> Router<crate::server::DynServiceState> {
+    let cache = from_fn_with_state(Arc::new(cache.clone()), cache_headers);
+    // Sessions are not cached, as their responses are given to a single end-user.
+    let lookups = Router::new()
         .route("/domain/:domain", get(domain_by_name))
         .route("/ip/*netid", get(network_by_netid))
         .route("/autnum/:asnumber", get(autnum_by_num))
         .route("/nameserver/:name", get(nameserver_by_name))
         .route("/entity/:handle", get(entity_by_handle))
-        .route("/domains", get(not_implemented))
-        .route("/nameservers", get(not_implemented))
-        .route("/entities", get(not_implemented))
         .route("/help", get(srvhelp))
+        .layer(cache.clone())
+        .route("/login", get(login))
+        .route("/session/status", get(session_status))
+        .route("/session/refresh", get(session_refresh))
+        .route("/logout", get(logout));
+    let searches = Router::new()
+        .route("/domains", get(domains_search))
+        .route("/nameservers", get(nameservers_search))
+        .route("/entities", get(entities_search))
+        .route("/domains/reverse_search/entity", get(domains_by_entity))
+        .route(
+            "/nameservers/reverse_search/entity",
+            get(nameservers_by_entity),
+        )
+        .route("/entities/reverse_search/entity", get(entities_by_entity))
+        .layer(cache);
+    let credentials = credentials.map(Arc::new);
+    let lookups = identify_callers(lookups, &credentials, &openidc);
+    let searches = identify_callers(searches, &credentials, &openidc);
+    Router::new()
+        .merge(rate_limited(lookups, rate_limit.lookup))
+        .merge(rate_limited(searches, rate_limit.search))
+        .layer(from_fn(track_requests))
 }
 
-async fn not_implemented() -> impl IntoResponse {
-    NOT_IMPLEMENTED.response()
+/// Adds the access tier of the caller to the requests of the routes. This is done within
+/// the rate limits, so that callers giving unknown credentials are limited too.
+fn identify_callers(
+    mut router: Router<crate::server::DynServiceState>,
+    credentials: &Option<Arc<Credentials>>,
+    openidc: &Option<OpenIdc>,
+) -> Router<crate::server::DynServiceState> {
+    if let Some(openidc) = openidc {
+        router = router.route_layer(from_fn_with_state(openidc.clone(), session_tier));
+    }
+    if let Some(credentials) = credentials {
+        router = router.route_layer(from_fn_with_state(credentials.clone(), authenticate));
+    }
+    router
 }
@@ -1,9 +1,13 @@
 use axum::{extract::State, response::Response};
 use axum_extra::typed_header::TypedHeader;
 use headers::Host;
-use icann_rdap_common::response::RdapResponse;
+use icann_rdap_common::response::{types::Extension, RdapResponse};
 
-use crate::{error::RdapServerError, rdap::response::ResponseUtil, server::DynServiceState};
+use crate::{
+    error::RdapServerError,
+    rdap::{openidc::ROIDC1, response::ResponseUtil},
+    server::DynServiceState,
+};
 
 /// Get server help.
 #[axum_macros::debug_handler]
@@ -21,5 +25,15 @@ pub(crate) async fn srvhelp(
         srv_help = storage.get_srv_help(None).await?;
     }
 
+    if let (Some(openidc), RdapResponse::Help(help)) = (state.get_openidc(), &mut srv_help) {
+        help.openidc_configuration = Some(openidc.configuration());
+        help.openidc_providers = Some(openidc.help_providers());
+        let conformance = help.common.rdap_conformance.get_or_insert_with(Vec::new);
+        let roidc1 = Extension::from(ROIDC1);
+        if !conformance.contains(&roidc1) {
+            conformance.push(roidc1);
+        }
+    }
+
     Ok(srv_help.response())
 }
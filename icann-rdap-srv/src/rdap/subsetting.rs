@@ -0,0 +1,311 @@
+use http::Uri;
+use icann_rdap_common::response::{
+    domain::Domain,
+    entity::Entity,
+    nameserver::Nameserver,
+    search::{AvailableFieldSet, SubsettingMetadata},
+    types::{Common, Extension, ObjectCommon},
+    RdapResponse,
+};
+use serde::Deserialize;
+
+use crate::rdap::{
+    paging::{rdap_link, replace_params},
+    response::BAD_REQUEST,
+};
+
+/// The RDAP conformance value of RFC 8982 partial responses.
+pub const SUBSETTING: &str = "subsetting";
+
+/// The field sets and their descriptions. The last is the default field set.
+const FIELD_SETS: &[(&str, &str)] = &[
+    (
+        "id",
+        "Only the object class name and the key of each object: the names of domains and nameservers and the handles of entities.",
+    ),
+    (
+        "brief",
+        "The fields of the id field set with the handle, status and self link of each object.",
+    ),
+    ("full", "All the fields of each object."),
+];
+
+/// The query parameters of RFC 8982 partial responses, which may be given with any search.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SubsettingParams {
+    #[serde(rename = "fieldSet")]
+    field_set: Option<String>,
+}
+
+/// An object found by a search that can be trimmed to a field set.
+trait Subsettable: Sized {
+    /// Trims the object to the fields of the `id` field set.
+    fn to_id(self) -> Self;
+
+    /// Trims the object to the fields of the `brief` field set.
+    fn to_brief(self) -> Self;
+}
+
+/// Gets the object common fields of the `id` field set.
+fn id_object_common(object_common: &ObjectCommon) -> ObjectCommon {
+    ObjectCommon {
+        object_class_name: object_common.object_class_name.clone(),
+        handle: None,
+        remarks: None,
+        links: None,
+        events: None,
+        status: None,
+        port_43: None,
+        entities: None,
+        redacted: None,
+    }
+}
+
+/// Gets the object common fields of the `brief` field set.
+fn brief_object_common(object_common: &ObjectCommon) -> ObjectCommon {
+    ObjectCommon {
+        handle: object_common.handle.clone(),
+        status: object_common.status.clone(),
+        links: object_common.get_self_link().map(|link| vec![link.clone()]),
+        ..id_object_common(object_common)
+    }
+}
+
+impl Subsettable for Domain {
+    fn to_id(self) -> Self {
+        Self {
+            common: Common::builder().build(),
+            object_common: id_object_common(&self.object_common),
+            ldh_name: self.ldh_name,
+            unicode_name: self.unicode_name,
+            variants: None,
+            secure_dns: None,
+            nameservers: None,
+            public_ids: None,
+            network: None,
+        }
+    }
+
+    fn to_brief(self) -> Self {
+        Self {
+            object_common: brief_object_common(&self.object_common),
+            ..self.to_id()
+        }
+    }
+}
+
+impl Subsettable for Nameserver {
+    fn to_id(self) -> Self {
+        Self {
+            common: Common::builder().build(),
+            object_common: id_object_common(&self.object_common),
+            ldh_name: self.ldh_name,
+            unicode_name: self.unicode_name,
+            ip_addresses: None,
+        }
+    }
+
+    fn to_brief(self) -> Self {
+        Self {
+            object_common: brief_object_common(&self.object_common),
+            ..self.to_id()
+        }
+    }
+}
+
+impl Subsettable for Entity {
+    fn to_id(self) -> Self {
+        Self {
+            common: Common::builder().build(),
+            object_common: ObjectCommon {
+                handle: self.object_common.handle.clone(),
+                ..id_object_common(&self.object_common)
+            },
+            vcard_array: None,
+            roles: None,
+            public_ids: None,
+            as_event_actor: None,
+            autnums: None,
+            networks: None,
+        }
+    }
+
+    fn to_brief(self) -> Self {
+        Self {
+            object_common: brief_object_common(&self.object_common),
+            ..self.to_id()
+        }
+    }
+}
+
+/// Trims the found objects to the field set asked for, giving the subsetting metadata,
+/// or `None` if the field set is unknown.
+fn subset<T: Subsettable>(
+    results: &mut Vec<T>,
+    params: &SubsettingParams,
+    uri: &Uri,
+) -> Option<SubsettingMetadata> {
+    let (default, _) = FIELD_SETS[FIELD_SETS.len() - 1];
+    let field_set = params.field_set.as_deref().unwrap_or(default);
+    match field_set {
+        "id" => *results = results.drain(..).map(T::to_id).collect(),
+        "brief" => *results = results.drain(..).map(T::to_brief).collect(),
+        "full" => {}
+        _ => return None,
+    }
+
+    let value = uri.to_string();
+    let available_field_sets = FIELD_SETS
+        .iter()
+        .map(|(name, description)| {
+            let href = replace_params(uri, &["fieldSet"], Some(("fieldSet", name)));
+            AvailableFieldSet::builder()
+                .name(*name)
+                .default(*name == default)
+                .description(*description)
+                .links(vec![rdap_link(&value, "alternate", href)])
+                .build()
+        })
+        .collect::<Vec<AvailableFieldSet>>();
+    Some(
+        SubsettingMetadata::builder()
+            .current_field_set(field_set)
+            .available_field_sets(available_field_sets)
+            .build(),
+    )
+}
+
+/// Adds the subsetting RDAP conformance value.
+fn add_conformance(common: &mut Common) {
+    common
+        .rdap_conformance
+        .get_or_insert_with(Vec::new)
+        .push(Extension(SUBSETTING.to_string()));
+}
+
+/// Applies RFC 8982 partial responses to search results.
+///
+/// The `id` field set keeps only the object class name and key of each object, which
+/// keeps the responses of bulk searches small, and the `brief` field set adds the
+/// handle, status and self link. The default `full` field set keeps every field.
+/// A 400 is given for an unknown field set.
+pub(crate) fn subset_search_results(
+    response: RdapResponse,
+    params: &SubsettingParams,
+    uri: &Uri,
+) -> RdapResponse {
+    match response {
+        RdapResponse::DomainSearchResults(mut results) => {
+            let Some(subsetting) = subset(&mut results.results, params, uri) else {
+                return BAD_REQUEST.clone();
+            };
+            add_conformance(&mut results.common);
+            results.subsetting_metadata = Some(subsetting);
+            RdapResponse::DomainSearchResults(results)
+        }
+        RdapResponse::NameserverSearchResults(mut results) => {
+            let Some(subsetting) = subset(&mut results.results, params, uri) else {
+                return BAD_REQUEST.clone();
+            };
+            add_conformance(&mut results.common);
+            results.subsetting_metadata = Some(subsetting);
+            RdapResponse::NameserverSearchResults(results)
+        }
+        RdapResponse::EntitySearchResults(mut results) => {
+            let Some(subsetting) = subset(&mut results.results, params, uri) else {
+                return BAD_REQUEST.clone();
+            };
+            add_conformance(&mut results.common);
+            results.subsetting_metadata = Some(subsetting);
+            RdapResponse::EntitySearchResults(results)
+        }
+        _ => response,
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+
+    use icann_rdap_common::response::{domain::Domain, entity::Entity, types::Link};
+
+    use super::Subsettable;
+
+    fn domain() -> Domain {
+        Domain::basic()
+            .ldh_name("foo.example")
+            .handle("foo-example-1")
+            .status("active")
+            .link(
+                Link::builder()
+                    .rel("self")
+                    .href("https://example.net/domain/foo.example")
+                    .build(),
+            )
+            .link(
+                Link::builder()
+                    .rel("related")
+                    .href("https://example.com/domain/foo.example")
+                    .build(),
+            )
+            .entity(Entity::basic().handle("bar").build())
+            .build()
+    }
+
+    #[test]
+    fn GIVEN_domain_WHEN_to_id_THEN_only_names_kept() {
+        // GIVEN
+        let domain = domain();
+
+        // WHEN
+        let actual = domain.to_id();
+
+        // THEN
+        assert_eq!(actual.object_common.object_class_name, "domain");
+        assert_eq!(actual.ldh_name.as_deref(), Some("foo.example"));
+        assert!(actual.object_common.handle.is_none());
+        assert!(actual.object_common.status.is_none());
+        assert!(actual.object_common.links.is_none());
+        assert!(actual.object_common.entities.is_none());
+    }
+
+    #[test]
+    fn GIVEN_domain_WHEN_to_brief_THEN_handle_status_and_self_link_kept() {
+        // GIVEN
+        let domain = domain();
+
+        // WHEN
+        let actual = domain.to_brief();
+
+        // THEN
+        assert_eq!(actual.ldh_name.as_deref(), Some("foo.example"));
+        assert_eq!(
+            actual.object_common.handle.as_deref(),
+            Some("foo-example-1")
+        );
+        assert!(actual.object_common.status.is_some());
+        let links = actual.object_common.links.expect("no links");
+        assert_eq!(links.len(), 1);
+        assert!(links[0].is_relation("self"));
+        assert!(actual.object_common.entities.is_none());
+    }
+
+    #[test]
+    fn GIVEN_entity_WHEN_to_id_THEN_only_handle_kept() {
+        // GIVEN
+        let entity = Entity::basic()
+            .handle("foo")
+            .role("registrant")
+            .status("active")
+            .build();
+
+        // WHEN
+        let actual = entity.to_id();
+
+        // THEN
+        assert_eq!(actual.object_common.object_class_name, "entity");
+        assert_eq!(actual.object_common.handle.as_deref(), Some("foo"));
+        assert!(actual.object_common.status.is_none());
+        assert!(actual.roles.is_none());
+    }
+}
@@ -2,6 +2,8 @@ use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use axum::{error_handling::HandleErrorLayer, Router};
+use axum_client_ip::SecureClientIpSource;
+use axum_server::tls_rustls::RustlsConfig;
 use http::{Method, StatusCode};
 use icann_rdap_common::VERSION;
 use tokio::net::TcpListener;
@@ -12,22 +14,30 @@ use tower_http::{
 };
 
 use crate::{
+    admin::{admin_router, Admin},
     bootstrap::init_bootstrap,
-    config::{ListenConfig, ServiceConfig, StorageType},
+    config::{CacheConfig, ListenConfig, RateLimitConfig, ServiceConfig, StorageType},
     error::RdapServerError,
-    rdap::router::rdap_router,
+    health::{health_router, Health},
+    rdap::{
+        access::Credentials, openidc::OpenIdc, redaction::RedactionPolicy, router::rdap_router,
+    },
     storage::{
-        data::{load_data, reload_data},
+        data::{load_dir, scan_dir},
         mem::{config::MemConfig, ops::Mem},
         pg::{config::PgConfig, ops::Pg},
+        sqlite::{config::SqliteConfig, ops::Sqlite},
+        watch::{reload_data, DataWatcher},
         StoreOps,
     },
+    tls::CertWatcher,
 };
 
 /// Holds information on the server listening.
 pub struct Listener {
     pub local_addr: SocketAddr,
     tcp_listener: TcpListener,
+    tls: Option<(RustlsConfig, CertWatcher)>,
 }
 
 /// Starts the RDAP service.
@@ -46,24 +56,31 @@ impl Listener {
 
         tracing::debug!("tcp binding to {}", binding);
 
+        let tls = match &config.tls {
+            Some(tls) => Some(tls.load().await?),
+            None => None,
+        };
+
         let listener = TcpListener::bind(binding).await?;
         let local_addr = listener.local_addr()?;
         Ok(Self {
             local_addr,
             tcp_listener: listener,
+            tls,
         })
     }
 
     pub fn rdap_base(&self) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
         if self.local_addr.is_ipv4() {
             format!(
-                "http://{}:{}/rdap",
+                "{scheme}://{}:{}/rdap",
                 self.local_addr.ip(),
                 self.local_addr.port()
             )
         } else {
             format!(
-                "http://[{}]:{}/rdap",
+                "{scheme}://[{}]:{}/rdap",
                 self.local_addr.ip(),
                 self.local_addr.port()
             )
@@ -74,13 +91,15 @@ impl Listener {
     /// This function will initiate any needed non-HTTP services and then call
     /// call [Listener::start_with_state], which initiates the HTTP service.
     pub async fn start_server(self, service_config: &ServiceConfig) -> Result<(), RdapServerError> {
-        init_bootstrap(service_config).await?;
         if let StorageType::Memory(config) = &service_config.storage_type {
             let app_state = AppState::new_mem(config.clone(), service_config).await?;
             self.start_with_state(app_state).await?;
         } else if let StorageType::Postgres(config) = &service_config.storage_type {
             let app_state = AppState::new_pg(config.clone(), service_config).await?;
             self.start_with_state(app_state).await?;
+        } else if let StorageType::Sqlite(config) = &service_config.storage_type {
+            let app_state = AppState::new_sqlite(config.clone(), service_config).await?;
+            self.start_with_state(app_state).await?;
         };
         Ok(())
     }
@@ -95,6 +114,20 @@ impl Listener {
         let app = app_router::<T>(app_state);
 
         tracing::debug!("listening on {}", self.local_addr);
+        if let Some((tls, cert_watcher)) = self.tls {
+            let server = axum_server::from_tcp_rustls(self.tcp_listener.into_std()?, tls)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+            tokio::pin!(server);
+            // the certificate is reloaded for as long as the server is serving.
+            tokio::select! {
+                result = &mut server => return Ok(result?),
+                result = cert_watcher.run() => if let Err(e) = result {
+                    tracing::error!("TLS certificate no longer reloaded: {e}");
+                },
+            }
+            server.await?;
+            return Ok(());
+        }
         // axum::Server::from_tcp(self.tcp_listener)?
         //     .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         //     .await?;
@@ -107,15 +140,128 @@ impl Listener {
     }
 }
 
-async fn init_data(
+/// Bootstraps and loads the data in the background, so that health checks are answered
+/// while the data is loading. Until then, the server is not ready. When reloading
+/// automatically, the data directory is watched before the data is loaded so that
+/// changes made during the load are not missed, and when the data is restored from a
+/// snapshot, changes are loaded once the data directory has been scanned for the objects
+/// in each file. Once the data is loaded, SIGHUP reloads all of it.
+fn init_data<T>(storage: &T, config: &ServiceConfig, health: &Health)
+where
+    T: StoreOps + Clone + 'static,
+{
+    // SIGHUP is handled from the start, so that it does not end the server while loading.
+    #[cfg(unix)]
+    let hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .map(|hangup| (hangup, Box::new(storage.clone()) as Box<dyn StoreOps>))
+        .inspect_err(|e| tracing::error!("Data will not be reloaded on SIGHUP: {e}"))
+        .ok();
+    let store: Box<dyn StoreOps> = Box::new(storage.clone());
+    let config = config.clone();
+    let health = health.clone();
+    tokio::spawn(async move {
+        let loaded = async {
+            init_bootstrap(&config, &health).await?;
+            let watcher = config
+                .auto_reload
+                .then(|| DataWatcher::new(&config.data_dir))
+                .transpose();
+            let watcher = watcher.unwrap_or_else(|e| {
+                tracing::error!("Data will not be reloaded: {e}");
+                None
+            });
+            // the files of objects restored from a snapshot are scanned once they are served.
+            let file_ids = if store.restore_snapshot(&config.data_dir).await? {
+                None
+            } else {
+                Some(load_dir(&config.data_dir, &*store, false).await?)
+            };
+            health.count_objects(&*store).await?;
+            Ok::<_, RdapServerError>((watcher, file_ids))
+        };
+        let (watcher, file_ids) = match loaded.await {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::error!("Data not loaded: {e}");
+                return;
+            }
+        };
+        health.set_data_loaded();
+        #[cfg(unix)]
+        if let Some((hangup, hangup_store)) = hangup {
+            tokio::spawn(reload_on_hangup(
+                hangup,
+                hangup_store,
+                config.data_dir.clone(),
+                health.clone(),
+            ));
+        }
+        if let Some(watcher) = watcher {
+            let watched = async {
+                let file_ids = match file_ids {
+                    Some(file_ids) => file_ids,
+                    None => scan_dir(&config.data_dir).await?,
+                };
+                watcher.run(store, config, health, file_ids).await
+            };
+            if let Err(e) = watched.await {
+                tracing::error!("Data no longer reloaded: {e}");
+            }
+        }
+    });
+}
+
+/// Reloads all the data when SIGHUP is received, whether or not the data directory is
+/// watched.
+#[cfg(unix)]
+async fn reload_on_hangup(
+    mut hangup: tokio::signal::unix::Signal,
     store: Box<dyn StoreOps>,
-    config: &ServiceConfig,
-) -> Result<(), RdapServerError> {
-    load_data(config, &*store, false).await?;
-    if config.auto_reload {
-        tokio::spawn(reload_data(store, config.clone()));
+    data_dir: String,
+    health: Health,
+) {
+    while hangup.recv().await.is_some() {
+        tracing::info!("SIGHUP received, reloading data.");
+        if let Err(e) = reload_data(&data_dir, &*store, &health).await {
+            tracing::warn!("Data not reloaded: {e}");
+        }
     }
-    Ok(())
+}
+
+fn load_credentials(config: &ServiceConfig) -> Result<Option<Credentials>, RdapServerError> {
+    config
+        .credentials_file
+        .as_deref()
+        .map(Credentials::load)
+        .transpose()
+}
+
+fn load_redaction_policy(
+    config: &ServiceConfig,
+) -> Result<Option<RedactionPolicy>, RdapServerError> {
+    config
+        .redaction_policy_file
+        .as_deref()
+        .map(RedactionPolicy::load)
+        .transpose()
+}
+
+fn load_openidc(config: &ServiceConfig) -> Result<Option<OpenIdc>, RdapServerError> {
+    config
+        .openidc_file
+        .as_deref()
+        .map(OpenIdc::load)
+        .transpose()
+}
+
+fn load_admin(config: &ServiceConfig) -> Option<Admin> {
+    config.admin_token_sha256.as_ref().map(|token_sha256| {
+        Admin::builder()
+            .token_sha256(token_sha256)
+            .data_dir(&config.data_dir)
+            .auto_reload(config.auto_reload)
+            .build()
+    })
 }
 
 fn app_router<T>(state: AppState<T>) -> Router
@@ -123,9 +269,28 @@ where
     T: StoreOps + Clone + Send + Sync + 'static,
     AppState<T>: ServiceState,
 {
+    let rate_limit = state.rate_limit.clone();
+    let cache = state.cache.clone();
+    let client_ip_source = rate_limit
+        .client_ip_source
+        .clone()
+        .unwrap_or(SecureClientIpSource::ConnectInfo);
+    let credentials = state.credentials.clone();
+    let openidc = state.openidc.clone();
+    let admin = state.admin.clone();
     let state = Arc::new(state) as DynServiceState;
-    Router::new()
-        .nest("/rdap", rdap_router())
+    let mut router = Router::new()
+        .nest(
+            "/rdap",
+            rdap_router(&rate_limit, &cache, credentials, openidc),
+        )
+        .merge(health_router());
+    // The admin API has its own token, which is not one of the credentials of callers.
+    if let Some(admin) = admin {
+        router = router.nest("/admin", admin_router(admin));
+    }
+    router
+        .layer(client_ip_source.into_extension())
         .layer(
             ServiceBuilder::new()
                 .layer(HandleErrorLayer::new(|error: BoxError| async move {
@@ -143,7 +308,7 @@ where
                 .layer(
                     CorsLayer::new()
                         .allow_origin(Any)
-                        .allow_methods(vec![Method::GET])
+                        .allow_methods(vec![Method::GET, Method::HEAD])
                         .allow_headers(Any),
                 )
                 .into_inner(),
@@ -161,6 +326,24 @@ pub trait ServiceState: std::fmt::Debug {
     /// If returns true, this indicates the server has been configured to do
     /// bootstrapping.
     fn get_bootstrap(&self) -> bool;
+
+    /// Gets the maximum number of results in each page of search results.
+    fn get_max_search_results(&self) -> usize;
+
+    /// Gets the number of search results in each page of search results.
+    fn get_search_page_size(&self) -> usize;
+
+    /// Gets the policy used to redact domains and entities, if there is one.
+    fn get_redaction_policy(&self) -> Option<&RedactionPolicy>;
+
+    /// Gets the OpenID Connect login of end-users, if it is configured.
+    fn get_openidc(&self) -> Option<&OpenIdc>;
+
+    /// Gets the progress of loading data and bootstrapping.
+    fn get_health(&self) -> &Health;
+
+    /// Gets the admin API, if it is configured.
+    fn get_admin(&self) -> Option<&Admin>;
 }
 
 /// State that is passed to the HTTP service router and used by functions
@@ -169,6 +352,15 @@ pub trait ServiceState: std::fmt::Debug {
 pub struct AppState<T: StoreOps + Clone + Send + Sync + 'static> {
     pub storage: T,
     pub bootstrap: bool,
+    pub max_search_results: usize,
+    pub search_page_size: usize,
+    pub rate_limit: RateLimitConfig,
+    pub cache: CacheConfig,
+    pub credentials: Option<Credentials>,
+    pub redaction_policy: Option<RedactionPolicy>,
+    pub openidc: Option<OpenIdc>,
+    pub health: Health,
+    pub admin: Option<Admin>,
 }
 
 impl AppState<Mem> {
@@ -178,10 +370,20 @@ impl AppState<Mem> {
     ) -> Result<AppState<Mem>, RdapServerError> {
         let storage = Mem::new(config);
         storage.init().await?;
-        init_data(Box::new(storage.clone()), service_config).await?;
+        let health = Health::default();
+        init_data(&storage, service_config, &health);
         Ok(AppState::<Mem> {
             storage,
             bootstrap: service_config.bootstrap,
+            max_search_results: service_config.max_search_results,
+            search_page_size: service_config.search_page_size,
+            rate_limit: service_config.rate_limit.clone(),
+            cache: service_config.cache.clone(),
+            credentials: load_credentials(service_config)?,
+            redaction_policy: load_redaction_policy(service_config)?,
+            openidc: load_openidc(service_config)?,
+            health,
+            admin: load_admin(service_config),
         })
     }
 }
@@ -199,10 +401,20 @@ impl AppState<Pg> {
     ) -> Result<AppState<Pg>, RdapServerError> {
         let storage = Pg::new(config).await?;
         storage.init().await?;
-        init_data(Box::new(storage.clone()), service_config).await?;
+        let health = Health::default();
+        init_data(&storage, service_config, &health);
         Ok(AppState::<Pg> {
             storage,
             bootstrap: service_config.bootstrap,
+            max_search_results: service_config.max_search_results,
+            search_page_size: service_config.search_page_size,
+            rate_limit: service_config.rate_limit.clone(),
+            cache: service_config.cache.clone(),
+            credentials: load_credentials(service_config)?,
+            redaction_policy: load_redaction_policy(service_config)?,
+            openidc: load_openidc(service_config)?,
+            health,
+            admin: load_admin(service_config),
         })
     }
 }
@@ -222,6 +434,96 @@ impl ServiceState for AppState<Pg> {
     fn get_bootstrap(&self) -> bool {
         self.bootstrap
     }
+
+    fn get_max_search_results(&self) -> usize {
+        self.max_search_results
+    }
+
+    fn get_search_page_size(&self) -> usize {
+        self.search_page_size
+    }
+
+    fn get_redaction_policy(&self) -> Option<&RedactionPolicy> {
+        self.redaction_policy.as_ref()
+    }
+
+    fn get_openidc(&self) -> Option<&OpenIdc> {
+        self.openidc.as_ref()
+    }
+
+    fn get_health(&self) -> &Health {
+        &self.health
+    }
+
+    fn get_admin(&self) -> Option<&Admin> {
+        self.admin.as_ref()
+    }
+}
+
+impl AppState<Sqlite> {
+    pub async fn new_sqlite(
+        config: SqliteConfig,
+        service_config: &ServiceConfig,
+    ) -> Result<AppState<Sqlite>, RdapServerError> {
+        let storage = Sqlite::new(config).await?;
+        storage.init().await?;
+        let health = Health::default();
+        init_data(&storage, service_config, &health);
+        Ok(AppState::<Sqlite> {
+            storage,
+            bootstrap: service_config.bootstrap,
+            max_search_results: service_config.max_search_results,
+            search_page_size: service_config.search_page_size,
+            rate_limit: service_config.rate_limit.clone(),
+            cache: service_config.cache.clone(),
+            credentials: load_credentials(service_config)?,
+            redaction_policy: load_redaction_policy(service_config)?,
+            openidc: load_openidc(service_config)?,
+            health,
+            admin: load_admin(service_config),
+        })
+    }
+}
+
+impl std::fmt::Debug for AppState<Sqlite> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppState<Sqlite>").finish()
+    }
+}
+
+#[async_trait]
+impl ServiceState for AppState<Sqlite> {
+    async fn get_storage<'a>(&'a self) -> Result<&'a dyn StoreOps, RdapServerError> {
+        Ok(&self.storage)
+    }
+
+    fn get_bootstrap(&self) -> bool {
+        self.bootstrap
+    }
+
+    fn get_max_search_results(&self) -> usize {
+        self.max_search_results
+    }
+
+    fn get_search_page_size(&self) -> usize {
+        self.search_page_size
+    }
+
+    fn get_redaction_policy(&self) -> Option<&RedactionPolicy> {
+        self.redaction_policy.as_ref()
+    }
+
+    fn get_openidc(&self) -> Option<&OpenIdc> {
+        self.openidc.as_ref()
+    }
+
+    fn get_health(&self) -> &Health {
+        &self.health
+    }
+
+    fn get_admin(&self) -> Option<&Admin> {
+        self.admin.as_ref()
+    }
 }
 
 #[async_trait]
@@ -233,4 +535,28 @@ impl ServiceState for AppState<Mem> {
     fn get_bootstrap(&self) -> bool {
         self.bootstrap
     }
+
+    fn get_max_search_results(&self) -> usize {
+        self.max_search_results
+    }
+
+    fn get_search_page_size(&self) -> usize {
+        self.search_page_size
+    }
+
+    fn get_redaction_policy(&self) -> Option<&RedactionPolicy> {
+        self.redaction_policy.as_ref()
+    }
+
+    fn get_openidc(&self) -> Option<&OpenIdc> {
+        self.openidc.as_ref()
+    }
+
+    fn get_health(&self) -> &Health {
+        &self.health
+    }
+
+    fn get_admin(&self) -> Option<&Admin> {
+        self.admin.as_ref()
+    }
 }
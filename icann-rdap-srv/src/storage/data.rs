@@ -1,34 +1,47 @@
 use std::{
+    collections::HashMap,
+    ffi::OsString,
     net::IpAddr,
-    path::PathBuf,
-    time::{Duration, SystemTime},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
 };
 
 use buildstructor::Builder;
-use icann_rdap_common::response::{
-    autnum::Autnum,
-    domain::Domain,
-    entity::Entity,
-    nameserver::Nameserver,
-    network::{Cidr0Cidr, Network, V4Cidr, V6Cidr},
-    GetSelfLink, RdapResponse, SelfLink,
+use icann_rdap_common::{
+    check::{traverse_checks, CheckClass, CheckParams, GetChecks},
+    response::{
+        autnum::Autnum,
+        domain::Domain,
+        entity::Entity,
+        nameserver::Nameserver,
+        network::{Cidr0Cidr, Network, V4Cidr, V6Cidr},
+        GetSelfLink, RdapResponse, SelfLink,
+    },
 };
 use ipnet::{IpNet, Ipv4Subnets, Ipv6Subnets};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use strum_macros::Display;
-use tokio::time::sleep;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc,
+    task::spawn_blocking,
+};
 use tracing::{debug, info, warn};
 
 use crate::{
     config::ServiceConfig,
     error::RdapServerError,
+    metrics::record_object_counts,
     storage::{StoreOps, TxHandle},
 };
 
 pub const UPDATE: &str = "update";
 pub const RELOAD: &str = "reload";
 
+/// The number of lines of an NDJSON file that are parsed and checked together.
+const NDJSON_CHUNK_LINES: usize = 1000;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Display)]
 #[serde(untagged)]
 pub enum Template {
@@ -131,6 +144,120 @@ pub struct NetworkId {
     pub network_id: NetworkIdType,
 }
 
+/// The IDs of objects, or of the errors stored in their place, to delete from storage.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ObjectIds {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<DomainId>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<EntityId>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nameservers: Vec<NameserverId>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub autnums: Vec<AutnumId>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<NetworkId>,
+}
+
+impl ObjectIds {
+    /// Gets the ID of an RDAP object, which is empty for responses other than objects
+    /// or for objects without the properties by which they are stored.
+    pub(crate) fn of_object(rdap: &RdapResponse) -> Self {
+        let mut ids = Self::default();
+        match rdap {
+            RdapResponse::Domain(domain) => {
+                ids.domains
+                    .extend(domain.ldh_name.as_ref().map(|ldh_name| DomainId {
+                        ldh_name: ldh_name.to_owned(),
+                        unicode_name: domain.unicode_name.clone(),
+                    }))
+            }
+            RdapResponse::Entity(entity) => {
+                ids.entities
+                    .extend(entity.object_common.handle.as_ref().map(|handle| EntityId {
+                        handle: handle.to_owned(),
+                    }))
+            }
+            RdapResponse::Nameserver(nameserver) => {
+                ids.nameservers
+                    .extend(nameserver.ldh_name.as_ref().map(|ldh_name| NameserverId {
+                        ldh_name: ldh_name.to_owned(),
+                        unicode_name: nameserver.unicode_name.clone(),
+                    }))
+            }
+            RdapResponse::Autnum(autnum) => {
+                if let (Some(start_autnum), Some(end_autnum)) =
+                    (autnum.start_autnum, autnum.end_autnum)
+                {
+                    ids.autnums.push(AutnumId {
+                        start_autnum,
+                        end_autnum,
+                    });
+                }
+            }
+            RdapResponse::Network(network) => {
+                if let (Some(start_address), Some(end_address)) =
+                    (&network.start_address, &network.end_address)
+                {
+                    ids.networks.push(NetworkId {
+                        network_id: NetworkIdType::Range {
+                            start_address: start_address.to_owned(),
+                            end_address: end_address.to_owned(),
+                        },
+                    });
+                }
+            }
+            _ => {}
+        };
+        ids
+    }
+
+    /// Gets the IDs of the objects, or errors, created by a template.
+    pub(crate) fn of_template(template: &Template) -> Self {
+        let mut ids = Self::default();
+        match template {
+            Template::Domain { ids: domains, .. } => ids.domains.clone_from(domains),
+            Template::Entity { ids: entities, .. } => ids.entities.clone_from(entities),
+            Template::Nameserver {
+                ids: nameservers, ..
+            } => ids.nameservers.clone_from(nameservers),
+            Template::Autnum { ids: autnums, .. } => ids.autnums.clone_from(autnums),
+            Template::Network { ids: networks, .. } => ids.networks.clone_from(networks),
+        };
+        ids
+    }
+
+    /// Adds the IDs of `other` to these IDs.
+    pub(crate) fn append(&mut self, mut other: Self) {
+        self.domains.append(&mut other.domains);
+        self.entities.append(&mut other.entities);
+        self.nameservers.append(&mut other.nameservers);
+        self.autnums.append(&mut other.autnums);
+        self.networks.append(&mut other.networks);
+    }
+
+    /// Gets the IDs which are not also in `other`.
+    pub(crate) fn without(&self, other: &Self) -> Self {
+        fn retain<T: Clone + PartialEq>(ids: &[T], other: &[T]) -> Vec<T> {
+            ids.iter()
+                .filter(|id| !other.contains(id))
+                .cloned()
+                .collect()
+        }
+        Self {
+            domains: retain(&self.domains, &other.domains),
+            entities: retain(&self.entities, &other.entities),
+            nameservers: retain(&self.nameservers, &other.nameservers),
+            autnums: retain(&self.autnums, &other.autnums),
+            networks: retain(&self.networks, &other.networks),
+        }
+    }
+}
+
+/// The IDs of the objects loaded from each file in the data directory, by file name.
+pub(crate) type FileIds = HashMap<OsString, ObjectIds>;
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum NetworkIdType {
@@ -170,42 +297,79 @@ pub enum NetworkIdType {
 /// ```
 /// In this example, 2 domains will be created for "foo.example" and "bar.exaple" using
 /// the template.
+///
+/// Files ending with a `.ndjson` extension hold many RDAP objects or templates, one on each
+/// line. These files are streamed from disk, and their lines are parsed and checked for
+/// specification errors in parallel, so large amounts of data need not be split into a file
+/// for each object. Later lines replace the objects of earlier lines with the same ID.
+///
+/// Files ending with a `.tombstone` extension hold the IDs of objects to delete, which
+/// allows an `update` to remove objects:
+///
+/// ```json
+/// {
+///   "domains": [ {"ldhName":"foo.example"} ],
+///   "networks": [ {"networkId":"192.0.2.0/24"} ]
+/// }
+/// ```
+/// Tombstones are applied after all the other files, so an object is deleted even when
+/// a file for it is also in the directory.
 pub async fn load_data(
     config: &ServiceConfig,
     store: &dyn StoreOps,
     truncate: bool,
 ) -> Result<(), RdapServerError> {
+    load_dir(&config.data_dir, store, truncate).await?;
+    record_object_counts(store).await.map(|_| ())
+}
+
+/// Loads the data directory as described by [load_data], giving the IDs of the objects
+/// loaded from each file so that they can be deleted when the file is removed.
+pub(crate) async fn load_dir(
+    data_dir: &str,
+    store: &dyn StoreOps,
+    truncate: bool,
+) -> Result<FileIds, RdapServerError> {
+    let mut file_ids = FileIds::new();
     let mut json_count: usize = 0;
+    let mut ndjson_count: usize = 0;
     let mut template_count: usize = 0;
     let mut srvhelp_count: usize = 0;
+    let mut tombstones = Vec::new();
     let mut tx = if truncate {
         store.new_truncate_tx().await?
     } else {
         store.new_tx().await?
     };
-    let path = PathBuf::from(&config.data_dir);
+    let path = PathBuf::from(data_dir);
     if !path.exists() || !path.is_dir() {
         warn!(
             "Directory {} does not exist or is not a directory. Server has no content to serve.",
             path.to_string_lossy()
         );
-        return Ok(());
+        return Ok(file_ids);
     }
 
     let mut entries = tokio::fs::read_dir(path).await?;
     while let Some(entry) = entries.next_entry().await? {
         let entry_path = entry.path();
+        // NDJSON files are streamed rather than read whole.
+        if entry_path.extension().is_some_and(|ext| ext == "ndjson") {
+            let ids = load_ndjson(&entry_path, &mut tx).await?;
+            file_ids.insert(entry.file_name(), ids);
+            ndjson_count += 1;
+            continue;
+        }
         let contents = tokio::fs::read_to_string(&entry_path).await?;
-        if entry_path
-            .extension()
-            .map_or(false, |ext| ext == "template")
-        {
-            load_rdap_template(&contents, &entry_path.to_string_lossy(), &mut tx).await?;
+        if entry_path.extension().is_some_and(|ext| ext == "template") {
+            let ids = load_rdap_template(&contents, &entry_path.to_string_lossy(), &mut tx).await?;
+            file_ids.insert(entry.file_name(), ids);
             template_count += 1;
-        } else if entry_path.extension().map_or(false, |ext| ext == "json") {
-            load_rdap(&contents, &entry_path.to_string_lossy(), &mut tx).await?;
+        } else if entry_path.extension().is_some_and(|ext| ext == "json") {
+            let ids = load_rdap(&contents, &entry_path.to_string_lossy(), &mut tx).await?;
+            file_ids.insert(entry.file_name(), ids);
             json_count += 1;
-        } else if entry_path.extension().map_or(false, |ext| ext == "help") {
+        } else if entry_path.extension().is_some_and(|ext| ext == "help") {
             load_srvhelp(
                 &contents,
                 &entry_path.to_string_lossy(),
@@ -214,17 +378,166 @@ pub async fn load_data(
             )
             .await?;
             srvhelp_count += 1;
+        } else if entry_path.extension().is_some_and(|ext| ext == "tombstone") {
+            tombstones.push((contents, entry_path));
         }
     }
+    for (contents, entry_path) in &tombstones {
+        load_tombstone(contents, &entry_path.to_string_lossy(), &mut tx).await?;
+    }
 
     info!("{json_count} RDAP JSON files loaded.");
+    info!("{ndjson_count} RDAP NDJSON files loaded.");
     info!("{template_count} RDAP template files loaded.");
     info!("{srvhelp_count} RDAP server help files loaded.");
-    if json_count == 0 && template_count == 0 && srvhelp_count == 0 {
+    info!("{} tombstone files loaded.", tombstones.len());
+    if json_count == 0 && ndjson_count == 0 && template_count == 0 && srvhelp_count == 0 {
         warn!("No data loaded. Server has no content to serve.");
     }
     tx.commit().await?;
-    Ok(())
+    Ok(file_ids)
+}
+
+/// Loads the files of the data directory that have been added, changed or removed, with
+/// the IDs of the objects previously loaded from each file in `file_ids`. The objects
+/// of a removed file are deleted, as are those no longer in a changed file. Tombstones
+/// are applied after the other files.
+///
+/// Files that cannot be loaded, such as those still being written, are skipped with a
+/// warning. They are loaded once they change again.
+pub(crate) async fn load_changes(
+    data_dir: &str,
+    file_names: &[OsString],
+    store: &dyn StoreOps,
+    file_ids: &mut FileIds,
+) -> Result<(), RdapServerError> {
+    let mut tx = store.new_tx().await?;
+    let (tombstones, files): (Vec<_>, Vec<_>) = file_names.iter().partition(|file_name| {
+        Path::new(file_name)
+            .extension()
+            .is_some_and(|ext| ext == "tombstone")
+    });
+    for file_name in files {
+        let path = Path::new(data_dir).join(file_name);
+        if !path.is_file() {
+            if let Some(ids) = file_ids.remove(file_name) {
+                debug!("{} removed", path.to_string_lossy());
+                delete_ids(&ids, &mut tx).await?;
+            }
+            continue;
+        }
+        match load_file(&path, &mut tx).await {
+            Ok(Some(ids)) => {
+                if let Some(previous) = file_ids.insert(file_name.to_owned(), ids.clone()) {
+                    delete_ids(&previous.without(&ids), &mut tx).await?;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("{} not loaded: {e}", path.to_string_lossy()),
+        }
+    }
+    for file_name in tombstones {
+        let path = Path::new(data_dir).join(file_name);
+        let Ok(contents) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        if let Err(e) = load_tombstone(&contents, &path.to_string_lossy(), &mut tx).await {
+            warn!("{} not loaded: {e}", path.to_string_lossy());
+        }
+    }
+    info!("{} changed data files loaded.", file_names.len());
+    tx.commit().await
+}
+
+/// Gets the IDs of the objects in each file of the data directory without loading them,
+/// as is needed to load changes to the files of objects restored from a snapshot. Files
+/// which cannot be parsed are skipped, as they could not have been loaded.
+pub(crate) async fn scan_dir(data_dir: &str) -> Result<FileIds, RdapServerError> {
+    let mut file_ids = FileIds::new();
+    if !Path::new(data_dir).is_dir() {
+        return Ok(file_ids);
+    }
+    let mut entries = tokio::fs::read_dir(data_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let ids = spawn_blocking(move || scan_file(&path))
+            .await
+            .expect("scanning data file");
+        if let Some(ids) = ids {
+            file_ids.insert(entry.file_name(), ids);
+        }
+    }
+    debug!("{} data files scanned.", file_ids.len());
+    Ok(file_ids)
+}
+
+/// Gets the IDs of the objects in a JSON, NDJSON or template file, if it can be parsed.
+fn scan_file(path: &Path) -> Option<ObjectIds> {
+    let ext = path.extension()?;
+    if ext == "json" {
+        scan_object(serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?)
+    } else if ext == "template" {
+        scan_template(serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?)
+    } else if ext == "ndjson" {
+        let file = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+        let mut ids = ObjectIds::default();
+        for line in std::io::BufRead::lines(file) {
+            let line = line.ok()?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // as when loaded, lines with an `ids` member are templates.
+            let value = serde_json::from_str::<Value>(&line).ok()?;
+            ids.append(if value.get("ids").is_some() {
+                scan_template(value)?
+            } else {
+                scan_object(value)?
+            });
+        }
+        Some(ids)
+    } else {
+        None
+    }
+}
+
+fn scan_object(value: Value) -> Option<ObjectIds> {
+    let rdap = RdapResponse::try_from(value).ok()?;
+    Some(ObjectIds::of_object(&rdap))
+}
+
+fn scan_template(value: Value) -> Option<ObjectIds> {
+    let template = serde_json::from_value::<Template>(value).ok()?;
+    Some(ObjectIds::of_template(&template))
+}
+
+/// Loads a JSON, NDJSON, template or server help file, giving the IDs of the objects loaded.
+/// Files of other kinds are not loaded.
+async fn load_file(
+    path: &Path,
+    tx: &mut Box<dyn TxHandle>,
+) -> Result<Option<ObjectIds>, RdapServerError> {
+    let Some(ext) = path.extension() else {
+        return Ok(None);
+    };
+    let path_name = path.to_string_lossy();
+    if ext == "template" {
+        let contents = tokio::fs::read_to_string(path).await?;
+        load_rdap_template(&contents, &path_name, tx)
+            .await
+            .map(Some)
+    } else if ext == "json" {
+        let contents = tokio::fs::read_to_string(path).await?;
+        load_rdap(&contents, &path_name, tx).await.map(Some)
+    } else if ext == "ndjson" {
+        load_ndjson(path, tx).await.map(Some)
+    } else if ext == "help" {
+        let contents = tokio::fs::read_to_string(path).await?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        load_srvhelp(&contents, &path_name, &file_name, tx).await?;
+        Ok(None)
+    } else {
+        Ok(None)
+    }
 }
 
 /// Loads the RDAP JSON files and puts them in storage.
@@ -232,25 +545,267 @@ async fn load_rdap(
     contents: &str,
     path_name: &str,
     tx: &mut Box<dyn TxHandle>,
-) -> Result<(), RdapServerError> {
+) -> Result<ObjectIds, RdapServerError> {
     debug!("loading {path_name} into storage");
     let json = serde_json::from_str::<Value>(contents);
     if let Ok(value) = json {
         let rdap = RdapResponse::try_from(value);
         if let Ok(rdap) = rdap {
-            match rdap {
-                RdapResponse::Entity(entity) => tx.add_entity(&entity).await,
-                RdapResponse::Domain(domain) => tx.add_domain(&domain).await,
-                RdapResponse::Nameserver(nameserver) => tx.add_nameserver(&nameserver).await,
-                RdapResponse::Autnum(autnum) => tx.add_autnum(&autnum).await,
-                RdapResponse::Network(network) => tx.add_network(&network).await,
-                _ => return Err(RdapServerError::NonRdapJsonFile(path_name.to_owned())),
-            }?;
+            add_rdap(&rdap, path_name, tx).await
         } else {
-            return Err(RdapServerError::NonRdapJsonFile(path_name.to_owned()));
+            Err(RdapServerError::NonRdapJsonFile(path_name.to_owned()))
         }
     } else {
-        return Err(RdapServerError::NonJsonFile(path_name.to_owned()));
+        Err(RdapServerError::NonJsonFile(path_name.to_owned()))
+    }
+}
+
+/// Puts an RDAP object into storage.
+async fn add_rdap(
+    rdap: &RdapResponse,
+    path_name: &str,
+    tx: &mut Box<dyn TxHandle>,
+) -> Result<ObjectIds, RdapServerError> {
+    match rdap {
+        RdapResponse::Entity(entity) => tx.add_entity(entity).await,
+        RdapResponse::Domain(domain) => tx.add_domain(domain).await,
+        RdapResponse::Nameserver(nameserver) => tx.add_nameserver(nameserver).await,
+        RdapResponse::Autnum(autnum) => tx.add_autnum(autnum).await,
+        RdapResponse::Network(network) => tx.add_network(network).await,
+        _ => return Err(RdapServerError::NonRdapJsonFile(path_name.to_owned())),
+    }?;
+    Ok(ObjectIds::of_object(rdap))
+}
+
+/// An RDAP object or template from a line of an NDJSON file.
+enum NdjsonItem {
+    Object(RdapResponse),
+    Template(Template),
+}
+
+/// Loads an NDJSON file, which has an RDAP object or template on each line, and puts
+/// the objects in storage.
+///
+/// The file is streamed from disk in chunks of lines. Each chunk is parsed and checked
+/// for specification errors on a blocking thread, with as many chunks in flight as there
+/// are CPUs, and the results are put in storage in the order of the lines in the file.
+async fn load_ndjson(
+    path: &Path,
+    tx: &mut Box<dyn TxHandle>,
+) -> Result<ObjectIds, RdapServerError> {
+    let path_name = path.to_string_lossy().to_string();
+    debug!("loading {path_name} into storage");
+    let mut lines = BufReader::new(tokio::fs::File::open(path).await?).lines();
+    let parallelism = std::thread::available_parallelism().map_or(4, NonZeroUsize::get);
+    let (sender, mut chunks) = mpsc::channel(parallelism);
+    let reader = tokio::spawn({
+        let path_name = path_name.clone();
+        async move {
+            let mut line_number = 0;
+            loop {
+                let mut chunk = Vec::with_capacity(NDJSON_CHUNK_LINES);
+                while chunk.len() < NDJSON_CHUNK_LINES {
+                    let Some(line) = lines.next_line().await? else {
+                        break;
+                    };
+                    line_number += 1;
+                    if !line.trim().is_empty() {
+                        chunk.push((line_number, line));
+                    }
+                }
+                if chunk.is_empty() {
+                    return Ok::<_, RdapServerError>(());
+                }
+                let path_name = path_name.clone();
+                let parsed = spawn_blocking(move || parse_ndjson_chunk(&path_name, chunk));
+                // the chunks are no longer wanted once one of them cannot be loaded.
+                if sender.send(parsed).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    });
+
+    let mut ids = ObjectIds::default();
+    let mut count: usize = 0;
+    while let Some(parsed) = chunks.recv().await {
+        let items = parsed.await.expect("parsing NDJSON lines")?;
+        for item in items {
+            match item {
+                NdjsonItem::Object(rdap) => ids.append(add_rdap(&rdap, &path_name, tx).await?),
+                NdjsonItem::Template(template) => {
+                    ids.append(ObjectIds::of_template(&template));
+                    add_template(template, tx).await?;
+                }
+            }
+            count += 1;
+        }
+    }
+    reader.await.expect("reading NDJSON lines")?;
+    debug!("{count} objects and templates loaded from {path_name}");
+    Ok(ids)
+}
+
+/// Parses and checks the numbered lines of an NDJSON file. Lines with an `ids` member
+/// are templates, and the other lines are RDAP objects.
+fn parse_ndjson_chunk(
+    path_name: &str,
+    chunk: Vec<(usize, String)>,
+) -> Result<Vec<NdjsonItem>, RdapServerError> {
+    chunk
+        .into_iter()
+        .map(|(line_number, line)| {
+            let line_name = format!("{path_name}:{line_number}");
+            let Ok(value) = serde_json::from_str::<Value>(&line) else {
+                return Err(RdapServerError::NonJsonFile(line_name));
+            };
+            let (item, errors) = if value.get("ids").is_some() {
+                let Ok(template) = serde_json::from_value::<Template>(value) else {
+                    return Err(RdapServerError::NonJsonFile(line_name));
+                };
+                let (template, errors) = template_spec_errors(template);
+                (NdjsonItem::Template(template), errors)
+            } else {
+                let Ok(rdap) = RdapResponse::try_from(value) else {
+                    return Err(RdapServerError::NonRdapJsonFile(line_name));
+                };
+                let errors = spec_errors(&rdap);
+                (NdjsonItem::Object(rdap), errors)
+            };
+            if !errors.is_empty() {
+                for error in errors {
+                    warn!("{line_name}: {error}");
+                }
+                return Err(RdapServerError::ErrorOnChecks);
+            }
+            Ok(item)
+        })
+        .collect()
+}
+
+/// Gets the specification errors of the object, or error, of a template. It is moved into
+/// RDAP to be checked and then moved back, so that it is not cloned.
+fn template_spec_errors(template: Template) -> (Template, Vec<String>) {
+    fn checked<T: TemplateObject>(object: T) -> (T, Vec<String>) {
+        let rdap = object.into_rdap();
+        let errors = spec_errors(&rdap);
+        (T::from_rdap(rdap), errors)
+    }
+    match template {
+        Template::Domain { domain, ids } => {
+            let (domain, errors) = checked(domain);
+            (Template::Domain { domain, ids }, errors)
+        }
+        Template::Entity { entity, ids } => {
+            let (entity, errors) = checked(entity);
+            (Template::Entity { entity, ids }, errors)
+        }
+        Template::Nameserver { nameserver, ids } => {
+            let (nameserver, errors) = checked(nameserver);
+            (Template::Nameserver { nameserver, ids }, errors)
+        }
+        Template::Autnum { autnum, ids } => {
+            let (autnum, errors) = checked(autnum);
+            (Template::Autnum { autnum, ids }, errors)
+        }
+        Template::Network { network, ids } => {
+            let (network, errors) = checked(network);
+            (Template::Network { network, ids }, errors)
+        }
+    }
+}
+
+/// The object, or error, of a template, which is moved into RDAP and back.
+trait TemplateObject {
+    fn into_rdap(self) -> RdapResponse;
+
+    /// Moves RDAP given by [TemplateObject::into_rdap] back.
+    fn from_rdap(rdap: RdapResponse) -> Self;
+}
+
+macro_rules! impl_template_object {
+    ($or_error:ident, $object:ident, $class:ident) => {
+        impl TemplateObject for $or_error {
+            fn into_rdap(self) -> RdapResponse {
+                match self {
+                    $or_error::$object(object) => RdapResponse::$class(object),
+                    $or_error::ErrorResponse(error) => RdapResponse::ErrorResponse(error),
+                }
+            }
+
+            fn from_rdap(rdap: RdapResponse) -> Self {
+                match rdap {
+                    RdapResponse::$class(object) => $or_error::$object(object),
+                    RdapResponse::ErrorResponse(error) => $or_error::ErrorResponse(error),
+                    _ => unreachable!("RDAP not of a template"),
+                }
+            }
+        }
+    };
+}
+
+impl_template_object!(DomainOrError, DomainObject, Domain);
+impl_template_object!(EntityOrError, EntityObject, Entity);
+impl_template_object!(NameserverOrError, NameserverObject, Nameserver);
+impl_template_object!(AutnumOrError, AutnumObject, Autnum);
+impl_template_object!(NetworkOrError, NetworkObject, Network);
+
+/// Gets the specification errors found by the checks of RDAP.
+pub(crate) fn spec_errors(rdap: &RdapResponse) -> Vec<String> {
+    let checks = rdap.get_checks(CheckParams {
+        do_subchecks: true,
+        root: rdap,
+        parent_type: rdap.get_type(),
+    });
+    let mut errors = Vec::new();
+    traverse_checks(
+        &checks,
+        &[CheckClass::SpecificationError],
+        None,
+        &mut |struct_tree, check_item| errors.push(format!("{struct_tree} -> {check_item}")),
+    );
+    errors
+}
+
+/// Loads the tombstone files and deletes the objects they name from storage.
+async fn load_tombstone(
+    contents: &str,
+    path_name: &str,
+    tx: &mut Box<dyn TxHandle>,
+) -> Result<(), RdapServerError> {
+    debug!("processing {path_name} tombstone");
+    let json = serde_json::from_str::<ObjectIds>(contents);
+    if let Ok(ids) = json {
+        delete_ids(&ids, tx).await
+    } else {
+        Err(RdapServerError::NonJsonFile(path_name.to_owned()))
+    }
+}
+
+/// Deletes the objects, or the errors stored in their place, with the IDs from storage.
+pub(crate) async fn delete_ids(
+    ids: &ObjectIds,
+    tx: &mut Box<dyn TxHandle>,
+) -> Result<(), RdapServerError> {
+    for id in &ids.domains {
+        debug!("deleting domain {id:?}");
+        tx.delete_domain(id).await?;
+    }
+    for id in &ids.entities {
+        debug!("deleting entity {id:?}");
+        tx.delete_entity(id).await?;
+    }
+    for id in &ids.nameservers {
+        debug!("deleting nameserver {id:?}");
+        tx.delete_nameserver(id).await?;
+    }
+    for id in &ids.autnums {
+        debug!("deleting autnum {id:?}");
+        tx.delete_autnum(id).await?;
+    }
+    for id in &ids.networks {
+        debug!("deleting network {id:?}");
+        tx.delete_network(id).await?;
     }
     Ok(())
 }
@@ -290,118 +845,97 @@ async fn load_rdap_template(
     contents: &str,
     path_name: &str,
     tx: &mut Box<dyn TxHandle>,
-) -> Result<(), RdapServerError> {
+) -> Result<ObjectIds, RdapServerError> {
     debug!("processing {path_name} template");
     let json = serde_json::from_str::<Template>(contents);
     if let Ok(value) = json {
-        match value {
-            Template::Domain { domain, ids } => {
-                for id in ids {
-                    debug!("adding domain from template for {id:?}");
-                    match &domain {
-                        DomainOrError::DomainObject(domain) => {
-                            let domain = make_domain_from_template(domain, id);
-                            tx.add_domain(&domain).await?;
-                        }
-                        DomainOrError::ErrorResponse(error) => {
-                            tx.add_domain_err(&id, error).await?;
-                        }
-                    };
-                }
-            }
-            Template::Entity { entity, ids } => {
-                for id in ids {
-                    debug!("adding entity from template for {id:?}");
-                    match &entity {
-                        EntityOrError::EntityObject(entity) => {
-                            let entity = make_entity_from_template(entity, id);
-                            tx.add_entity(&entity).await?;
-                        }
-                        EntityOrError::ErrorResponse(error) => {
-                            tx.add_entity_err(&id, error).await?;
-                        }
-                    };
-                }
-            }
-            Template::Nameserver { nameserver, ids } => {
-                for id in ids {
-                    debug!("adding nameserver from template for {id:?}");
-                    match &nameserver {
-                        NameserverOrError::NameserverObject(nameserver) => {
-                            let nameserver = make_nameserver_from_template(nameserver, id);
-                            tx.add_nameserver(&nameserver).await?;
-                        }
-                        NameserverOrError::ErrorResponse(error) => {
-                            tx.add_nameserver_err(&id, error).await?;
-                        }
-                    };
-                }
-            }
-            Template::Autnum { autnum, ids } => {
-                for id in ids {
-                    debug!("adding autnum from template for {id:?}");
-                    match &autnum {
-                        AutnumOrError::AutnumObject(autnum) => {
-                            let autnum = make_autnum_from_template(autnum, id);
-                            tx.add_autnum(&autnum).await?;
-                        }
-                        AutnumOrError::ErrorResponse(error) => {
-                            tx.add_autnum_err(&id, error).await?;
-                        }
-                    };
-                }
-            }
-            Template::Network { network, ids } => {
-                for id in ids {
-                    debug!("adding network from template for {id:?}");
-                    match &network {
-                        NetworkOrError::NetworkObject(network) => {
-                            let network = make_network_from_template(network, id)?;
-                            tx.add_network(&network).await?;
-                        }
-                        NetworkOrError::ErrorResponse(error) => {
-                            tx.add_network_err(&id, error).await?;
-                        }
-                    };
-                }
-            }
-        };
+        let ids = ObjectIds::of_template(&value);
+        add_template(value, tx).await?;
+        Ok(ids)
     } else {
-        return Err(RdapServerError::NonJsonFile(path_name.to_owned()));
+        Err(RdapServerError::NonJsonFile(path_name.to_owned()))
     }
-    Ok(())
 }
 
-pub(crate) async fn reload_data(
-    store: Box<dyn StoreOps>,
-    config: ServiceConfig,
+/// Creates the RDAP objects or errors of a template for each of its IDs, and puts them
+/// into storage.
+pub(crate) async fn add_template(
+    template: Template,
+    tx: &mut Box<dyn TxHandle>,
 ) -> Result<(), RdapServerError> {
-    let update_path = PathBuf::from(&config.data_dir);
-    let update_path = update_path.join(UPDATE);
-    let reload_path = PathBuf::from(&config.data_dir);
-    let reload_path = reload_path.join(RELOAD);
-    let mut last_time = SystemTime::now();
-    loop {
-        sleep(Duration::from_millis(1000)).await;
-        let update_meta = tokio::fs::metadata(&update_path).await;
-        if update_meta.is_ok() {
-            let modified = update_meta.unwrap().modified()?;
-            if modified > last_time {
-                last_time = modified;
-                info!("Data being updated.");
-                load_data(&config, &*store, false).await?;
+    match template {
+        Template::Domain { domain, ids } => {
+            for id in ids {
+                debug!("adding domain from template for {id:?}");
+                match &domain {
+                    DomainOrError::DomainObject(domain) => {
+                        let domain = make_domain_from_template(domain, id);
+                        tx.add_domain(&domain).await?;
+                    }
+                    DomainOrError::ErrorResponse(error) => {
+                        tx.add_domain_err(&id, error).await?;
+                    }
+                };
             }
-        };
-        let reload_meta = tokio::fs::metadata(&reload_path).await;
-        if reload_meta.is_ok() {
-            let modified = reload_meta.unwrap().modified()?;
-            if modified > last_time {
-                last_time = modified;
-                info!("Data being reloaded.");
-                load_data(&config, &*store, true).await?;
+        }
+        Template::Entity { entity, ids } => {
+            for id in ids {
+                debug!("adding entity from template for {id:?}");
+                match &entity {
+                    EntityOrError::EntityObject(entity) => {
+                        let entity = make_entity_from_template(entity, id);
+                        tx.add_entity(&entity).await?;
+                    }
+                    EntityOrError::ErrorResponse(error) => {
+                        tx.add_entity_err(&id, error).await?;
+                    }
+                };
             }
-        };
-    }
+        }
+        Template::Nameserver { nameserver, ids } => {
+            for id in ids {
+                debug!("adding nameserver from template for {id:?}");
+                match &nameserver {
+                    NameserverOrError::NameserverObject(nameserver) => {
+                        let nameserver = make_nameserver_from_template(nameserver, id);
+                        tx.add_nameserver(&nameserver).await?;
+                    }
+                    NameserverOrError::ErrorResponse(error) => {
+                        tx.add_nameserver_err(&id, error).await?;
+                    }
+                };
+            }
+        }
+        Template::Autnum { autnum, ids } => {
+            for id in ids {
+                debug!("adding autnum from template for {id:?}");
+                match &autnum {
+                    AutnumOrError::AutnumObject(autnum) => {
+                        let autnum = make_autnum_from_template(autnum, id);
+                        tx.add_autnum(&autnum).await?;
+                    }
+                    AutnumOrError::ErrorResponse(error) => {
+                        tx.add_autnum_err(&id, error).await?;
+                    }
+                };
+            }
+        }
+        Template::Network { network, ids } => {
+            for id in ids {
+                debug!("adding network from template for {id:?}");
+                match &network {
+                    NetworkOrError::NetworkObject(network) => {
+                        let network = make_network_from_template(network, id)?;
+                        tx.add_network(&network).await?;
+                    }
+                    NetworkOrError::ErrorResponse(error) => {
+                        tx.add_network_err(&id, error).await?;
+                    }
+                };
+            }
+        }
+    };
+    Ok(())
 }
 
 pub async fn trigger_reload(data_dir: &str) -> Result<(), RdapServerError> {
@@ -550,7 +1084,7 @@ fn make_network_from_template(
 #[allow(non_snake_case)]
 mod tests {
 
-    use icann_rdap_common::response::{domain::Domain, types::Link};
+    use icann_rdap_common::response::{domain::Domain, error::Error, types::Link};
 
     use super::*;
 
@@ -588,6 +1122,28 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn GIVEN_template_redirect_WHEN_template_spec_errors_THEN_template_given_back() {
+        // GIVEN
+        let template = Template::Domain {
+            domain: DomainOrError::ErrorResponse(
+                Error::redirect().url("https://other.example/").build(),
+            ),
+            ids: vec![DomainId::builder().ldh_name("foo.example").build()],
+        };
+        let expected = serde_json::to_string(&template).expect("serializing template");
+
+        // WHEN
+        let (actual, errors) = template_spec_errors(template);
+
+        // THEN
+        assert!(errors.is_empty());
+        assert_eq!(
+            serde_json::to_string(&actual).expect("serializing template"),
+            expected
+        );
+    }
+
     #[test]
     fn GIVEN_template_network_with_cidr_WHEN_serialize_THEN_success() {
         // GIVEN
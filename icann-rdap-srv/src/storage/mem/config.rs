@@ -1,4 +1,9 @@
 use buildstructor::Builder;
 
 #[derive(Debug, Builder, Clone)]
-pub struct MemConfig {}
+pub struct MemConfig {
+    /// If specified, a snapshot of the data is written to this file after each commit,
+    /// and the data is restored from it on startup when it is newer than the data directory.
+    /// The file must not be in the data directory.
+    pub snapshot_file: Option<String>,
+}
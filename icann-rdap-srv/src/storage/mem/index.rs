@@ -0,0 +1,324 @@
+use std::sync::Arc;
+
+use icann_rdap_common::response::entity::Entity;
+use im::OrdSet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::storage::search::{entity_emails, entity_full_name, RelatedEntitySearch, SearchPattern};
+
+/// An index of values to the keys of the objects having those values.
+///
+/// The index is a persistent, ordered set of value and key pairs, so cloning it is cheap and
+/// the clone shares its structure with the original until either is changed. Keeping pairs
+/// rather than a set of keys for each value means a value indexing a single object costs no
+/// more than the pair, and a value indexing many objects is changed without copying its keys.
+#[derive(Debug, Clone)]
+pub struct KeyIndex<T: Ord + Clone> {
+    pairs: OrdSet<(T, Arc<str>)>,
+}
+
+impl<T: Ord + Clone> Default for KeyIndex<T> {
+    fn default() -> Self {
+        Self {
+            pairs: OrdSet::new(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> KeyIndex<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the object with the given key under the value.
+    pub fn insert(&mut self, value: T, key: &str) {
+        self.pairs.insert((value, Arc::from(key)));
+    }
+
+    /// Removes the object with the given key from under the value.
+    pub fn remove(&mut self, value: T, key: &str) {
+        self.pairs.remove(&(value, Arc::from(key)));
+    }
+
+    /// Finds the keys of the objects indexed under the value.
+    pub fn get(&self, value: T) -> impl Iterator<Item = &str> {
+        self.range_from(value.clone())
+            .take_while(move |(found, _)| **found == value)
+            .map(|(_, key)| key)
+    }
+
+    /// Gets the values and keys in order, starting with the given value.
+    pub fn range_from(&self, value: T) -> impl Iterator<Item = (&T, &str)> {
+        self.pairs
+            .range((value, Arc::from(""))..)
+            .map(|(value, key)| (value, key.as_ref()))
+    }
+
+    /// Gets all the values and keys in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &str)> {
+        self.pairs.iter().map(|(value, key)| (value, key.as_ref()))
+    }
+}
+
+/// Indexes are serialized as their value and key pairs, in order.
+impl<T: Ord + Clone + Serialize> Serialize for KeyIndex<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Ord + Clone + Deserialize<'de>> Deserialize<'de> for KeyIndex<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pairs = Vec::<(T, Arc<str>)>::deserialize(deserializer)?;
+        Ok(Self {
+            pairs: pairs.into_iter().collect(),
+        })
+    }
+}
+
+/// An index of names to the keys of the objects having those names, supporting
+/// RFC 9082 partial match searches.
+///
+/// Names are kept in lower case both forwards and reversed, so that patterns with
+/// a trailing wildcard (e.g. `foo*`) become a range scan of the forward names and
+/// patterns with a leading wildcard (e.g. `*.example`) become a range scan of the
+/// reversed names.
+#[derive(Debug, Clone, Default)]
+pub struct NameIndex {
+    forward: KeyIndex<Arc<str>>,
+    reverse: KeyIndex<Arc<str>>,
+}
+
+fn reverse(name: &str) -> String {
+    name.chars().rev().collect()
+}
+
+impl NameIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the object with the given key under the name.
+    pub fn insert(&mut self, name: &str, key: &str) {
+        let name = name.to_lowercase();
+        self.reverse.insert(Arc::from(reverse(&name)), key);
+        self.forward.insert(Arc::from(name), key);
+    }
+
+    /// Removes the object with the given key from under the name.
+    pub fn remove(&mut self, name: &str, key: &str) {
+        let name = name.to_lowercase();
+        self.reverse.remove(Arc::from(reverse(&name)), key);
+        self.forward.remove(Arc::from(name), key);
+    }
+
+    /// Finds the keys of the objects with names matching the pattern.
+    ///
+    /// Keys are in the order of the names that matched. An object indexed
+    /// under more than one matching name will have its key returned more than once.
+    pub fn search<'a>(
+        &'a self,
+        pattern: &'a SearchPattern,
+    ) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        if !pattern.is_partial() {
+            return Box::new(self.forward.get(Arc::from(pattern.prefix())));
+        }
+        let prefix = pattern.prefix();
+        let suffix = pattern.suffix();
+        if !prefix.is_empty() && prefix.len() >= suffix.len() {
+            Box::new(
+                self.forward
+                    .range_from(Arc::from(prefix))
+                    .take_while(move |(name, _)| name.starts_with(prefix))
+                    .filter(|(name, _)| pattern.is_match(name))
+                    .map(|(_, key)| key),
+            )
+        } else if !suffix.is_empty() {
+            let reversed = reverse(suffix);
+            Box::new(
+                self.reverse
+                    .range_from(Arc::from(reversed.as_str()))
+                    .take_while(move |(name, _)| name.starts_with(&reversed))
+                    .filter(|(name, _)| pattern.is_match(&reverse(name)))
+                    .map(|(_, key)| key),
+            )
+        } else {
+            Box::new(
+                self.forward
+                    .iter()
+                    .filter(|(name, _)| pattern.is_match(name))
+                    .map(|(_, key)| key),
+            )
+        }
+    }
+}
+
+/// Only the forward names are serialized, as the reversed names are made from them.
+impl Serialize for NameIndex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.forward.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NameIndex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let forward = KeyIndex::<Arc<str>>::deserialize(deserializer)?;
+        let mut reverse_index = KeyIndex::new();
+        for (name, key) in forward.iter() {
+            reverse_index.insert(Arc::from(reverse(name)), key);
+        }
+        Ok(Self {
+            forward,
+            reverse: reverse_index,
+        })
+    }
+}
+
+/// An index of the entities embedded in objects to the keys of those objects,
+/// supporting RFC 9536 reverse searches.
+///
+/// The index only narrows down the objects which may be related. Found objects must
+/// still be checked with [RelatedEntitySearch::is_related], as the properties searched
+/// for must all belong to the same embedded entity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelatedEntityIndex {
+    handles: NameIndex,
+    full_names: NameIndex,
+    emails: NameIndex,
+    roles: KeyIndex<Arc<str>>,
+}
+
+impl RelatedEntityIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the object with the given key under its embedded entities.
+    pub fn insert(&mut self, entities: Option<&Vec<Entity>>, key: &str) {
+        for entity in entities.into_iter().flatten() {
+            if let Some(handle) = entity.object_common.handle.as_ref() {
+                self.handles.insert(handle, key);
+            }
+            if let Some(full_name) = entity_full_name(entity) {
+                self.full_names.insert(&full_name, key);
+            }
+            for email in entity_emails(entity) {
+                self.emails.insert(&email, key);
+            }
+            for role in entity.roles.iter().flatten() {
+                self.roles.insert(Arc::from(role.to_lowercase()), key);
+            }
+        }
+    }
+
+    /// Removes the object with the given key from under its embedded entities.
+    pub fn remove(&mut self, entities: Option<&Vec<Entity>>, key: &str) {
+        for entity in entities.into_iter().flatten() {
+            if let Some(handle) = entity.object_common.handle.as_ref() {
+                self.handles.remove(handle, key);
+            }
+            if let Some(full_name) = entity_full_name(entity) {
+                self.full_names.remove(&full_name, key);
+            }
+            for email in entity_emails(entity) {
+                self.emails.remove(&email, key);
+            }
+            for role in entity.roles.iter().flatten() {
+                self.roles.remove(Arc::from(role.to_lowercase()), key);
+            }
+        }
+    }
+
+    /// Finds the keys of the objects which may be related by the search, using
+    /// the first of the handle, full name, email or role given in the search.
+    pub fn search<'a>(
+        &'a self,
+        search: &'a RelatedEntitySearch,
+    ) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        if let Some(handle) = search.handle.as_ref() {
+            self.handles.search(handle)
+        } else if let Some(full_name) = search.full_name.as_ref() {
+            self.full_names.search(full_name)
+        } else if let Some(email) = search.email.as_ref() {
+            self.emails.search(email)
+        } else if let Some(role) = search.role.as_ref() {
+            Box::new(self.roles.get(Arc::from(role.to_lowercase())))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+
+    use rstest::rstest;
+
+    use crate::storage::search::SearchPattern;
+
+    use super::{KeyIndex, NameIndex};
+
+    fn index() -> NameIndex {
+        let mut index = NameIndex::new();
+        index.insert("foo.example", "foo.example");
+        index.insert("bar.example", "bar.example");
+        index.insert("foo.example.net", "foo.example.net");
+        index.insert("Caf\u{e9}.example", "xn--caf-dma.example");
+        index
+    }
+
+    #[rstest]
+    #[case("foo.example", &["foo.example"])]
+    #[case("foo*", &["foo.example", "foo.example.net"])]
+    #[case("*.example", &["foo.example", "bar.example", "xn--caf-dma.example"])]
+    #[case("f*.net", &["foo.example.net"])]
+    #[case("*example*", &["bar.example", "xn--caf-dma.example", "foo.example", "foo.example.net"])]
+    #[case("caf\u{e9}*", &["xn--caf-dma.example"])]
+    #[case("baz*", &[])]
+    fn GIVEN_names_WHEN_search_THEN_matching_keys(
+        #[case] pattern: &str,
+        #[case] expected: &[&str],
+    ) {
+        // GIVEN
+        let index = index();
+        let pattern = SearchPattern::new(pattern);
+
+        // WHEN
+        let actual = index.search(&pattern).collect::<Vec<_>>();
+
+        // THEN
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn GIVEN_removed_name_WHEN_search_THEN_not_found() {
+        // GIVEN
+        let mut index = index();
+        index.remove("foo.example", "foo.example");
+        let pattern = SearchPattern::new("foo*");
+
+        // WHEN
+        let actual = index.search(&pattern).collect::<Vec<_>>();
+
+        // THEN
+        assert_eq!(actual, ["foo.example.net"]);
+    }
+
+    #[test]
+    fn GIVEN_keys_under_values_WHEN_get_THEN_only_keys_under_value() {
+        // GIVEN
+        let mut index = KeyIndex::new();
+        index.insert(1, "foo");
+        index.insert(2, "bar");
+        index.insert(2, "baz");
+        index.insert(3, "foo");
+        index.remove(2, "baz");
+
+        // WHEN
+        let actual = index.get(2).collect::<Vec<_>>();
+
+        // THEN
+        assert_eq!(actual, ["bar"]);
+    }
+}
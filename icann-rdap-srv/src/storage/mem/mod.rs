@@ -1,5 +1,8 @@
 #![allow(dead_code)] // TODO remove
 
 pub mod config;
+pub mod index;
 pub mod ops;
+pub mod snapshot;
+pub mod table;
 pub mod tx;
@@ -1,47 +1,190 @@
-use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::Arc};
+use std::{
+    collections::HashSet,
+    net::IpAddr,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
 
 use async_trait::async_trait;
-use btree_range_map::RangeMap;
-use icann_rdap_common::response::RdapResponse;
+use icann_rdap_common::response::{
+    domain::Domain, entity::Entity, nameserver::Nameserver, RdapResponse,
+};
+use im::HashMap;
 use ipnet::{IpNet, Ipv4Net, Ipv6Net};
-use prefix_trie::PrefixMap;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, Mutex, OwnedMutexGuard, RwLock};
+use tracing::{info, warn};
 
 use crate::{
     error::RdapServerError,
     rdap::response::NOT_FOUND,
-    storage::{StoreOps, TxHandle},
+    storage::{
+        search::{
+            domain_search_results, entity_search_results, nameserver_search_results,
+            reverse_search_results, RelatedEntitySearch, ReverseSearchType, SearchPattern,
+        },
+        Lookup, ObjectCounts, SerializedObject, StoreOps, TxHandle,
+    },
+};
+
+use super::{
+    config::MemConfig,
+    index::{KeyIndex, NameIndex, RelatedEntityIndex},
+    snapshot::{restore_snapshot, spawn_writer},
+    table::{PrefixTable, RangeTable},
+    tx::MemTx,
 };
 
-use super::{config::MemConfig, tx::MemTx};
+/// An object or error as kept by the in-memory store. Objects are also kept serialized,
+/// so that lookups of them can be served without cloning or serializing the object.
+/// Errors (such as redirects) and help are rewritten before being served, so they are not.
+#[derive(Debug)]
+pub(crate) struct Stored {
+    pub(crate) response: RdapResponse,
+    pub(crate) serialized: Option<SerializedObject>,
+}
+
+impl Stored {
+    pub(crate) fn object(response: RdapResponse) -> Result<Arc<Self>, RdapServerError> {
+        let serialized = SerializedObject::new(&response)?;
+        Ok(Arc::new(Self {
+            response,
+            serialized: Some(serialized),
+        }))
+    }
+
+    pub(crate) fn unserialized(response: RdapResponse) -> Arc<Self> {
+        Arc::new(Self {
+            response,
+            serialized: None,
+        })
+    }
+}
+
+/// The objects and search indexes of the in-memory store.
+///
+/// Every collection is persistent, so cloning the data is cheap and the clone shares its
+/// structure with the original. A transaction works on a clone and pays only for what it
+/// changes.
+#[derive(Clone, Default)]
+pub(crate) struct MemData {
+    pub(crate) autnums: RangeTable<Arc<Stored>>,
+    pub(crate) ip4: PrefixTable<Ipv4Net, Arc<Stored>>,
+    pub(crate) ip6: PrefixTable<Ipv6Net, Arc<Stored>>,
+    pub(crate) domains: HashMap<Arc<str>, Arc<Stored>>,
+    pub(crate) idns: HashMap<Arc<str>, Arc<Stored>>,
+    pub(crate) domain_names: NameIndex,
+    pub(crate) domain_ns_names: NameIndex,
+    pub(crate) domain_related: RelatedEntityIndex,
+    pub(crate) domain_ns_ips: KeyIndex<IpAddr>,
+    pub(crate) nameservers: HashMap<Arc<str>, Arc<Stored>>,
+    pub(crate) nameserver_names: NameIndex,
+    pub(crate) nameserver_ips: KeyIndex<IpAddr>,
+    pub(crate) nameserver_related: RelatedEntityIndex,
+    pub(crate) entities: HashMap<Arc<str>, Arc<Stored>>,
+    pub(crate) entity_names: NameIndex,
+    pub(crate) entity_related: RelatedEntityIndex,
+    pub(crate) entity_handles: NameIndex,
+    pub(crate) srvhelps: HashMap<Arc<str>, Arc<Stored>>,
+}
 
 #[derive(Clone)]
 pub struct Mem {
-    pub(crate) autnums: Arc<RwLock<RangeMap<u32, Arc<RdapResponse>>>>,
-    pub(crate) ip4: Arc<RwLock<PrefixMap<Ipv4Net, Arc<RdapResponse>>>>,
-    pub(crate) ip6: Arc<RwLock<PrefixMap<Ipv6Net, Arc<RdapResponse>>>>,
-    pub(crate) domains: Arc<RwLock<HashMap<String, Arc<RdapResponse>>>>,
-    pub(crate) idns: Arc<RwLock<HashMap<String, Arc<RdapResponse>>>>,
-    pub(crate) nameservers: Arc<RwLock<HashMap<String, Arc<RdapResponse>>>>,
-    pub(crate) entities: Arc<RwLock<HashMap<String, Arc<RdapResponse>>>>,
-    pub(crate) srvhelps: Arc<RwLock<HashMap<String, Arc<RdapResponse>>>>,
+    data: Arc<RwLock<Arc<MemData>>>,
+    // held by each transaction until it ends, so that no commit replaces another.
+    writer: Arc<Mutex<()>>,
+    // started by the first commit when there is a snapshot file.
+    snapshots: Arc<OnceLock<watch::Sender<Arc<MemData>>>>,
     pub(crate) config: MemConfig,
 }
 
 impl Mem {
     pub fn new(config: MemConfig) -> Self {
         Self {
-            autnums: Arc::new(RwLock::new(RangeMap::new())),
-            ip4: Arc::new(RwLock::new(PrefixMap::new())),
-            ip6: Arc::new(RwLock::new(PrefixMap::new())),
-            domains: Arc::new(RwLock::new(HashMap::new())),
-            idns: Arc::new(RwLock::new(HashMap::new())),
-            nameservers: Arc::new(RwLock::new(HashMap::new())),
-            entities: Arc::new(RwLock::new(HashMap::new())),
-            srvhelps: Arc::new(RwLock::new(HashMap::new())),
+            data: Arc::new(RwLock::new(Arc::new(MemData::default()))),
+            writer: Arc::new(Mutex::new(())),
+            snapshots: Arc::new(OnceLock::new()),
             config,
         }
     }
+
+    /// Waits for the transaction being written, if any, to end, giving the lock which
+    /// the next transaction holds until it ends.
+    pub(crate) async fn lock_writer(&self) -> OwnedMutexGuard<()> {
+        Arc::clone(&self.writer).lock_owned().await
+    }
+
+    /// Gets the data as last committed. Later commits do not change it.
+    pub(crate) async fn snapshot(&self) -> Arc<MemData> {
+        Arc::clone(&*self.data.read().await)
+    }
+
+    /// Replaces the data with the data of a transaction, all at once. When there is a
+    /// snapshot file, a snapshot of the data is then written in the background.
+    pub(crate) async fn replace(&self, data: MemData) {
+        let data = Arc::new(data);
+        *self.data.write().await = Arc::clone(&data);
+        if let Some(snapshot_file) = self.config.snapshot_file.as_deref() {
+            self.snapshots
+                .get_or_init(|| spawn_writer(snapshot_file))
+                .send_replace(data);
+        }
+    }
+}
+
+/// Gets the objects stored under the keys, skipping duplicate keys and anything
+/// stored that is not the wanted object (such as a redirect). At most one more than
+/// `max_results` objects are returned so that truncated results can be detected.
+fn found_objects<'a, T: Clone>(
+    objects: &HashMap<Arc<str>, Arc<Stored>>,
+    keys: impl Iterator<Item = &'a str>,
+    max_results: usize,
+    object: impl Fn(&RdapResponse) -> Option<&T>,
+) -> Vec<T> {
+    let mut seen = HashSet::new();
+    keys.filter(|key| seen.insert(*key))
+        .filter_map(|key| objects.get(key).and_then(|stored| object(&stored.response)))
+        .take(max_results.saturating_add(1))
+        .cloned()
+        .collect()
+}
+
+fn found_domains<'a>(
+    domains: &HashMap<Arc<str>, Arc<Stored>>,
+    ldh_names: impl Iterator<Item = &'a str>,
+    max_results: usize,
+) -> Vec<Domain> {
+    found_objects(domains, ldh_names, max_results, |response| match response {
+        RdapResponse::Domain(domain) => Some(domain),
+        _ => None,
+    })
+}
+
+fn found_nameservers<'a>(
+    nameservers: &HashMap<Arc<str>, Arc<Stored>>,
+    ldh_names: impl Iterator<Item = &'a str>,
+    max_results: usize,
+) -> Vec<Nameserver> {
+    found_objects(
+        nameservers,
+        ldh_names,
+        max_results,
+        |response| match response {
+            RdapResponse::Nameserver(nameserver) => Some(nameserver),
+            _ => None,
+        },
+    )
+}
+
+fn found_entities<'a>(
+    entities: &HashMap<Arc<str>, Arc<Stored>>,
+    handles: impl Iterator<Item = &'a str>,
+    max_results: usize,
+) -> Vec<Entity> {
+    found_objects(entities, handles, max_results, |response| match response {
+        RdapResponse::Entity(entity) => Some(entity),
+        _ => None,
+    })
 }
 
 impl Default for Mem {
@@ -61,72 +204,238 @@ impl StoreOps for Mem {
     }
 
     async fn new_truncate_tx(&self) -> Result<Box<dyn TxHandle>, RdapServerError> {
-        Ok(Box::new(MemTx::new_truncate(self)))
+        Ok(Box::new(MemTx::new_truncate(self).await))
     }
 
     async fn get_domain_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError> {
-        let domains = self.domains.read().await;
-        let result = domains.get(ldh);
+        let data = self.snapshot().await;
+        let result = data.domains.get(ldh);
         match result {
-            Some(domain) => Ok(RdapResponse::clone(domain)),
+            Some(domain) => Ok(domain.response.clone()),
             None => Ok(NOT_FOUND.clone()),
         }
     }
 
     async fn get_domain_by_unicode(&self, unicode: &str) -> Result<RdapResponse, RdapServerError> {
-        let idns = self.idns.read().await;
-        let result = idns.get(unicode);
+        let data = self.snapshot().await;
+        let result = data.idns.get(unicode);
         match result {
-            Some(domain) => Ok(RdapResponse::clone(domain)),
+            Some(domain) => Ok(domain.response.clone()),
             None => Ok(NOT_FOUND.clone()),
         }
     }
 
+    async fn search_domains_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let pattern = SearchPattern::new(name);
+        let results = found_domains(
+            &data.domains,
+            data.domain_names.search(&pattern),
+            max_results,
+        );
+        Ok(domain_search_results(results, max_results))
+    }
+
+    async fn search_domains_by_ns_ldh_name(
+        &self,
+        ns_ldh_name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let pattern = SearchPattern::new(ns_ldh_name);
+        let results = found_domains(
+            &data.domains,
+            data.domain_ns_names.search(&pattern),
+            max_results,
+        );
+        Ok(domain_search_results(results, max_results))
+    }
+
+    async fn search_domains_by_ns_ip(
+        &self,
+        ns_ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let addr = ns_ip.parse::<IpAddr>()?;
+        let results = found_domains(&data.domains, data.domain_ns_ips.get(addr), max_results);
+        Ok(domain_search_results(results, max_results))
+    }
+
     async fn get_entity_by_handle(&self, handle: &str) -> Result<RdapResponse, RdapServerError> {
-        let entities = self.entities.read().await;
-        let result = entities.get(handle);
+        let data = self.snapshot().await;
+        let result = data.entities.get(handle);
         match result {
-            Some(entity) => Ok(RdapResponse::clone(entity)),
+            Some(entity) => Ok(entity.response.clone()),
             None => Ok(NOT_FOUND.clone()),
         }
     }
 
+    async fn search_entities_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let pattern = SearchPattern::new(name);
+        let results = found_entities(
+            &data.entities,
+            data.entity_names.search(&pattern),
+            max_results,
+        );
+        Ok(entity_search_results(results, max_results))
+    }
+
+    async fn search_entities_by_handle(
+        &self,
+        handle: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let pattern = SearchPattern::new(handle);
+        let results = found_entities(
+            &data.entities,
+            data.entity_handles.search(&pattern),
+            max_results,
+        );
+        Ok(entity_search_results(results, max_results))
+    }
+
     async fn get_nameserver_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError> {
-        let nameservers = self.nameservers.read().await;
-        let result = nameservers.get(ldh);
+        let data = self.snapshot().await;
+        let result = data.nameservers.get(ldh);
         match result {
-            Some(nameserver) => Ok(RdapResponse::clone(nameserver)),
+            Some(nameserver) => Ok(nameserver.response.clone()),
             None => Ok(NOT_FOUND.clone()),
         }
     }
 
+    async fn search_nameservers_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let pattern = SearchPattern::new(name);
+        let results = found_nameservers(
+            &data.nameservers,
+            data.nameserver_names.search(&pattern),
+            max_results,
+        );
+        Ok(nameserver_search_results(results, max_results))
+    }
+
+    async fn search_nameservers_by_ip(
+        &self,
+        ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let net = if ip.contains('/') {
+            IpNet::from_str(ip)?.trunc()
+        } else {
+            IpNet::from(ip.parse::<IpAddr>()?)
+        };
+        let (first, last) = (net.network(), net.broadcast());
+        let ldh_names = data
+            .nameserver_ips
+            .range_from(first)
+            .take_while(|(addr, _)| **addr <= last)
+            .map(|(_, ldh_name)| ldh_name);
+        let results = found_nameservers(&data.nameservers, ldh_names, max_results);
+        Ok(nameserver_search_results(results, max_results))
+    }
+
+    async fn reverse_search_by_entity(
+        &self,
+        search_type: ReverseSearchType,
+        related: &RelatedEntitySearch,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
+        let response = match search_type {
+            ReverseSearchType::Domains => {
+                let results = found_objects(
+                    &data.domains,
+                    data.domain_related.search(related),
+                    max_results,
+                    |response| match response {
+                        RdapResponse::Domain(domain)
+                            if related.is_related(domain.object_common.entities.as_ref()) =>
+                        {
+                            Some(domain)
+                        }
+                        _ => None,
+                    },
+                );
+                domain_search_results(results, max_results)
+            }
+            ReverseSearchType::Nameservers => {
+                let results = found_objects(
+                    &data.nameservers,
+                    data.nameserver_related.search(related),
+                    max_results,
+                    |response| match response {
+                        RdapResponse::Nameserver(nameserver)
+                            if related.is_related(nameserver.object_common.entities.as_ref()) =>
+                        {
+                            Some(nameserver)
+                        }
+                        _ => None,
+                    },
+                );
+                nameserver_search_results(results, max_results)
+            }
+            ReverseSearchType::Entities => {
+                let results = found_objects(
+                    &data.entities,
+                    data.entity_related.search(related),
+                    max_results,
+                    |response| match response {
+                        RdapResponse::Entity(entity)
+                            if related.is_related(entity.object_common.entities.as_ref()) =>
+                        {
+                            Some(entity)
+                        }
+                        _ => None,
+                    },
+                );
+                entity_search_results(results, max_results)
+            }
+        };
+        Ok(reverse_search_results(response))
+    }
+
     async fn get_autnum_by_num(&self, num: u32) -> Result<RdapResponse, RdapServerError> {
-        let autnums = self.autnums.read().await;
-        let result = autnums.get(num);
+        let data = self.snapshot().await;
+        let result = data.autnums.get(num);
         match result {
-            Some(autnum) => Ok(RdapResponse::clone(autnum)),
+            Some(autnum) => Ok(autnum.response.clone()),
             None => Ok(NOT_FOUND.clone()),
         }
     }
 
     async fn get_network_by_ipaddr(&self, ipaddr: &str) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
         let addr = ipaddr.parse::<IpAddr>()?;
         match addr {
             IpAddr::V4(v4) => {
                 let slash32 = Ipv4Net::new(v4, 32)?;
-                let ip4s = self.ip4.read().await;
-                let result = ip4s.get_lpm(&slash32);
+                let result = data.ip4.get_lpm(&slash32);
                 match result {
-                    Some(network) => Ok(RdapResponse::clone(network.1)),
+                    Some(network) => Ok(network.1.response.clone()),
                     None => Ok(NOT_FOUND.clone()),
                 }
             }
             IpAddr::V6(v6) => {
                 let slash128 = Ipv6Net::new(v6, 128)?;
-                let ip6s = self.ip6.read().await;
-                let result = ip6s.get_lpm(&slash128);
+                let result = data.ip6.get_lpm(&slash128);
                 match result {
-                    Some(network) => Ok(RdapResponse::clone(network.1)),
+                    Some(network) => Ok(network.1.response.clone()),
                     None => Ok(NOT_FOUND.clone()),
                 }
             }
@@ -134,21 +443,20 @@ impl StoreOps for Mem {
     }
 
     async fn get_network_by_cidr(&self, cidr: &str) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
         let net = IpNet::from_str(cidr)?;
         match net {
             IpNet::V4(ipv4net) => {
-                let ip4s = self.ip4.read().await;
-                let result = ip4s.get_lpm(&ipv4net);
+                let result = data.ip4.get_lpm(&ipv4net);
                 match result {
-                    Some(network) => Ok(RdapResponse::clone(network.1)),
+                    Some(network) => Ok(network.1.response.clone()),
                     None => Ok(NOT_FOUND.clone()),
                 }
             }
             IpNet::V6(ipv6net) => {
-                let ip6s = self.ip6.read().await;
-                let result = ip6s.get_lpm(&ipv6net);
+                let result = data.ip6.get_lpm(&ipv6net);
                 match result {
-                    Some(network) => Ok(RdapResponse::clone(network.1)),
+                    Some(network) => Ok(network.1.response.clone()),
                     None => Ok(NOT_FOUND.clone()),
                 }
             }
@@ -156,12 +464,84 @@ impl StoreOps for Mem {
     }
 
     async fn get_srv_help(&self, host: Option<&str>) -> Result<RdapResponse, RdapServerError> {
+        let data = self.snapshot().await;
         let host = host.unwrap_or("..default");
-        let srvhelps = self.srvhelps.read().await;
-        let result = srvhelps.get(host);
+        let result = data.srvhelps.get(host);
         match result {
-            Some(srvhelp) => Ok(RdapResponse::clone(srvhelp)),
+            Some(srvhelp) => Ok(srvhelp.response.clone()),
             None => Ok(NOT_FOUND.clone()),
         }
     }
+
+    async fn restore_snapshot(&self, data_dir: &str) -> Result<bool, RdapServerError> {
+        let Some(snapshot_file) = self.config.snapshot_file.as_deref() else {
+            return Ok(false);
+        };
+        // the data is not replaced by a commit, as the snapshot need not be written again.
+        match restore_snapshot(Path::new(snapshot_file), Path::new(data_dir)).await {
+            Ok(Some(data)) => {
+                *self.data.write().await = Arc::new(data);
+                info!("Data restored from snapshot {snapshot_file}.");
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(e) => {
+                warn!(
+                    "Snapshot {snapshot_file} not restored, so the data directory is loaded: {e}"
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    async fn get_serialized(
+        &self,
+        lookup: Lookup<'_>,
+    ) -> Result<Option<SerializedObject>, RdapServerError> {
+        let data = self.snapshot().await;
+        let stored = match lookup {
+            Lookup::DomainByLdh(ldh) => data.domains.get(ldh),
+            Lookup::EntityByHandle(handle) => data.entities.get(handle),
+            Lookup::NameserverByLdh(ldh) => data.nameservers.get(ldh),
+            Lookup::AutnumByNum(num) => data.autnums.get(num),
+            Lookup::NetworkByIpAddr(ipaddr) => match ipaddr.parse::<IpAddr>()? {
+                IpAddr::V4(v4) => data.ip4.get_lpm(&Ipv4Net::new(v4, 32)?).map(|(_, v)| v),
+                IpAddr::V6(v6) => data.ip6.get_lpm(&Ipv6Net::new(v6, 128)?).map(|(_, v)| v),
+            },
+            Lookup::NetworkByCidr(cidr) => match IpNet::from_str(cidr)? {
+                IpNet::V4(ipv4net) => data.ip4.get_lpm(&ipv4net).map(|(_, v)| v),
+                IpNet::V6(ipv6net) => data.ip6.get_lpm(&ipv6net).map(|(_, v)| v),
+            },
+        };
+        Ok(stored.and_then(|stored| stored.serialized.clone()))
+    }
+
+    async fn get_object_counts(&self) -> Result<ObjectCounts, RdapServerError> {
+        // Autnums and networks are stored for each range or prefix they cover,
+        // so they are counted by distinct response.
+        let data = self.snapshot().await;
+        let autnums = data
+            .autnums
+            .iter()
+            .map(|(_, autnum)| Arc::as_ptr(autnum) as usize)
+            .collect::<HashSet<_>>()
+            .len();
+        let mut networks = data
+            .ip4
+            .iter()
+            .map(|(_, network)| Arc::as_ptr(network) as usize)
+            .collect::<HashSet<_>>();
+        networks.extend(
+            data.ip6
+                .iter()
+                .map(|(_, network)| Arc::as_ptr(network) as usize),
+        );
+        Ok(ObjectCounts {
+            domains: data.domains.len(),
+            entities: data.entities.len(),
+            nameservers: data.nameservers.len(),
+            autnums,
+            networks: networks.len(),
+        })
+    }
 }
@@ -0,0 +1,398 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use axum::body::Bytes;
+use icann_rdap_common::response::RdapResponse;
+use ipnet::{Ipv4Net, Ipv6Net};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{sync::watch, task::spawn_blocking};
+use tracing::{debug, info, warn};
+
+use crate::{error::RdapServerError, storage::SerializedObject};
+
+use super::{
+    index::{KeyIndex, NameIndex, RelatedEntityIndex},
+    ops::{MemData, Stored},
+    table::{PrefixTable, RangeTable},
+};
+
+/// The start of every snapshot file.
+const MAGIC: &[u8; 8] = b"RDAPSNAP";
+
+/// The version of the snapshot format, which must change whenever the format does.
+const FORMAT_VERSION: u32 = 1;
+
+/// The number of objects parsed on each blocking thread when restoring a snapshot.
+const PARSE_CHUNK_OBJECTS: usize = 10_000;
+
+/// Describes the payload following it, so that a snapshot written in another format
+/// or by another version of the server, or one which is truncated or corrupt, is not restored.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format_version: u32,
+    // the JSON of objects may change from one version of the server to the next.
+    server_version: String,
+    payload_length: u64,
+    payload_sha256: [u8; 32],
+}
+
+/// An object or error kept as JSON, along with whether it is served as serialized.
+#[derive(Serialize, Deserialize)]
+struct SnapshotObject {
+    #[serde(with = "serde_bytes")]
+    json: Vec<u8>,
+    serialized: bool,
+}
+
+/// The data of the store. Each object is kept once and referred to by its place in
+/// `objects`, as autnums and networks are stored under many ranges and prefixes, and
+/// IDNs under both of their names. The search indexes are kept as they are.
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    objects: Vec<SnapshotObject>,
+    autnums: Vec<(u32, u32, usize)>,
+    ip4: Vec<(Ipv4Net, usize)>,
+    ip6: Vec<(Ipv6Net, usize)>,
+    domains: Vec<(Arc<str>, usize)>,
+    idns: Vec<(Arc<str>, usize)>,
+    nameservers: Vec<(Arc<str>, usize)>,
+    entities: Vec<(Arc<str>, usize)>,
+    srvhelps: Vec<(Arc<str>, usize)>,
+    domain_names: NameIndex,
+    domain_ns_names: NameIndex,
+    domain_related: RelatedEntityIndex,
+    domain_ns_ips: KeyIndex<IpAddr>,
+    nameserver_names: NameIndex,
+    nameserver_ips: KeyIndex<IpAddr>,
+    nameserver_related: RelatedEntityIndex,
+    entity_names: NameIndex,
+    entity_related: RelatedEntityIndex,
+    entity_handles: NameIndex,
+}
+
+/// Gives each stored object its place in the objects of a payload.
+#[derive(Default)]
+struct Objects {
+    ids: HashMap<*const Stored, usize>,
+    objects: Vec<SnapshotObject>,
+}
+
+impl Objects {
+    fn id(&mut self, stored: &Arc<Stored>) -> Result<usize, RdapServerError> {
+        if let Some(id) = self.ids.get(&Arc::as_ptr(stored)) {
+            return Ok(*id);
+        }
+        let object = match &stored.serialized {
+            Some(serialized) => SnapshotObject {
+                json: serialized.json.to_vec(),
+                serialized: true,
+            },
+            None => SnapshotObject {
+                json: serde_json::to_vec(&stored.response)?,
+                serialized: false,
+            },
+        };
+        let id = self.objects.len();
+        self.objects.push(object);
+        self.ids.insert(Arc::as_ptr(stored), id);
+        Ok(id)
+    }
+
+    fn ids(
+        &mut self,
+        map: &im::HashMap<Arc<str>, Arc<Stored>>,
+    ) -> Result<Vec<(Arc<str>, usize)>, RdapServerError> {
+        map.iter()
+            .map(|(key, stored)| Ok((Arc::clone(key), self.id(stored)?)))
+            .collect()
+    }
+}
+
+impl Payload {
+    fn new(data: &MemData) -> Result<Self, RdapServerError> {
+        let mut objects = Objects::default();
+        let autnums = data
+            .autnums
+            .iter()
+            .map(|(range, stored)| Ok((*range.start(), *range.end(), objects.id(stored)?)))
+            .collect::<Result<_, RdapServerError>>()?;
+        let ip4 = data
+            .ip4
+            .iter()
+            .map(|(net, stored)| Ok((*net, objects.id(stored)?)))
+            .collect::<Result<_, RdapServerError>>()?;
+        let ip6 = data
+            .ip6
+            .iter()
+            .map(|(net, stored)| Ok((*net, objects.id(stored)?)))
+            .collect::<Result<_, RdapServerError>>()?;
+        let domains = objects.ids(&data.domains)?;
+        let idns = objects.ids(&data.idns)?;
+        let nameservers = objects.ids(&data.nameservers)?;
+        let entities = objects.ids(&data.entities)?;
+        let srvhelps = objects.ids(&data.srvhelps)?;
+        // the indexes are persistent, so cloning them does not copy them.
+        Ok(Self {
+            objects: objects.objects,
+            autnums,
+            ip4,
+            ip6,
+            domains,
+            idns,
+            nameservers,
+            entities,
+            srvhelps,
+            domain_names: data.domain_names.clone(),
+            domain_ns_names: data.domain_ns_names.clone(),
+            domain_related: data.domain_related.clone(),
+            domain_ns_ips: data.domain_ns_ips.clone(),
+            nameserver_names: data.nameserver_names.clone(),
+            nameserver_ips: data.nameserver_ips.clone(),
+            nameserver_related: data.nameserver_related.clone(),
+            entity_names: data.entity_names.clone(),
+            entity_related: data.entity_related.clone(),
+            entity_handles: data.entity_handles.clone(),
+        })
+    }
+
+    /// Makes the data of the store, parsing the objects in parallel.
+    async fn into_data(self) -> Result<MemData, RdapServerError> {
+        let mut parsing = Vec::new();
+        let mut objects = self.objects.into_iter();
+        loop {
+            let chunk = objects
+                .by_ref()
+                .take(PARSE_CHUNK_OBJECTS)
+                .collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
+            }
+            parsing.push(spawn_blocking(move || parse_objects(chunk)));
+        }
+        let mut stored = Vec::new();
+        for parsed in parsing {
+            stored.extend(parsed.await.expect("parsing snapshot objects")?);
+        }
+        let object = |id: usize| {
+            stored.get(id).cloned().ok_or_else(|| {
+                RdapServerError::Snapshot(format!("object {id} is not in the snapshot"))
+            })
+        };
+        let map = |ids: Vec<(Arc<str>, usize)>| {
+            ids.into_iter()
+                .map(|(key, id)| Ok((key, object(id)?)))
+                .collect::<Result<im::HashMap<_, _>, RdapServerError>>()
+        };
+
+        let mut autnums = RangeTable::new();
+        for (start, end, id) in self.autnums {
+            autnums.insert(start..=end, object(id)?);
+        }
+        let mut ip4 = PrefixTable::new();
+        for (net, id) in self.ip4 {
+            ip4.insert(net, object(id)?);
+        }
+        let mut ip6 = PrefixTable::new();
+        for (net, id) in self.ip6 {
+            ip6.insert(net, object(id)?);
+        }
+        Ok(MemData {
+            autnums,
+            ip4,
+            ip6,
+            domains: map(self.domains)?,
+            idns: map(self.idns)?,
+            domain_names: self.domain_names,
+            domain_ns_names: self.domain_ns_names,
+            domain_related: self.domain_related,
+            domain_ns_ips: self.domain_ns_ips,
+            nameservers: map(self.nameservers)?,
+            nameserver_names: self.nameserver_names,
+            nameserver_ips: self.nameserver_ips,
+            nameserver_related: self.nameserver_related,
+            entities: map(self.entities)?,
+            entity_names: self.entity_names,
+            entity_related: self.entity_related,
+            entity_handles: self.entity_handles,
+            srvhelps: map(self.srvhelps)?,
+        })
+    }
+}
+
+fn parse_objects(objects: Vec<SnapshotObject>) -> Result<Vec<Arc<Stored>>, RdapServerError> {
+    objects
+        .into_iter()
+        .map(|object| {
+            let response = serde_json::from_slice::<RdapResponse>(&object.json)?;
+            // the JSON of serialized objects is served as it is, so it is not serialized again.
+            let serialized = object
+                .serialized
+                .then(|| SerializedObject::from_json(&response, Bytes::from(object.json)));
+            Ok(Arc::new(Stored {
+                response,
+                serialized,
+            }))
+        })
+        .collect()
+}
+
+fn bincode_error(e: bincode::Error) -> RdapServerError {
+    RdapServerError::Snapshot(e.to_string())
+}
+
+/// Writes a snapshot of the data to the file. The snapshot is written to a temporary file
+/// which then replaces the file, so that the file always holds a whole snapshot.
+pub(crate) fn write_snapshot(path: &Path, data: &MemData) -> Result<(), RdapServerError> {
+    let payload = bincode::serialize(&Payload::new(data)?).map_err(bincode_error)?;
+    let header = Header {
+        format_version: FORMAT_VERSION,
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        payload_length: payload.len() as u64,
+        payload_sha256: Sha256::digest(&payload).into(),
+    };
+    let mut temp_path = PathBuf::from(path).into_os_string();
+    temp_path.push(".tmp");
+    let mut file = BufWriter::new(File::create(&temp_path)?);
+    file.write_all(MAGIC)?;
+    bincode::serialize_into(&mut file, &header).map_err(bincode_error)?;
+    file.write_all(&payload)?;
+    file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Reads the payload of a snapshot, checking that it was written in this format by this
+/// version of the server and that it is whole.
+fn read_payload(snapshot: &[u8]) -> Result<Payload, RdapServerError> {
+    let snapshot = snapshot
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| RdapServerError::Snapshot("not a snapshot file".to_string()))?;
+    let header = bincode::deserialize::<Header>(snapshot).map_err(bincode_error)?;
+    if header.format_version != FORMAT_VERSION {
+        return Err(RdapServerError::Snapshot(format!(
+            "snapshot format version is {} rather than {FORMAT_VERSION}",
+            header.format_version
+        )));
+    }
+    if header.server_version != env!("CARGO_PKG_VERSION") {
+        return Err(RdapServerError::Snapshot(format!(
+            "snapshot written by server version {}",
+            header.server_version
+        )));
+    }
+    let header_length = bincode::serialized_size(&header).map_err(bincode_error)? as usize;
+    let payload = &snapshot[header_length..];
+    if payload.len() as u64 != header.payload_length {
+        return Err(RdapServerError::Snapshot(format!(
+            "payload is {} bytes rather than {}",
+            payload.len(),
+            header.payload_length
+        )));
+    }
+    if Sha256::digest(payload).as_slice() != header.payload_sha256 {
+        return Err(RdapServerError::Snapshot(
+            "payload does not match its checksum".to_string(),
+        ));
+    }
+    bincode::deserialize(payload).map_err(bincode_error)
+}
+
+/// Gets the time of the latest change to the data directory or to the files in it.
+fn data_dir_modified(data_dir: &Path) -> std::io::Result<SystemTime> {
+    let mut modified = std::fs::metadata(data_dir)?.modified()?;
+    for entry in std::fs::read_dir(data_dir)? {
+        modified = modified.max(entry?.metadata()?.modified()?);
+    }
+    Ok(modified)
+}
+
+/// Restores the data from the snapshot file if it is newer than the contents of the data
+/// directory. Gives `None` if there is no snapshot or it is older than the data directory.
+pub(crate) async fn restore_snapshot(
+    path: &Path,
+    data_dir: &Path,
+) -> Result<Option<MemData>, RdapServerError> {
+    let Ok(metadata) = tokio::fs::metadata(path).await else {
+        debug!("no snapshot at {}", path.display());
+        return Ok(None);
+    };
+    let data_dir_modified = {
+        let data_dir = data_dir.to_path_buf();
+        spawn_blocking(move || data_dir_modified(&data_dir))
+            .await
+            .expect("getting data directory modification time")?
+    };
+    if metadata.modified()? <= data_dir_modified {
+        info!(
+            "Snapshot {} is older than the data directory and is not restored.",
+            path.display()
+        );
+        return Ok(None);
+    }
+    let snapshot = tokio::fs::read(path).await?;
+    let payload = spawn_blocking(move || read_payload(&snapshot))
+        .await
+        .expect("reading snapshot")?;
+    Ok(Some(payload.into_data().await?))
+}
+
+/// Starts writing snapshots of the data sent to the returned sender. Data sent while a
+/// snapshot is being written replaces any data still waiting to be written, so only
+/// the latest data is written.
+pub(crate) fn spawn_writer(path: &str) -> watch::Sender<Arc<MemData>> {
+    let (sender, mut receiver) = watch::channel(Arc::new(MemData::default()));
+    let path = PathBuf::from(path);
+    tokio::spawn(async move {
+        while receiver.changed().await.is_ok() {
+            let data = Arc::clone(&receiver.borrow_and_update());
+            let path = path.clone();
+            let written = spawn_blocking(move || write_snapshot(&path, &data))
+                .await
+                .expect("writing snapshot");
+            match written {
+                Ok(()) => debug!("snapshot written"),
+                Err(e) => warn!("Snapshot not written: {e}"),
+            }
+        }
+    });
+    sender
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use super::{read_payload, MAGIC};
+
+    #[test]
+    fn GIVEN_not_snapshot_WHEN_read_payload_THEN_error() {
+        // GIVEN
+        let snapshot = b"{\"objectClassName\":\"domain\"}";
+
+        // WHEN
+        let actual = read_payload(snapshot);
+
+        // THEN
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn GIVEN_truncated_header_WHEN_read_payload_THEN_error() {
+        // GIVEN
+        let mut snapshot = MAGIC.to_vec();
+        snapshot.extend_from_slice(&[1, 0]);
+
+        // WHEN
+        let actual = read_payload(&snapshot);
+
+        // THEN
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,370 @@
+use std::ops::RangeInclusive;
+
+use im::OrdMap;
+use ipnet::{Ipv4Net, Ipv6Net};
+
+/// An IP prefix which can be kept in a [PrefixTable].
+pub trait Prefix: Ord + Copy {
+    /// Gets the prefix of the given length containing this prefix, with its host bits cleared.
+    fn with_len(&self, len: u8) -> Self;
+
+    fn prefix_length(&self) -> u8;
+
+    /// Gets the prefix with its host bits cleared, as it is kept in a table.
+    fn key(&self) -> Self {
+        self.with_len(self.prefix_length())
+    }
+}
+
+macro_rules! impl_prefix {
+    ($net:ty) => {
+        impl Prefix for $net {
+            fn with_len(&self, len: u8) -> Self {
+                <$net>::new(self.addr(), len)
+                    .expect("prefix length no longer than the prefix")
+                    .trunc()
+            }
+
+            fn prefix_length(&self) -> u8 {
+                self.prefix_len()
+            }
+        }
+    };
+}
+
+impl_prefix!(Ipv4Net);
+impl_prefix!(Ipv6Net);
+
+/// A persistent map of IP prefixes supporting longest prefix matches. Cloning the table is
+/// cheap, and the clone shares its structure with the original until either is changed.
+///
+/// A longest prefix match is a lookup of the prefix and then of each shorter prefix
+/// containing it in turn, skipping the lengths of which no prefixes are kept.
+#[derive(Debug, Clone)]
+pub struct PrefixTable<P: Prefix, V: Clone> {
+    map: OrdMap<P, V>,
+    // the number of prefixes kept of each length.
+    lengths: OrdMap<u8, usize>,
+}
+
+impl<P: Prefix, V: Clone> Default for PrefixTable<P, V> {
+    fn default() -> Self {
+        Self {
+            map: OrdMap::new(),
+            lengths: OrdMap::new(),
+        }
+    }
+}
+
+impl<P: Prefix, V: Clone> PrefixTable<P, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, prefix: P, value: V) -> Option<V> {
+        let previous = self.map.insert(prefix.key(), value);
+        if previous.is_none() {
+            *self.lengths.entry(prefix.prefix_length()).or_default() += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, prefix: &P) -> Option<V> {
+        let removed = self.map.remove(&prefix.key());
+        if removed.is_some() {
+            let len = prefix.prefix_length();
+            match self.lengths.get(&len) {
+                Some(1) => {
+                    self.lengths.remove(&len);
+                }
+                Some(count) => {
+                    self.lengths.insert(len, count - 1);
+                }
+                None => {}
+            }
+        }
+        removed
+    }
+
+    /// Gets the longest prefix containing the prefix, which may be the prefix itself.
+    pub fn get_lpm(&self, prefix: &P) -> Option<(P, &V)> {
+        self.lengths
+            .range(..=prefix.prefix_length())
+            .rev()
+            .find_map(|(len, _)| {
+                let key = prefix.with_len(*len);
+                self.map.get(&key).map(|value| (key, value))
+            })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&P, &V)> {
+        self.map.iter()
+    }
+}
+
+/// A persistent map of inclusive ranges of numbers, which may overlap. Cloning the table is
+/// cheap, and the clone shares its structure with the original until either is changed.
+///
+/// A number is found in the smallest range containing it, or of ranges of the same size,
+/// in the range starting first. The ranges are kept as added, and the numbers they cover
+/// are kept as non-overlapping fragments, each under its start with its end and the range
+/// in which its numbers are found.
+#[derive(Debug, Clone)]
+pub struct RangeTable<V: Clone> {
+    ranges: OrdMap<(u32, u32), V>,
+    fragments: OrdMap<u32, (u32, (u32, u32))>,
+}
+
+impl<V: Clone> Default for RangeTable<V> {
+    fn default() -> Self {
+        Self {
+            ranges: OrdMap::new(),
+            fragments: OrdMap::new(),
+        }
+    }
+}
+
+/// Determines if numbers in both ranges are found in the first rather than the second.
+fn precedes(range: (u32, u32), other: (u32, u32)) -> bool {
+    (range.1 - range.0, range.0) < (other.1 - other.0, other.0)
+}
+
+impl<V: Clone> RangeTable<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the range, replacing the value of the same range if it was already added.
+    pub fn insert(&mut self, range: RangeInclusive<u32>, value: V) {
+        let key = (*range.start(), *range.end());
+        if key.0 > key.1 {
+            return;
+        }
+        if self.ranges.insert(key, value).is_none() {
+            self.cover(key, key);
+        }
+    }
+
+    /// Removes the range, so that its numbers are found in the next smallest ranges
+    /// containing them. Only the range itself is removed, not the ranges it overlaps.
+    pub fn remove(&mut self, range: RangeInclusive<u32>) {
+        let key = (*range.start(), *range.end());
+        if self.ranges.remove(&key).is_none() {
+            return;
+        }
+        self.clear(key);
+        // the ranges overlapping the removed range cover its numbers again. Those starting
+        // before it are found by a scan, which is fine as ranges are seldom removed.
+        let overlapping = self
+            .ranges
+            .keys()
+            .take_while(|(start, _)| *start <= key.1)
+            .filter(|(_, end)| *end >= key.0)
+            .copied()
+            .collect::<Vec<_>>();
+        for other in overlapping {
+            self.cover((other.0.max(key.0), other.1.min(key.1)), other);
+        }
+    }
+
+    /// Gets the fragments overlapping the span, as their start, end and range.
+    fn overlapping(&self, span: (u32, u32)) -> Vec<(u32, u32, (u32, u32))> {
+        let before = self
+            .fragments
+            .get_prev(&span.0)
+            .map(|(start, (end, range))| (*start, *end, *range))
+            .filter(|(start, end, _)| *start < span.0 && *end >= span.0);
+        before
+            .into_iter()
+            .chain(
+                self.fragments
+                    .range(span.0..=span.1)
+                    .map(|(start, (end, range))| (*start, *end, *range)),
+            )
+            .collect()
+    }
+
+    /// Removes the fragments of the span, keeping the parts of them outside it.
+    fn clear(&mut self, span: (u32, u32)) {
+        for (start, end, range) in self.overlapping(span) {
+            self.fragments.remove(&start);
+            if start < span.0 {
+                self.fragments.insert(start, (span.0 - 1, range));
+            }
+            if end > span.1 {
+                self.fragments.insert(span.1 + 1, (end, range));
+            }
+        }
+    }
+
+    /// Finds the numbers of the span in the range, where they are not found in a
+    /// range preceding it.
+    fn cover(&mut self, span: (u32, u32), range: (u32, u32)) {
+        let mut pieces = vec![];
+        let mut next = Some(span.0);
+        for (start, end, other) in self.overlapping(span) {
+            let (overlap_start, overlap_end) = (start.max(span.0), end.min(span.1));
+            if let Some(gap_start) = next.filter(|gap_start| *gap_start < overlap_start) {
+                pieces.push((gap_start, overlap_start - 1));
+            }
+            if precedes(range, other) {
+                self.fragments.remove(&start);
+                if start < overlap_start {
+                    self.fragments.insert(start, (overlap_start - 1, other));
+                }
+                if end > overlap_end {
+                    self.fragments.insert(overlap_end + 1, (end, other));
+                }
+                pieces.push((overlap_start, overlap_end));
+            }
+            next = overlap_end.checked_add(1);
+        }
+        if let Some(gap_start) = next.filter(|gap_start| *gap_start <= span.1) {
+            pieces.push((gap_start, span.1));
+        }
+        for (start, end) in pieces {
+            self.fragments.insert(start, (end, range));
+        }
+    }
+
+    /// Gets the value of the smallest range containing the number.
+    pub fn get(&self, num: u32) -> Option<&V> {
+        self.fragments
+            .get_prev(&num)
+            .filter(|(_, (end, _))| *end >= num)
+            .and_then(|(_, (_, range))| self.ranges.get(range))
+    }
+
+    /// Iterates over the ranges as they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (RangeInclusive<u32>, &V)> {
+        self.ranges
+            .iter()
+            .map(|((start, end), value)| (*start..=*end, value))
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use ipnet::Ipv4Net;
+    use rstest::rstest;
+
+    use super::{PrefixTable, RangeTable};
+
+    fn net(cidr: &str) -> Ipv4Net {
+        cidr.parse().expect("parsing cidr")
+    }
+
+    fn prefixes() -> PrefixTable<Ipv4Net, &'static str> {
+        let mut table = PrefixTable::new();
+        table.insert(net("10.0.0.0/8"), "10/8");
+        table.insert(net("10.1.0.0/16"), "10.1/16");
+        table.insert(net("10.1.2.0/24"), "10.1.2/24");
+        table.insert(net("11.0.0.0/8"), "11/8");
+        table
+    }
+
+    #[rstest]
+    #[case("10.1.2.3/32", Some("10.1.2/24"))]
+    #[case("10.1.3.3/32", Some("10.1/16"))]
+    #[case("10.2.0.0/16", Some("10/8"))]
+    #[case("10.1.0.0/16", Some("10.1/16"))]
+    #[case("10.0.0.0/7", None)]
+    #[case("12.0.0.1/32", None)]
+    fn GIVEN_prefixes_WHEN_get_lpm_THEN_longest_prefix(
+        #[case] prefix: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        // GIVEN
+        let table = prefixes();
+
+        // WHEN
+        let actual = table.get_lpm(&net(prefix)).map(|(_, value)| *value);
+
+        // THEN
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn GIVEN_cloned_prefixes_WHEN_clone_changed_THEN_original_unchanged() {
+        // GIVEN
+        let table = prefixes();
+        let mut clone = table.clone();
+
+        // WHEN
+        clone.remove(&net("10.1.2.0/24"));
+
+        // THEN
+        assert!(clone
+            .get_lpm(&net("10.1.2.3/32"))
+            .is_some_and(|(_, v)| *v == "10.1/16"));
+        assert!(table
+            .get_lpm(&net("10.1.2.3/32"))
+            .is_some_and(|(_, v)| *v == "10.1.2/24"));
+    }
+
+    #[rstest]
+    #[case(5, Some("inner"))]
+    #[case(4, Some("outer"))]
+    #[case(15, Some("inner"))]
+    #[case(16, Some("other"))]
+    #[case(25, Some("other"))]
+    #[case(0, None)]
+    #[case(31, None)]
+    fn GIVEN_overlapping_ranges_WHEN_get_THEN_smallest_range_found(
+        #[case] num: u32,
+        #[case] expected: Option<&str>,
+    ) {
+        // GIVEN
+        let mut table = RangeTable::new();
+        table.insert(5..=15, "inner");
+        table.insert(1..=20, "outer");
+        table.insert(15..=30, "other");
+
+        // WHEN
+        let actual = table.get(num).copied();
+
+        // THEN
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn GIVEN_ranges_of_same_size_WHEN_get_THEN_first_range_found() {
+        // GIVEN
+        let mut table = RangeTable::new();
+        table.insert(10..=20, "second");
+        table.insert(5..=15, "first");
+
+        // WHEN
+        let actual = table.get(12).copied();
+
+        // THEN
+        assert_eq!(actual, Some("first"));
+    }
+
+    #[test]
+    fn GIVEN_overlapping_ranges_WHEN_remove_THEN_next_smallest_range_found() {
+        // GIVEN
+        let mut table = RangeTable::new();
+        table.insert(1..=20, "outer");
+        table.insert(5..=9, "inner");
+        table.insert(u32::MAX - 1..=u32::MAX, "top");
+
+        // WHEN
+        table.remove(5..=9);
+        table.remove(u32::MAX - 1..=u32::MAX);
+        table.remove(2..=3);
+
+        // THEN
+        assert_eq!(table.get(5), Some(&"outer"));
+        assert_eq!(table.get(2), Some(&"outer"));
+        assert_eq!(table.get(u32::MAX), None);
+        assert_eq!(
+            table
+                .iter()
+                .map(|(range, value)| (range, *value))
+                .collect::<Vec<_>>(),
+            vec![(1..=20, "outer")]
+        );
+    }
+}
@@ -1,64 +1,184 @@
-use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::Arc};
+use std::{net::IpAddr, str::FromStr, sync::Arc};
 
 use async_trait::async_trait;
-use btree_range_map::RangeMap;
 use icann_rdap_common::response::{
     autnum::Autnum, domain::Domain, entity::Entity, help::Help, nameserver::Nameserver,
     network::Network, RdapResponse,
 };
-use ipnet::{IpSubnets, Ipv4Net, Ipv4Subnets, Ipv6Net, Ipv6Subnets};
-use prefix_trie::PrefixMap;
+use ipnet::{IpSubnets, Ipv4Subnets, Ipv6Subnets};
+use tokio::sync::OwnedMutexGuard;
 
 use crate::{
     error::RdapServerError,
     storage::{
-        data::{AutnumId, DomainId, EntityId, NameserverId, NetworkId},
+        data::{AutnumId, DomainId, EntityId, NameserverId, NetworkId, NetworkIdType},
+        search::{entity_full_name, nameserver_ips},
         TxHandle,
     },
 };
 
-use super::ops::Mem;
+use super::ops::{Mem, MemData, Stored};
 
 pub struct MemTx {
     mem: Mem,
-    autnums: RangeMap<u32, Arc<RdapResponse>>,
-    ip4: PrefixMap<Ipv4Net, Arc<RdapResponse>>,
-    ip6: PrefixMap<Ipv6Net, Arc<RdapResponse>>,
-    domains: HashMap<String, Arc<RdapResponse>>,
-    idns: HashMap<String, Arc<RdapResponse>>,
-    nameservers: HashMap<String, Arc<RdapResponse>>,
-    entities: HashMap<String, Arc<RdapResponse>>,
-    srvhelps: HashMap<String, Arc<RdapResponse>>,
+    data: MemData,
+    _writer: OwnedMutexGuard<()>,
 }
 
 impl MemTx {
+    /// Starts a transaction on the data as last committed. The data is shared with the
+    /// store until the transaction changes it, so starting one does not copy the objects
+    /// or indexes. Transactions are written one at a time, so this waits for the one being
+    /// written to end, as its changes would otherwise be lost when this one is committed.
     pub async fn new(mem: &Mem) -> Self {
+        let writer = mem.lock_writer().await;
         Self {
             mem: mem.clone(),
-            autnums: Arc::clone(&mem.autnums).read_owned().await.clone(),
-            ip4: Arc::clone(&mem.ip4).read_owned().await.clone(),
-            ip6: Arc::clone(&mem.ip6).read_owned().await.clone(),
-            domains: Arc::clone(&mem.domains).read_owned().await.clone(),
-            idns: Arc::clone(&mem.idns).read_owned().await.clone(),
-            nameservers: Arc::clone(&mem.nameservers).read_owned().await.clone(),
-            entities: Arc::clone(&mem.entities).read_owned().await.clone(),
-            srvhelps: Arc::clone(&mem.srvhelps).read_owned().await.clone(),
+            data: MemData::clone(&*mem.snapshot().await),
+            _writer: writer,
         }
     }
 
-    pub fn new_truncate(mem: &Mem) -> Self {
+    pub async fn new_truncate(mem: &Mem) -> Self {
         Self {
             mem: mem.clone(),
-            autnums: RangeMap::new(),
-            ip4: PrefixMap::new(),
-            ip6: PrefixMap::new(),
-            domains: HashMap::new(),
-            idns: HashMap::new(),
-            nameservers: HashMap::new(),
-            entities: HashMap::new(),
-            srvhelps: HashMap::new(),
+            data: MemData::default(),
+            _writer: mem.lock_writer().await,
         }
     }
+
+    /// Adds the names of the domain, the names and IP addresses of its nameservers,
+    /// and its related entities to the domain search indexes.
+    fn index_domain(&mut self, ldh_name: &str, domain: &Domain) {
+        self.data.domain_names.insert(ldh_name, ldh_name);
+        self.data
+            .domain_related
+            .insert(domain.object_common.entities.as_ref(), ldh_name);
+        if let Some(unicode_name) = domain.unicode_name.as_ref() {
+            self.data.domain_names.insert(unicode_name, ldh_name);
+        }
+        for nameserver in domain.nameservers.iter().flatten() {
+            if let Some(ns_ldh_name) = nameserver.ldh_name.as_ref() {
+                self.data.domain_ns_names.insert(ns_ldh_name, ldh_name);
+            }
+            for addr in nameserver_ips(nameserver) {
+                self.data.domain_ns_ips.insert(addr, ldh_name);
+            }
+        }
+    }
+
+    /// Removes the domain currently stored under the LDH name from the domain search indexes.
+    fn unindex_domain(&mut self, ldh_name: &str) {
+        if let Some(RdapResponse::Domain(domain)) = self
+            .data
+            .domains
+            .get(ldh_name)
+            .map(|stored| &stored.response)
+        {
+            if let Some(unicode_name) = domain.unicode_name.as_ref() {
+                self.data.domain_names.remove(unicode_name, ldh_name);
+            }
+            self.data
+                .domain_related
+                .remove(domain.object_common.entities.as_ref(), ldh_name);
+            for nameserver in domain.nameservers.iter().flatten() {
+                if let Some(ns_ldh_name) = nameserver.ldh_name.as_ref() {
+                    self.data.domain_ns_names.remove(ns_ldh_name, ldh_name);
+                }
+                for addr in nameserver_ips(nameserver) {
+                    self.data.domain_ns_ips.remove(addr, ldh_name);
+                }
+            }
+        }
+        self.data.domain_names.remove(ldh_name, ldh_name);
+    }
+
+    /// Adds the name, IP addresses and related entities of the nameserver to the nameserver
+    /// search indexes.
+    fn index_nameserver(&mut self, ldh_name: &str, nameserver: &Nameserver) {
+        self.data.nameserver_names.insert(ldh_name, ldh_name);
+        self.data
+            .nameserver_related
+            .insert(nameserver.object_common.entities.as_ref(), ldh_name);
+        for addr in nameserver_ips(nameserver) {
+            self.data.nameserver_ips.insert(addr, ldh_name);
+        }
+    }
+
+    /// Removes the nameserver currently stored under the LDH name from the nameserver
+    /// search indexes.
+    fn unindex_nameserver(&mut self, ldh_name: &str) {
+        if let Some(RdapResponse::Nameserver(nameserver)) = self
+            .data
+            .nameservers
+            .get(ldh_name)
+            .map(|stored| &stored.response)
+        {
+            self.data
+                .nameserver_related
+                .remove(nameserver.object_common.entities.as_ref(), ldh_name);
+            for addr in nameserver_ips(nameserver) {
+                self.data.nameserver_ips.remove(addr, ldh_name);
+            }
+        }
+        self.data.nameserver_names.remove(ldh_name, ldh_name);
+    }
+
+    /// Adds the handle, full name and related entities of the entity to the entity search indexes.
+    fn index_entity(&mut self, handle: &str, entity: &Entity) {
+        self.data.entity_handles.insert(handle, handle);
+        self.data
+            .entity_related
+            .insert(entity.object_common.entities.as_ref(), handle);
+        if let Some(full_name) = entity_full_name(entity) {
+            self.data.entity_names.insert(&full_name, handle);
+        }
+    }
+
+    /// Removes the entity currently stored under the handle from the entity search indexes.
+    fn unindex_entity(&mut self, handle: &str) {
+        if let Some(RdapResponse::Entity(entity)) = self
+            .data
+            .entities
+            .get(handle)
+            .map(|stored| &stored.response)
+        {
+            if let Some(full_name) = entity_full_name(entity) {
+                self.data.entity_names.remove(&full_name, handle);
+            }
+            self.data
+                .entity_related
+                .remove(entity.object_common.entities.as_ref(), handle);
+        }
+        self.data.entity_handles.remove(handle, handle);
+    }
+}
+
+/// Gets the CIDR blocks that exactly cover the addresses of a network ID.
+fn network_id_subnets(network_id: &NetworkId) -> Result<IpSubnets, RdapServerError> {
+    let subnets = match &network_id.network_id {
+        NetworkIdType::Cidr(cidr) => cidr.subnets(cidr.prefix_len())?,
+        NetworkIdType::Range {
+            start_address,
+            end_address,
+        } => match (
+            IpAddr::from_str(start_address)?,
+            IpAddr::from_str(end_address)?,
+        ) {
+            (IpAddr::V4(start_addr), IpAddr::V4(end_addr)) => {
+                IpSubnets::from(Ipv4Subnets::new(start_addr, end_addr, 0))
+            }
+            (IpAddr::V6(start_addr), IpAddr::V6(end_addr)) => {
+                IpSubnets::from(Ipv6Subnets::new(start_addr, end_addr, 0))
+            }
+            _ => {
+                return Err(RdapServerError::EmptyIndexData(
+                    "mismatch ip version".to_string(),
+                ))
+            }
+        },
+    };
+    Ok(subnets)
 }
 
 #[async_trait]
@@ -69,10 +189,12 @@ impl TxHandle for MemTx {
             .handle
             .as_ref()
             .ok_or_else(|| RdapServerError::EmptyIndexData("handle".to_string()))?;
-        self.entities.insert(
-            handle.to_owned(),
-            Arc::new(RdapResponse::Entity(entity.clone())),
+        self.unindex_entity(handle);
+        self.data.entities.insert(
+            Arc::from(handle.as_str()),
+            Stored::object(RdapResponse::Entity(entity.clone()))?,
         );
+        self.index_entity(handle, entity);
         Ok(())
     }
 
@@ -81,27 +203,33 @@ impl TxHandle for MemTx {
         entity_id: &EntityId,
         error: &icann_rdap_common::response::error::Error,
     ) -> Result<(), RdapServerError> {
-        self.entities.insert(
-            entity_id.handle.to_owned(),
-            Arc::new(RdapResponse::ErrorResponse(error.clone())),
+        self.unindex_entity(&entity_id.handle);
+        self.data.entities.insert(
+            Arc::from(entity_id.handle.as_str()),
+            Stored::unserialized(RdapResponse::ErrorResponse(error.clone())),
         );
         Ok(())
     }
 
     async fn add_domain(&mut self, domain: &Domain) -> Result<(), RdapServerError> {
-        let domain_response = Arc::new(RdapResponse::Domain(domain.clone()));
+        let domain_response = Stored::object(RdapResponse::Domain(domain.clone()))?;
 
         // add the domain as LDH, which is required.
         let ldh_name = domain
             .ldh_name
             .as_ref()
             .ok_or_else(|| RdapServerError::EmptyIndexData("ldhName".to_string()))?;
-        self.domains
-            .insert(ldh_name.to_owned(), domain_response.clone());
+        self.unindex_domain(ldh_name);
+        self.data
+            .domains
+            .insert(Arc::from(ldh_name.as_str()), domain_response.clone());
+        self.index_domain(ldh_name, domain);
 
         // add the domain by unicodeName
         if let Some(unicode_name) = domain.unicode_name.as_ref() {
-            self.idns.insert(unicode_name.to_owned(), domain_response);
+            self.data
+                .idns
+                .insert(Arc::from(unicode_name.as_str()), domain_response);
         };
 
         Ok(())
@@ -112,9 +240,10 @@ impl TxHandle for MemTx {
         domain_id: &DomainId,
         error: &icann_rdap_common::response::error::Error,
     ) -> Result<(), RdapServerError> {
-        self.domains.insert(
-            domain_id.ldh_name.to_owned(),
-            Arc::new(RdapResponse::ErrorResponse(error.clone())),
+        self.unindex_domain(&domain_id.ldh_name);
+        self.data.domains.insert(
+            Arc::from(domain_id.ldh_name.as_str()),
+            Stored::unserialized(RdapResponse::ErrorResponse(error.clone())),
         );
         Ok(())
     }
@@ -124,10 +253,12 @@ impl TxHandle for MemTx {
             .ldh_name
             .as_ref()
             .ok_or_else(|| RdapServerError::EmptyIndexData("ldhName".to_string()))?;
-        self.nameservers.insert(
-            ldh_name.to_owned(),
-            Arc::new(RdapResponse::Nameserver(nameserver.clone())),
+        self.unindex_nameserver(ldh_name);
+        self.data.nameservers.insert(
+            Arc::from(ldh_name.as_str()),
+            Stored::object(RdapResponse::Nameserver(nameserver.clone()))?,
         );
+        self.index_nameserver(ldh_name, nameserver);
         Ok(())
     }
 
@@ -136,9 +267,10 @@ impl TxHandle for MemTx {
         nameserver_id: &NameserverId,
         error: &icann_rdap_common::response::error::Error,
     ) -> Result<(), RdapServerError> {
-        self.nameservers.insert(
-            nameserver_id.ldh_name.to_owned(),
-            Arc::new(RdapResponse::ErrorResponse(error.clone())),
+        self.unindex_nameserver(&nameserver_id.ldh_name);
+        self.data.nameservers.insert(
+            Arc::from(nameserver_id.ldh_name.as_str()),
+            Stored::unserialized(RdapResponse::ErrorResponse(error.clone())),
         );
         Ok(())
     }
@@ -152,9 +284,9 @@ impl TxHandle for MemTx {
             .end_autnum
             .as_ref()
             .ok_or_else(|| RdapServerError::EmptyIndexData("endNum".to_string()))?;
-        self.autnums.insert(
+        self.data.autnums.insert(
             (*start_num)..=(*end_num),
-            Arc::new(RdapResponse::Autnum(autnum.clone())),
+            Stored::object(RdapResponse::Autnum(autnum.clone()))?,
         );
         Ok(())
     }
@@ -164,9 +296,9 @@ impl TxHandle for MemTx {
         autnum_id: &AutnumId,
         error: &icann_rdap_common::response::error::Error,
     ) -> Result<(), RdapServerError> {
-        self.autnums.insert(
+        self.data.autnums.insert(
             (autnum_id.start_autnum)..=(autnum_id.end_autnum),
-            Arc::new(RdapResponse::ErrorResponse(error.clone())),
+            Stored::unserialized(RdapResponse::ErrorResponse(error.clone())),
         );
         Ok(())
     }
@@ -184,18 +316,18 @@ impl TxHandle for MemTx {
             .ip_version
             .as_ref()
             .ok_or_else(|| RdapServerError::EmptyIndexData("ipVersion".to_string()))?;
+        // the network is stored once, under each of the CIDR blocks covering it.
+        let network_response = Stored::object(RdapResponse::Network(network.clone()))?;
         let is_v4 = ip_type.eq_ignore_ascii_case("v4");
         if is_v4 {
             let subnets = Ipv4Subnets::new(start_addr.parse()?, end_addr.parse()?, 0);
             for net in subnets {
-                self.ip4
-                    .insert(net, Arc::new(RdapResponse::Network(network.clone())));
+                self.data.ip4.insert(net, network_response.clone());
             }
         } else {
             let subnets = Ipv6Subnets::new(start_addr.parse()?, end_addr.parse()?, 0);
             for net in subnets {
-                self.ip6
-                    .insert(net, Arc::new(RdapResponse::Network(network.clone())));
+                self.data.ip6.insert(net, network_response.clone());
             }
         };
         Ok(())
@@ -206,48 +338,68 @@ impl TxHandle for MemTx {
         network_id: &NetworkId,
         error: &icann_rdap_common::response::error::Error,
     ) -> Result<(), RdapServerError> {
-        let subnets = match &network_id.network_id {
-            crate::storage::data::NetworkIdType::Cidr(cidr) => cidr.subnets(cidr.prefix_len())?,
-            crate::storage::data::NetworkIdType::Range {
-                start_address,
-                end_address,
-            } => {
-                let start_addr = IpAddr::from_str(start_address)?;
-                let end_addr = IpAddr::from_str(end_address)?;
-                if start_addr.is_ipv4() && end_addr.is_ipv4() {
-                    let IpAddr::V4(start_addr) = start_addr else {
-                        panic!("check failed")
-                    };
-                    let IpAddr::V4(end_addr) = end_addr else {
-                        panic!("check failed")
-                    };
-                    IpSubnets::from(Ipv4Subnets::new(start_addr, end_addr, 0))
-                } else if start_addr.is_ipv6() && end_addr.is_ipv6() {
-                    let IpAddr::V6(start_addr) = start_addr else {
-                        panic!("check failed")
-                    };
-                    let IpAddr::V6(end_addr) = end_addr else {
-                        panic!("check failed")
-                    };
-                    IpSubnets::from(Ipv6Subnets::new(start_addr, end_addr, 0))
-                } else {
-                    return Err(RdapServerError::EmptyIndexData(
-                        "mismatch ip version".to_string(),
-                    ));
+        let error_response = Stored::unserialized(RdapResponse::ErrorResponse(error.clone()));
+        match network_id_subnets(network_id)? {
+            IpSubnets::V4(subnets) => {
+                for net in subnets {
+                    self.data.ip4.insert(net, error_response.clone());
                 }
             }
-        };
-        match subnets {
+            IpSubnets::V6(subnets) => {
+                for net in subnets {
+                    self.data.ip6.insert(net, error_response.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_domain(&mut self, domain_id: &DomainId) -> Result<(), RdapServerError> {
+        self.unindex_domain(&domain_id.ldh_name);
+        if let Some(removed) = self.data.domains.remove(domain_id.ldh_name.as_str()) {
+            if let RdapResponse::Domain(domain) = &removed.response {
+                if let Some(unicode_name) = domain.unicode_name.as_ref() {
+                    self.data.idns.remove(unicode_name.as_str());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_entity(&mut self, entity_id: &EntityId) -> Result<(), RdapServerError> {
+        self.unindex_entity(&entity_id.handle);
+        self.data.entities.remove(entity_id.handle.as_str());
+        Ok(())
+    }
+
+    async fn delete_nameserver(
+        &mut self,
+        nameserver_id: &NameserverId,
+    ) -> Result<(), RdapServerError> {
+        self.unindex_nameserver(&nameserver_id.ldh_name);
+        self.data
+            .nameservers
+            .remove(nameserver_id.ldh_name.as_str());
+        Ok(())
+    }
+
+    async fn delete_autnum(&mut self, autnum_id: &AutnumId) -> Result<(), RdapServerError> {
+        self.data
+            .autnums
+            .remove((autnum_id.start_autnum)..=(autnum_id.end_autnum));
+        Ok(())
+    }
+
+    async fn delete_network(&mut self, network_id: &NetworkId) -> Result<(), RdapServerError> {
+        match network_id_subnets(network_id)? {
             IpSubnets::V4(subnets) => {
                 for net in subnets {
-                    self.ip4
-                        .insert(net, Arc::new(RdapResponse::ErrorResponse(error.clone())));
+                    self.data.ip4.remove(&net);
                 }
             }
             IpSubnets::V6(subnets) => {
                 for net in subnets {
-                    self.ip6
-                        .insert(net, Arc::new(RdapResponse::ErrorResponse(error.clone())));
+                    self.data.ip6.remove(&net);
                 }
             }
         }
@@ -260,44 +412,17 @@ impl TxHandle for MemTx {
         host: Option<&str>,
     ) -> Result<(), RdapServerError> {
         let host = host.unwrap_or("..default");
-        self.srvhelps
-            .insert(host.to_string(), Arc::new(RdapResponse::Help(help.clone())));
+        self.data.srvhelps.insert(
+            Arc::from(host),
+            Stored::unserialized(RdapResponse::Help(help.clone())),
+        );
         Ok(())
     }
 
-    async fn commit(mut self: Box<Self>) -> Result<(), RdapServerError> {
-        // autnums
-        let mut autnum_g = self.mem.autnums.write().await;
-        std::mem::swap(&mut self.autnums, &mut autnum_g);
-
-        // ip4
-        let mut ip4_g = self.mem.ip4.write().await;
-        std::mem::swap(&mut self.ip4, &mut ip4_g);
-
-        // ip6
-        let mut ip6_g = self.mem.ip6.write().await;
-        std::mem::swap(&mut self.ip6, &mut ip6_g);
-
-        // domains
-        let mut domains_g = self.mem.domains.write().await;
-        std::mem::swap(&mut self.domains, &mut domains_g);
-
-        //idns
-        let mut idns_g = self.mem.idns.write().await;
-        std::mem::swap(&mut self.idns, &mut idns_g);
-
-        // nameservers
-        let mut nameservers_g = self.mem.nameservers.write().await;
-        std::mem::swap(&mut self.nameservers, &mut nameservers_g);
-
-        // entities
-        let mut entities_g = self.mem.entities.write().await;
-        std::mem::swap(&mut self.entities, &mut entities_g);
-
-        //srvhelps
-        let mut srvhelps_g = self.mem.srvhelps.write().await;
-        std::mem::swap(&mut self.srvhelps, &mut srvhelps_g);
-
+    async fn commit(self: Box<Self>) -> Result<(), RdapServerError> {
+        // the objects and indexes are swapped together, so readers never see them
+        // part way through a commit.
+        self.mem.replace(self.data).await;
         Ok(())
     }
 
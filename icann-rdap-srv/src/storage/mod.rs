@@ -1,25 +1,84 @@
 use async_trait::async_trait;
+use axum::body::Bytes;
 use icann_rdap_common::response::{
     autnum::Autnum, domain::Domain, entity::Entity, help::Help, nameserver::Nameserver,
     network::Network, RdapResponse,
 };
+use serde::Serialize;
 
-use crate::error::RdapServerError;
+use crate::{error::RdapServerError, rdap::caching::CacheInfo};
 
-use self::data::{AutnumId, DomainId, EntityId, NameserverId, NetworkId};
+use self::{
+    data::{AutnumId, DomainId, EntityId, NameserverId, NetworkId},
+    search::{RelatedEntitySearch, ReverseSearchType},
+};
 
 pub mod data;
 pub mod mem;
 pub mod pg;
+pub mod search;
+pub(crate) mod sql;
+pub mod sqlite;
+pub mod watch;
 
 pub type DynStoreOps = dyn StoreOps + Send + Sync;
 
+/// The number of objects of each class in storage, including the errors (such as
+/// redirects) stored in place of objects.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ObjectCounts {
+    pub domains: usize,
+    pub entities: usize,
+    pub nameservers: usize,
+    pub autnums: usize,
+    pub networks: usize,
+}
+
+/// The lookups of single objects which a storage engine may answer with a
+/// [SerializedObject].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup<'a> {
+    DomainByLdh(&'a str),
+    EntityByHandle(&'a str),
+    NameserverByLdh(&'a str),
+    AutnumByNum(u32),
+    NetworkByIpAddr(&'a str),
+    NetworkByCidr(&'a str),
+}
+
+/// An object serialized to JSON when it was stored, with its cache headers, so that
+/// it can be served without being cloned or serialized again.
+#[derive(Debug, Clone)]
+pub struct SerializedObject {
+    pub json: Bytes,
+    pub cache_info: CacheInfo,
+}
+
+impl SerializedObject {
+    pub fn new(object: &RdapResponse) -> Result<Self, RdapServerError> {
+        let json = Bytes::from(serde_json::to_vec(object)?);
+        Ok(Self::from_json(object, json))
+    }
+
+    /// Keeps JSON previously serialized from the object.
+    pub fn from_json(object: &RdapResponse, json: Bytes) -> Self {
+        let cache_info = CacheInfo::new(object, &json);
+        Self { json, cache_info }
+    }
+}
+
 /// This trait defines the operations for a storage engine.
 #[async_trait]
 pub trait StoreOps: Send + Sync {
     /// Initializes the backend storage
     async fn init(&self) -> Result<(), RdapServerError>;
 
+    /// Tests that the storage can be reached, cheaply enough to be done on every readiness
+    /// check. Storage engines that cannot be unreachable need not test anything.
+    async fn ping(&self) -> Result<(), RdapServerError> {
+        Ok(())
+    }
+
     /// Gets a new transaction.
     async fn new_tx(&self) -> Result<Box<dyn TxHandle>, RdapServerError>;
 
@@ -32,13 +91,88 @@ pub trait StoreOps: Send + Sync {
     /// Get a domain from storage using the 'unicodeName' as the key.
     async fn get_domain_by_unicode(&self, unicode: &str) -> Result<RdapResponse, RdapServerError>;
 
+    /// Search for domains by matching the name against both the 'ldhName' and 'unicodeName'.
+    /// The name may use the RFC 9082 partial match wildcard (`*`) and is matched without regard
+    /// to case. No more than `max_results` domains are returned in the domain search results.
+    async fn search_domains_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
+    /// Search for domains by the 'ldhName' of their nameservers. The name may use the RFC 9082
+    /// partial match wildcard (`*`) and is matched without regard to case. No more than
+    /// `max_results` domains are returned in the domain search results.
+    async fn search_domains_by_ns_ldh_name(
+        &self,
+        ns_ldh_name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
+    /// Search for domains by an IP address of their nameservers. No more than `max_results`
+    /// domains are returned in the domain search results.
+    async fn search_domains_by_ns_ip(
+        &self,
+        ns_ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
     /// Get an entity from storage using the 'handle' of the entity as the key.
     async fn get_entity_by_handle(&self, handle: &str) -> Result<RdapResponse, RdapServerError>;
 
+    /// Search for entities by matching the name against the full name ('fn') of the entity's
+    /// contact. The name may use the RFC 9082 partial match wildcard (`*`) and is matched
+    /// without regard to case. No more than `max_results` entities are returned in the entity
+    /// search results.
+    async fn search_entities_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
+    /// Search for entities by matching the 'handle'. The handle may use the RFC 9082 partial match
+    /// wildcard (`*`) and is matched without regard to case. No more than `max_results` entities
+    /// are returned in the entity search results.
+    async fn search_entities_by_handle(
+        &self,
+        handle: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
     /// Get a nameserver from storage using the 'ldhName' as the key.
     async fn get_nameserver_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError>;
 
-    /// Get an autnum from storage using an autonomous system numbers as the key.
+    /// Search for nameservers by matching the name against the 'ldhName'. The name may use the
+    /// RFC 9082 partial match wildcard (`*`) and is matched without regard to case. No more than
+    /// `max_results` nameservers are returned in the nameserver search results.
+    async fn search_nameservers_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
+    /// Search for nameservers by IP address. The IP address may also be given in CIDR notation
+    /// (e.g. "192.0.2.0/24"), in which case nameservers with any IP address in the CIDR match.
+    /// No more than `max_results` nameservers are returned in the nameserver search results.
+    async fn search_nameservers_by_ip(
+        &self,
+        ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
+    /// RFC 9536 reverse search for domains, nameservers or entities by the properties of an
+    /// entity embedded in them. No more than `max_results` objects are returned in the
+    /// search results, which have the `reverse_search` RDAP conformance value.
+    async fn reverse_search_by_entity(
+        &self,
+        search_type: ReverseSearchType,
+        related: &RelatedEntitySearch,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError>;
+
+    /// Get an autnum from storage using an autonomous system numbers as the key. The autnum
+    /// returned should be the smallest range containing the number, or of ranges of the same
+    /// size, the one with the lowest start.
     async fn get_autnum_by_num(&self, num: u32) -> Result<RdapResponse, RdapServerError>;
 
     /// Get a network from storage using an IP address. The network returned should be the
@@ -52,6 +186,28 @@ pub trait StoreOps: Send + Sync {
 
     /// Get server help.
     async fn get_srv_help(&self, host: Option<&str>) -> Result<RdapResponse, RdapServerError>;
+
+    /// Get the number of objects of each class in storage.
+    async fn get_object_counts(&self) -> Result<ObjectCounts, RdapServerError>;
+
+    /// Restores the data from a snapshot written by the storage engine, if it keeps one and
+    /// it is newer than the contents of the data directory. Gives true if the data was
+    /// restored, in which case the data directory need not be loaded. Storage engines do
+    /// not keep snapshots by default.
+    async fn restore_snapshot(&self, _data_dir: &str) -> Result<bool, RdapServerError> {
+        Ok(false)
+    }
+
+    /// Get an object as it was serialized when stored. This is `None` when the object is
+    /// not found, when an error (such as a redirect) is stored in its place, or when the
+    /// storage engine does not keep serialized objects, as is the default. The object
+    /// must then be got with the lookup giving an [RdapResponse].
+    async fn get_serialized(
+        &self,
+        _lookup: Lookup<'_>,
+    ) -> Result<Option<SerializedObject>, RdapServerError> {
+        Ok(None)
+    }
 }
 
 /// Represents a handle to a transaction.
@@ -109,6 +265,24 @@ pub trait TxHandle: Send {
         error: &icann_rdap_common::response::error::Error,
     ) -> Result<(), RdapServerError>;
 
+    /// Delete a domain, or the error stored in its place, from storage.
+    async fn delete_domain(&mut self, domain_id: &DomainId) -> Result<(), RdapServerError>;
+
+    /// Delete an entity, or the error stored in its place, from storage.
+    async fn delete_entity(&mut self, entity_id: &EntityId) -> Result<(), RdapServerError>;
+
+    /// Delete a nameserver, or the error stored in its place, from storage.
+    async fn delete_nameserver(
+        &mut self,
+        nameserver_id: &NameserverId,
+    ) -> Result<(), RdapServerError>;
+
+    /// Delete the autnum, or the error, stored for the range of autonomous system numbers.
+    async fn delete_autnum(&mut self, autnum_id: &AutnumId) -> Result<(), RdapServerError>;
+
+    /// Delete the network, or the error, stored for the range of IP addresses.
+    async fn delete_network(&mut self, network_id: &NetworkId) -> Result<(), RdapServerError>;
+
     async fn add_srv_help(
         &mut self,
         help: &Help,
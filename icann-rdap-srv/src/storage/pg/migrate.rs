@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    query_as,
+};
+use strum_macros::Display;
+use tracing::info;
+
+use crate::error::RdapServerError;
+
+use super::ops::Pg;
+
+/// The versioned schema migrations, embedded from the `migrations` directory.
+/// The migrations applied to a database are recorded in its `_sqlx_migrations` table.
+pub(crate) static MIGRATOR: Migrator = sqlx::migrate!("src/storage/pg/migrations");
+
+/// The state of a schema migration in a database.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum MigrationState {
+    /// The migration has been applied.
+    Applied,
+
+    /// The migration has not been applied.
+    Pending,
+
+    /// The migration was attempted but did not complete.
+    Failed,
+
+    /// The migration has been applied, but it has since been changed.
+    Modified,
+}
+
+/// The status of a schema migration.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub installed_on: Option<DateTime<Utc>>,
+}
+
+impl Pg {
+    /// Applies any schema migrations that have not yet been applied to the database.
+    pub async fn migrate(&self) -> Result<(), RdapServerError> {
+        MIGRATOR.run(&self.pg_pool).await?;
+        info!("Database schema is up to date.");
+        Ok(())
+    }
+
+    /// Gets the status of each schema migration, ordered by version.
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>, RdapServerError> {
+        let mut conn = self.pg_pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        let applied: Vec<(i64, bool, Vec<u8>, DateTime<Utc>)> = query_as(
+            "select version, success, checksum, installed_on from _sqlx_migrations order by version",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        let applied = applied
+            .into_iter()
+            .map(|(version, success, checksum, installed_on)| {
+                (version, (success, checksum, installed_on))
+            })
+            .collect::<HashMap<_, _>>();
+        let statuses = MIGRATOR
+            .iter()
+            .map(|migration| {
+                let (state, installed_on) = match applied.get(&migration.version) {
+                    None => (MigrationState::Pending, None),
+                    Some((false, _, installed_on)) => (MigrationState::Failed, Some(*installed_on)),
+                    Some((true, checksum, installed_on)) => {
+                        if *checksum == *migration.checksum {
+                            (MigrationState::Applied, Some(*installed_on))
+                        } else {
+                            (MigrationState::Modified, Some(*installed_on))
+                        }
+                    }
+                };
+                MigrationStatus {
+                    version: migration.version,
+                    description: migration.description.to_string(),
+                    state,
+                    installed_on,
+                }
+            })
+            .collect();
+        Ok(statuses)
+    }
+}
@@ -0,0 +1,43 @@
+-- Creates the tables for the PostgreSQL storage backend.
+--
+-- Each RDAP object (or the RDAP error used in its place, such as a redirect)
+-- is stored whole as JSONB alongside the columns used to look it up.
+
+create table if not exists domain (
+    ldh_name text primary key,
+    unicode_name text,
+    response jsonb not null
+);
+
+create index if not exists domain_unicode_name_idx on domain (unicode_name);
+
+create table if not exists entity (
+    handle text primary key,
+    response jsonb not null
+);
+
+create table if not exists nameserver (
+    ldh_name text primary key,
+    response jsonb not null
+);
+
+create table if not exists autnum (
+    start_autnum bigint not null,
+    end_autnum bigint not null,
+    response jsonb not null,
+    primary key (start_autnum, end_autnum)
+);
+
+-- Networks are stored as one row per CIDR block covering the network's range,
+-- so that lookups are a longest prefix match over the blocks.
+create table if not exists network (
+    prefix cidr primary key,
+    response jsonb not null
+);
+
+create index if not exists network_prefix_idx on network using gist (prefix inet_ops);
+
+create table if not exists srv_help (
+    host text primary key,
+    response jsonb not null
+);
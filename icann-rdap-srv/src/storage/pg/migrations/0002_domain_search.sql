@@ -0,0 +1,5 @@
+-- Creates the indexes for partial match (trailing wildcard) domain searches.
+create index if not exists domain_ldh_name_search_idx
+    on domain (lower(ldh_name) text_pattern_ops);
+create index if not exists domain_unicode_name_search_idx
+    on domain (lower(unicode_name) text_pattern_ops);
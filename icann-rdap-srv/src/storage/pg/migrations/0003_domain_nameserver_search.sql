@@ -0,0 +1,20 @@
+-- Creates the tables indexing domains by the names and IP addresses of their
+-- nameservers, used for domain searches by nameserver. The rows for a domain
+-- are replaced whenever the domain is added.
+
+create table if not exists domain_ns_name (
+    ldh_name text not null references domain (ldh_name) on delete cascade,
+    ns_ldh_name text not null,
+    primary key (ldh_name, ns_ldh_name)
+);
+
+create index if not exists domain_ns_name_search_idx
+    on domain_ns_name (lower(ns_ldh_name) text_pattern_ops);
+
+create table if not exists domain_ns_ip (
+    ldh_name text not null references domain (ldh_name) on delete cascade,
+    ns_ip inet not null,
+    primary key (ldh_name, ns_ip)
+);
+
+create index if not exists domain_ns_ip_idx on domain_ns_ip (ns_ip);
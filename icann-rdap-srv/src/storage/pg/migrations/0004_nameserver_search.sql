@@ -0,0 +1,14 @@
+-- Creates the indexes for nameserver searches by name and the table indexing
+-- nameservers by their IP addresses. The rows for a nameserver are replaced
+-- whenever the nameserver is added.
+
+create index if not exists nameserver_ldh_name_search_idx
+    on nameserver (lower(ldh_name) text_pattern_ops);
+
+create table if not exists nameserver_ip (
+    ldh_name text not null references nameserver (ldh_name) on delete cascade,
+    ip inet not null,
+    primary key (ldh_name, ip)
+);
+
+create index if not exists nameserver_ip_idx on nameserver_ip using gist (ip inet_ops);
@@ -0,0 +1,9 @@
+-- Adds the full name of an entity's contact to the entity table and creates
+-- the indexes for entity searches by full name and handle.
+
+alter table entity add column if not exists full_name text;
+
+create index if not exists entity_full_name_search_idx
+    on entity (lower(full_name) text_pattern_ops);
+create index if not exists entity_handle_search_idx
+    on entity (lower(handle) text_pattern_ops);
@@ -0,0 +1,43 @@
+-- Creates the tables indexing domains, nameservers and entities by the entities
+-- embedded in them, used for RFC 9536 reverse searches. Each row is one embedded
+-- entity, so that all the properties searched for are matched against the same
+-- entity. The rows for an object are replaced whenever the object is added.
+
+create table if not exists domain_entity (
+    ldh_name text not null references domain (ldh_name) on delete cascade,
+    position integer not null,
+    entity_handle text,
+    full_name text,
+    emails text[] not null,
+    roles text[] not null,
+    primary key (ldh_name, position)
+);
+
+create index if not exists domain_entity_handle_search_idx
+    on domain_entity (lower(entity_handle) text_pattern_ops);
+
+create table if not exists nameserver_entity (
+    ldh_name text not null references nameserver (ldh_name) on delete cascade,
+    position integer not null,
+    entity_handle text,
+    full_name text,
+    emails text[] not null,
+    roles text[] not null,
+    primary key (ldh_name, position)
+);
+
+create index if not exists nameserver_entity_handle_search_idx
+    on nameserver_entity (lower(entity_handle) text_pattern_ops);
+
+create table if not exists entity_entity (
+    handle text not null references entity (handle) on delete cascade,
+    position integer not null,
+    entity_handle text,
+    full_name text,
+    emails text[] not null,
+    roles text[] not null,
+    primary key (handle, position)
+);
+
+create index if not exists entity_entity_handle_search_idx
+    on entity_entity (lower(entity_handle) text_pattern_ops);
@@ -1,19 +1,32 @@
-#![allow(clippy::diverging_sub_expression)]
+use std::{net::IpAddr, str::FromStr};
+
 use async_trait::async_trait;
-use icann_rdap_common::response::RdapResponse;
-use sqlx::{query, PgPool};
+use icann_rdap_common::response::{
+    domain::Domain, entity::Entity, nameserver::Nameserver, RdapResponse,
+};
+use ipnet::IpNet;
+use serde::de::DeserializeOwned;
+use sqlx::{query, query_as, query_scalar, types::Json, PgPool};
 use tracing::{debug, info};
 
 use crate::{
     error::RdapServerError,
-    storage::{StoreOps, TxHandle},
+    rdap::response::NOT_FOUND,
+    storage::{
+        search::{
+            domain_search_results, entity_search_results, nameserver_search_results,
+            reverse_search_results, RelatedEntitySearch, ReverseSearchType, SearchPattern,
+        },
+        sql::reverse_search_tables,
+        ObjectCounts, StoreOps, TxHandle,
+    },
 };
 
 use super::{config::PgConfig, tx::PgTx};
 
 #[derive(Clone)]
 pub struct Pg {
-    pg_pool: PgPool,
+    pub(crate) pg_pool: PgPool,
 }
 
 impl Pg {
@@ -23,6 +36,59 @@ impl Pg {
     }
 }
 
+/// Turns the response column of an optional row into an [RdapResponse],
+/// using a 404 if there was no row.
+fn found_or_not(result: Option<Json<RdapResponse>>) -> RdapResponse {
+    match result {
+        Some(Json(response)) => response,
+        None => NOT_FOUND.clone(),
+    }
+}
+
+impl Pg {
+    /// Finds the objects of the search type with an embedded entity matching the search.
+    /// Like other searches, errors such as redirects are not found.
+    async fn reverse_search<T>(
+        &self,
+        search_type: ReverseSearchType,
+        related: &RelatedEntitySearch,
+        max_results: usize,
+    ) -> Result<Vec<T>, RdapServerError>
+    where
+        T: DeserializeOwned + Send + Unpin + 'static,
+    {
+        if related.is_empty() {
+            return Ok(vec![]);
+        }
+        let (table, key_column, related_table) = reverse_search_tables(search_type);
+        let object_class_name = match search_type {
+            ReverseSearchType::Domains => "domain",
+            ReverseSearchType::Nameservers => "nameserver",
+            ReverseSearchType::Entities => "entity",
+        };
+        let results: Vec<Json<T>> = query_scalar(&format!(
+            "select response from {table} \
+             where {key_column} in \
+             (select {key_column} from {related_table} \
+             where ($1::text is null or lower(entity_handle) like $1) \
+             and ($2::text is null or lower(full_name) like $2) \
+             and ($3::text is null or exists (select from unnest(emails) email where email like $3)) \
+             and ($4::text is null or $4 = any(roles))) \
+             and response->>'objectClassName' = '{object_class_name}' \
+             order by {key_column} \
+             limit $5"
+        ))
+        .bind(related.handle.as_ref().map(SearchPattern::to_sql_like))
+        .bind(related.full_name.as_ref().map(SearchPattern::to_sql_like))
+        .bind(related.email.as_ref().map(SearchPattern::to_sql_like))
+        .bind(related.role.as_ref().map(|role| role.to_lowercase()))
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        Ok(results.into_iter().map(|Json(object)| object).collect())
+    }
+}
+
 #[async_trait]
 impl StoreOps for Pg {
     async fn init(&self) -> Result<(), RdapServerError> {
@@ -30,6 +96,11 @@ impl StoreOps for Pg {
         let mut conn = self.pg_pool.acquire().await?;
         query("select 1").fetch_one(&mut *conn).await?;
         info!("Database connection test is successful.");
+        self.migrate().await
+    }
+
+    async fn ping(&self) -> Result<(), RdapServerError> {
+        query("select 1").fetch_one(&self.pg_pool).await?;
         Ok(())
     }
 
@@ -41,33 +112,298 @@ impl StoreOps for Pg {
         Ok(Box::new(PgTx::new_truncate(&self.pg_pool).await?))
     }
 
-    async fn get_domain_by_ldh(&self, _ldh: &str) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+    async fn get_domain_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError> {
+        let result = query_scalar("select response from domain where ldh_name = $1")
+            .bind(ldh)
+            .fetch_optional(&self.pg_pool)
+            .await?;
+        Ok(found_or_not(result))
+    }
+
+    async fn get_domain_by_unicode(&self, unicode: &str) -> Result<RdapResponse, RdapServerError> {
+        let result = query_scalar("select response from domain where unicode_name = $1 limit 1")
+            .bind(unicode)
+            .fetch_optional(&self.pg_pool)
+            .await?;
+        Ok(found_or_not(result))
+    }
+
+    async fn search_domains_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(name).to_sql_like();
+        // only domains are searched, not errors such as redirects.
+        let results: Vec<Json<Domain>> = query_scalar(
+            "select response from domain \
+             where (lower(ldh_name) like $1 or lower(unicode_name) like $1) \
+             and response->>'objectClassName' = 'domain' \
+             order by ldh_name \
+             limit $2",
+        )
+        .bind(pattern)
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results.into_iter().map(|Json(domain)| domain).collect();
+        Ok(domain_search_results(results, max_results))
     }
 
-    async fn get_domain_by_unicode(&self, _unicode: &str) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+    async fn search_domains_by_ns_ldh_name(
+        &self,
+        ns_ldh_name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(ns_ldh_name).to_sql_like();
+        let results: Vec<Json<Domain>> = query_scalar(
+            "select response from domain \
+             where ldh_name in \
+             (select ldh_name from domain_ns_name where lower(ns_ldh_name) like $1) \
+             and response->>'objectClassName' = 'domain' \
+             order by ldh_name \
+             limit $2",
+        )
+        .bind(pattern)
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results.into_iter().map(|Json(domain)| domain).collect();
+        Ok(domain_search_results(results, max_results))
     }
 
-    async fn get_entity_by_handle(&self, _handle: &str) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+    async fn search_domains_by_ns_ip(
+        &self,
+        ns_ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let addr = ns_ip.parse::<IpAddr>()?;
+        let results: Vec<Json<Domain>> = query_scalar(
+            "select response from domain \
+             where ldh_name in \
+             (select ldh_name from domain_ns_ip where ns_ip = $1::inet) \
+             and response->>'objectClassName' = 'domain' \
+             order by ldh_name \
+             limit $2",
+        )
+        .bind(addr.to_string())
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results.into_iter().map(|Json(domain)| domain).collect();
+        Ok(domain_search_results(results, max_results))
     }
 
-    async fn get_nameserver_by_ldh(&self, _ldh: &str) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+    async fn get_entity_by_handle(&self, handle: &str) -> Result<RdapResponse, RdapServerError> {
+        let result = query_scalar("select response from entity where handle = $1")
+            .bind(handle)
+            .fetch_optional(&self.pg_pool)
+            .await?;
+        Ok(found_or_not(result))
     }
 
-    async fn get_autnum_by_num(&self, _num: u32) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+    async fn search_entities_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(name).to_sql_like();
+        // only entities are searched, not errors such as redirects.
+        let results: Vec<Json<Entity>> = query_scalar(
+            "select response from entity \
+             where lower(full_name) like $1 \
+             and response->>'objectClassName' = 'entity' \
+             order by handle \
+             limit $2",
+        )
+        .bind(pattern)
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results.into_iter().map(|Json(entity)| entity).collect();
+        Ok(entity_search_results(results, max_results))
     }
 
-    async fn get_network_by_ipaddr(&self, _ipaddr: &str) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+    async fn search_entities_by_handle(
+        &self,
+        handle: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(handle).to_sql_like();
+        let results: Vec<Json<Entity>> = query_scalar(
+            "select response from entity \
+             where lower(handle) like $1 \
+             and response->>'objectClassName' = 'entity' \
+             order by handle \
+             limit $2",
+        )
+        .bind(pattern)
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results.into_iter().map(|Json(entity)| entity).collect();
+        Ok(entity_search_results(results, max_results))
     }
-    async fn get_network_by_cidr(&self, _cidr: &str) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+
+    async fn get_nameserver_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError> {
+        let result = query_scalar("select response from nameserver where ldh_name = $1")
+            .bind(ldh)
+            .fetch_optional(&self.pg_pool)
+            .await?;
+        Ok(found_or_not(result))
     }
-    async fn get_srv_help(&self, _host: Option<&str>) -> Result<RdapResponse, RdapServerError> {
-        todo!()
+
+    async fn search_nameservers_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(name).to_sql_like();
+        // only nameservers are searched, not errors such as redirects.
+        let results: Vec<Json<Nameserver>> = query_scalar(
+            "select response from nameserver \
+             where lower(ldh_name) like $1 \
+             and response->>'objectClassName' = 'nameserver' \
+             order by ldh_name \
+             limit $2",
+        )
+        .bind(pattern)
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results
+            .into_iter()
+            .map(|Json(nameserver)| nameserver)
+            .collect();
+        Ok(nameserver_search_results(results, max_results))
+    }
+
+    async fn search_nameservers_by_ip(
+        &self,
+        ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let net = if ip.contains('/') {
+            IpNet::from_str(ip)?.trunc()
+        } else {
+            IpNet::from(ip.parse::<IpAddr>()?)
+        };
+        let results: Vec<Json<Nameserver>> = query_scalar(
+            "select response from nameserver \
+             where ldh_name in \
+             (select ldh_name from nameserver_ip where ip <<= $1::cidr) \
+             and response->>'objectClassName' = 'nameserver' \
+             order by ldh_name \
+             limit $2",
+        )
+        .bind(net.to_string())
+        .bind(i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX))
+        .fetch_all(&self.pg_pool)
+        .await?;
+        let results = results
+            .into_iter()
+            .map(|Json(nameserver)| nameserver)
+            .collect();
+        Ok(nameserver_search_results(results, max_results))
+    }
+
+    async fn reverse_search_by_entity(
+        &self,
+        search_type: ReverseSearchType,
+        related: &RelatedEntitySearch,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let response = match search_type {
+            ReverseSearchType::Domains => domain_search_results(
+                self.reverse_search(search_type, related, max_results)
+                    .await?,
+                max_results,
+            ),
+            ReverseSearchType::Nameservers => nameserver_search_results(
+                self.reverse_search(search_type, related, max_results)
+                    .await?,
+                max_results,
+            ),
+            ReverseSearchType::Entities => entity_search_results(
+                self.reverse_search(search_type, related, max_results)
+                    .await?,
+                max_results,
+            ),
+        };
+        Ok(reverse_search_results(response))
+    }
+
+    async fn get_autnum_by_num(&self, num: u32) -> Result<RdapResponse, RdapServerError> {
+        // the most specific (smallest) range containing the number wins, or of ranges
+        // of the same size, the one starting first.
+        let result = query_scalar(
+            "select response from autnum \
+             where start_autnum <= $1 and end_autnum >= $1 \
+             order by end_autnum - start_autnum, start_autnum \
+             limit 1",
+        )
+        .bind(i64::from(num))
+        .fetch_optional(&self.pg_pool)
+        .await?;
+        Ok(found_or_not(result))
+    }
+
+    async fn get_network_by_ipaddr(&self, ipaddr: &str) -> Result<RdapResponse, RdapServerError> {
+        let addr = ipaddr.parse::<IpAddr>()?;
+        let result = query_scalar(
+            "select response from network \
+             where prefix >>= $1::inet \
+             order by masklen(prefix) desc \
+             limit 1",
+        )
+        .bind(addr.to_string())
+        .fetch_optional(&self.pg_pool)
+        .await?;
+        Ok(found_or_not(result))
+    }
+
+    async fn get_network_by_cidr(&self, cidr: &str) -> Result<RdapResponse, RdapServerError> {
+        let net = IpNet::from_str(cidr)?.trunc();
+        let result = query_scalar(
+            "select response from network \
+             where prefix >>= $1::cidr \
+             order by masklen(prefix) desc \
+             limit 1",
+        )
+        .bind(net.to_string())
+        .fetch_optional(&self.pg_pool)
+        .await?;
+        Ok(found_or_not(result))
+    }
+
+    async fn get_srv_help(&self, host: Option<&str>) -> Result<RdapResponse, RdapServerError> {
+        let host = host.unwrap_or("..default");
+        let result = query_scalar("select response from srv_help where host = $1")
+            .bind(host)
+            .fetch_optional(&self.pg_pool)
+            .await?;
+        Ok(found_or_not(result))
+    }
+
+    async fn get_object_counts(&self) -> Result<ObjectCounts, RdapServerError> {
+        // Networks are stored as a row for each CIDR block, so they are counted by response.
+        let (domains, entities, nameservers, autnums, networks): (i64, i64, i64, i64, i64) =
+            query_as(
+                r#"select
+                    (select count(*) from domain),
+                    (select count(*) from entity),
+                    (select count(*) from nameserver),
+                    (select count(*) from autnum),
+                    (select count(distinct response) from network)"#,
+            )
+            .fetch_one(&self.pg_pool)
+            .await?;
+        Ok(ObjectCounts {
+            domains: domains as usize,
+            entities: entities as usize,
+            nameservers: nameservers as usize,
+            autnums: autnums as usize,
+            networks: networks as usize,
+        })
     }
 }
@@ -0,0 +1,341 @@
+use std::net::IpAddr;
+
+use icann_rdap_common::response::{
+    domain::Domain,
+    entity::Entity,
+    nameserver::Nameserver,
+    search::{DomainSearchResults, EntitySearchResults, NameserverSearchResults},
+    types::{Common, Extension, Notice, NoticeOrRemark},
+    RdapResponse,
+};
+use strum_macros::Display;
+
+/// The RDAP conformance value of RFC 9536 reverse searches.
+pub const REVERSE_SEARCH: &str = "reverse_search";
+
+/// A search string which may use the RFC 9082 partial match wildcard (`*`).
+///
+/// Searches are case-insensitive, so the pattern is kept in lower case and
+/// names must be lower cased before being matched against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPattern {
+    /// The pattern split on the wildcard. A pattern without a wildcard has one segment.
+    segments: Vec<String>,
+}
+
+impl SearchPattern {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            segments: pattern
+                .to_lowercase()
+                .split('*')
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// True if the pattern contains a wildcard.
+    pub fn is_partial(&self) -> bool {
+        self.segments.len() > 1
+    }
+
+    /// The part of the pattern before the first wildcard.
+    pub fn prefix(&self) -> &str {
+        self.segments.first().map_or("", String::as_str)
+    }
+
+    /// The part of the pattern after the last wildcard.
+    pub fn suffix(&self) -> &str {
+        if self.is_partial() {
+            self.segments.last().map_or("", String::as_str)
+        } else {
+            ""
+        }
+    }
+
+    /// Tests if a (lower cased) name matches the pattern.
+    pub fn is_match(&self, name: &str) -> bool {
+        let Some((first, rest)) = self.segments.split_first() else {
+            return name.is_empty();
+        };
+        let Some((last, middle)) = rest.split_last() else {
+            return name == first;
+        };
+        if name.len() < first.len() + last.len()
+            || !name.starts_with(first.as_str())
+            || !name.ends_with(last.as_str())
+        {
+            return false;
+        }
+        let mut remaining = &name[first.len()..name.len() - last.len()];
+        for segment in middle {
+            match remaining.find(segment.as_str()) {
+                Some(pos) => remaining = &remaining[pos + segment.len()..],
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Converts the pattern to an SQL `LIKE` pattern, escaping any `LIKE` wildcards
+    /// in the pattern with a backslash.
+    pub fn to_sql_like(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| {
+                segment
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            })
+            .collect::<Vec<String>>()
+            .join("%")
+    }
+}
+
+/// Gets the IP addresses of a nameserver used by searches, ignoring any that cannot be parsed.
+pub fn nameserver_ips(nameserver: &Nameserver) -> impl Iterator<Item = IpAddr> + '_ {
+    nameserver
+        .ip_addresses
+        .iter()
+        .flat_map(|ip_addresses| ip_addresses.v4.iter().chain(ip_addresses.v6.iter()))
+        .flatten()
+        .filter_map(|addr| addr.parse::<IpAddr>().ok())
+}
+
+/// Gets the full name of an entity used by searches, which is the `fn` of its contact.
+pub fn entity_full_name(entity: &Entity) -> Option<String> {
+    entity.contact().and_then(|contact| contact.full_name)
+}
+
+/// Gets the email addresses of an entity used by searches, which are those of its contact.
+pub fn entity_emails(entity: &Entity) -> Vec<String> {
+    entity
+        .contact()
+        .and_then(|contact| contact.emails)
+        .into_iter()
+        .flatten()
+        .map(|email| email.email)
+        .collect()
+}
+
+/// The types of objects found by an RFC 9536 reverse search.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum ReverseSearchType {
+    #[strum(serialize = "domains")]
+    Domains,
+    #[strum(serialize = "nameservers")]
+    Nameservers,
+    #[strum(serialize = "entities")]
+    Entities,
+}
+
+/// The properties of a related entity in an RFC 9536 reverse search.
+///
+/// An object is found by the search when one of the entities embedded in it matches
+/// all of the given properties. The handle, full name (`fn`) and email may use the
+/// RFC 9082 partial match wildcard (`*`). All properties are matched without regard to case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelatedEntitySearch {
+    pub handle: Option<SearchPattern>,
+    pub full_name: Option<SearchPattern>,
+    pub email: Option<SearchPattern>,
+    pub role: Option<String>,
+}
+
+impl RelatedEntitySearch {
+    /// True if no properties are given, in which case nothing is related.
+    pub fn is_empty(&self) -> bool {
+        self.handle.is_none()
+            && self.full_name.is_none()
+            && self.email.is_none()
+            && self.role.is_none()
+    }
+
+    /// Tests if an entity has all of the properties.
+    pub fn is_match(&self, entity: &Entity) -> bool {
+        !self.is_empty()
+            && self.handle.as_ref().is_none_or(|pattern| {
+                entity
+                    .object_common
+                    .handle
+                    .as_ref()
+                    .is_some_and(|handle| pattern.is_match(&handle.to_lowercase()))
+            })
+            && self.full_name.as_ref().is_none_or(|pattern| {
+                entity_full_name(entity).is_some_and(|name| pattern.is_match(&name.to_lowercase()))
+            })
+            && self.email.as_ref().is_none_or(|pattern| {
+                entity_emails(entity)
+                    .iter()
+                    .any(|email| pattern.is_match(&email.to_lowercase()))
+            })
+            && self.role.as_ref().is_none_or(|role| {
+                entity
+                    .roles
+                    .iter()
+                    .flatten()
+                    .any(|entity_role| entity_role.eq_ignore_ascii_case(role))
+            })
+    }
+
+    /// Tests if any of the entities embedded in an object have all of the properties.
+    pub fn is_related(&self, entities: Option<&Vec<Entity>>) -> bool {
+        entities
+            .into_iter()
+            .flatten()
+            .any(|entity| self.is_match(entity))
+    }
+}
+
+/// Adds the `reverse_search` RDAP conformance value to search results.
+pub fn reverse_search_results(mut response: RdapResponse) -> RdapResponse {
+    let common = match &mut response {
+        RdapResponse::DomainSearchResults(results) => &mut results.common,
+        RdapResponse::NameserverSearchResults(results) => &mut results.common,
+        RdapResponse::EntitySearchResults(results) => &mut results.common,
+        _ => return response,
+    };
+    common
+        .rdap_conformance
+        .get_or_insert_with(Vec::new)
+        .push(Extension(REVERSE_SEARCH.to_string()));
+    response
+}
+
+/// Notice added to search results that have been cut short by the maximum number of
+/// search results.
+pub fn truncated_notice(max_results: usize) -> Notice {
+    Notice(
+        NoticeOrRemark::builder()
+            .title("Search Results Truncated")
+            .description_entry(format!(
+                "Search results are limited to {max_results} results."
+            ))
+            .build(),
+    )
+}
+
+/// Truncates the results to `max_results`, giving the common part of the search
+/// results response with a notice if the results were truncated.
+fn truncate_results<T>(results: &mut Vec<T>, max_results: usize) -> Common {
+    let mut notices = vec![];
+    if results.len() > max_results {
+        results.truncate(max_results);
+        notices.push(truncated_notice(max_results));
+    }
+    Common::level0().notices(notices).build()
+}
+
+/// Creates the domain search results response. The domains given should be
+/// at most one more than `max_results`, which indicates the results are truncated.
+pub fn domain_search_results(mut domains: Vec<Domain>, max_results: usize) -> RdapResponse {
+    let common = truncate_results(&mut domains, max_results);
+    RdapResponse::DomainSearchResults(
+        DomainSearchResults::builder()
+            .common(common)
+            .results(domains)
+            .build(),
+    )
+}
+
+/// Creates the nameserver search results response. The nameservers given should be
+/// at most one more than `max_results`, which indicates the results are truncated.
+pub fn nameserver_search_results(
+    mut nameservers: Vec<Nameserver>,
+    max_results: usize,
+) -> RdapResponse {
+    let common = truncate_results(&mut nameservers, max_results);
+    RdapResponse::NameserverSearchResults(
+        NameserverSearchResults::builder()
+            .common(common)
+            .results(nameservers)
+            .build(),
+    )
+}
+
+/// Creates the entity search results response. The entities given should be
+/// at most one more than `max_results`, which indicates the results are truncated.
+pub fn entity_search_results(mut entities: Vec<Entity>, max_results: usize) -> RdapResponse {
+    let common = truncate_results(&mut entities, max_results);
+    RdapResponse::EntitySearchResults(
+        EntitySearchResults::builder()
+            .common(common)
+            .results(entities)
+            .build(),
+    )
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+
+    use rstest::rstest;
+
+    use super::SearchPattern;
+
+    #[rstest]
+    #[case("foo.example", "foo.example", true)]
+    #[case("foo.example", "bar.example", false)]
+    #[case("foo*", "foo.example", true)]
+    #[case("foo*", "bar.example", false)]
+    #[case("*.example", "foo.example", true)]
+    #[case("*.example", "foo.example.com", false)]
+    #[case("f*.example", "foo.example", true)]
+    #[case("f*.example", "bar.example", false)]
+    #[case("*oo*", "foo.example", true)]
+    #[case("*oo*", "bar.example", false)]
+    #[case("f*o*e", "foo.example", true)]
+    #[case("foo*foo", "foo", false)]
+    #[case("FOO*", "foo.example", true)]
+    #[case("*", "foo.example", true)]
+    fn GIVEN_pattern_WHEN_is_match_THEN_correct(
+        #[case] pattern: &str,
+        #[case] name: &str,
+        #[case] expected: bool,
+    ) {
+        // GIVEN
+        let pattern = SearchPattern::new(pattern);
+
+        // WHEN
+        let actual = pattern.is_match(name);
+
+        // THEN
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case("foo*", "foo", "")]
+    #[case("*.example", "", ".example")]
+    #[case("f*.ex*e", "f", "e")]
+    #[case("foo.example", "foo.example", "")]
+    fn GIVEN_pattern_WHEN_prefix_and_suffix_THEN_correct(
+        #[case] pattern: &str,
+        #[case] prefix: &str,
+        #[case] suffix: &str,
+    ) {
+        // GIVEN
+        let pattern = SearchPattern::new(pattern);
+
+        // WHEN
+        let actual = (pattern.prefix(), pattern.suffix());
+
+        // THEN
+        assert_eq!(actual, (prefix, suffix));
+    }
+
+    #[rstest]
+    #[case("foo*", "foo%")]
+    #[case("*_dmarc.example", "%\\_dmarc.example")]
+    #[case("100%*", "100\\%%")]
+    fn GIVEN_pattern_WHEN_to_sql_like_THEN_escaped(#[case] pattern: &str, #[case] expected: &str) {
+        // GIVEN
+        let pattern = SearchPattern::new(pattern);
+
+        // WHEN
+        let actual = pattern.to_sql_like();
+
+        // THEN
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,40 @@
+use std::net::IpAddr;
+
+use ipnet::{IpNet, Ipv4Subnets, Ipv6Subnets};
+
+use crate::error::RdapServerError;
+
+use super::search::ReverseSearchType;
+
+/// Gets the table of the objects found by a reverse search, the key column of that table
+/// and the table indexing the entities embedded in the objects.
+pub(crate) fn reverse_search_tables(
+    search_type: ReverseSearchType,
+) -> (&'static str, &'static str, &'static str) {
+    match search_type {
+        ReverseSearchType::Domains => ("domain", "ldh_name", "domain_entity"),
+        ReverseSearchType::Nameservers => ("nameserver", "ldh_name", "nameserver_entity"),
+        ReverseSearchType::Entities => ("entity", "handle", "entity_entity"),
+    }
+}
+
+/// Gets the CIDR blocks that exactly cover the range of addresses from start to end.
+pub(crate) fn range_to_prefixes(
+    start_address: &str,
+    end_address: &str,
+) -> Result<Vec<IpNet>, RdapServerError> {
+    let prefixes = match (start_address.parse()?, end_address.parse()?) {
+        (IpAddr::V4(start_addr), IpAddr::V4(end_addr)) => Ipv4Subnets::new(start_addr, end_addr, 0)
+            .map(IpNet::V4)
+            .collect(),
+        (IpAddr::V6(start_addr), IpAddr::V6(end_addr)) => Ipv6Subnets::new(start_addr, end_addr, 0)
+            .map(IpNet::V6)
+            .collect(),
+        _ => {
+            return Err(RdapServerError::EmptyIndexData(
+                "mismatch ip version".to_string(),
+            ))
+        }
+    };
+    Ok(prefixes)
+}
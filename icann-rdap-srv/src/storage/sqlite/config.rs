@@ -0,0 +1,6 @@
+use buildstructor::Builder;
+
+#[derive(Debug, Builder, Clone)]
+pub struct SqliteConfig {
+    pub db_url: String,
+}
@@ -0,0 +1,112 @@
+-- Creates the tables for the SQLite storage backend.
+--
+-- Each RDAP object (or the RDAP error used in its place, such as a redirect)
+-- is stored whole as JSON text alongside the columns used to look it up and
+-- search for it. Objects which are served as stored (not errors) also have the
+-- serialized cache headers of their JSON. IP addresses are stored as blobs of
+-- their octets in network order, so that blobs of the same IP version sort in
+-- address order.
+
+create table if not exists domain (
+    ldh_name text primary key,
+    unicode_name text,
+    response text not null,
+    cache_info text
+);
+
+create index if not exists domain_unicode_name_idx on domain (unicode_name);
+
+create table if not exists domain_ns_name (
+    ldh_name text not null references domain (ldh_name) on delete cascade,
+    ns_ldh_name text not null,
+    primary key (ldh_name, ns_ldh_name)
+);
+
+create table if not exists domain_ns_ip (
+    ldh_name text not null references domain (ldh_name) on delete cascade,
+    ns_ip blob not null,
+    primary key (ldh_name, ns_ip)
+);
+
+create index if not exists domain_ns_ip_idx on domain_ns_ip (ns_ip);
+
+create table if not exists entity (
+    handle text primary key,
+    full_name text,
+    response text not null,
+    cache_info text
+);
+
+create table if not exists nameserver (
+    ldh_name text primary key,
+    response text not null,
+    cache_info text
+);
+
+create table if not exists nameserver_ip (
+    ldh_name text not null references nameserver (ldh_name) on delete cascade,
+    ip_version integer not null,
+    ip blob not null,
+    primary key (ldh_name, ip)
+);
+
+create index if not exists nameserver_ip_idx on nameserver_ip (ip_version, ip);
+
+-- The tables indexing domains, nameservers and entities by the entities embedded
+-- in them, used for RFC 9536 reverse searches. Each row is one embedded entity,
+-- with its emails and roles as JSON arrays of lowercase text.
+
+create table if not exists domain_entity (
+    ldh_name text not null references domain (ldh_name) on delete cascade,
+    position integer not null,
+    entity_handle text,
+    full_name text,
+    emails text not null,
+    roles text not null,
+    primary key (ldh_name, position)
+);
+
+create table if not exists nameserver_entity (
+    ldh_name text not null references nameserver (ldh_name) on delete cascade,
+    position integer not null,
+    entity_handle text,
+    full_name text,
+    emails text not null,
+    roles text not null,
+    primary key (ldh_name, position)
+);
+
+create table if not exists entity_entity (
+    handle text not null references entity (handle) on delete cascade,
+    position integer not null,
+    entity_handle text,
+    full_name text,
+    emails text not null,
+    roles text not null,
+    primary key (handle, position)
+);
+
+create table if not exists autnum (
+    start_autnum integer not null,
+    end_autnum integer not null,
+    response text not null,
+    cache_info text,
+    primary key (start_autnum, end_autnum)
+);
+
+-- Networks are stored as one row per CIDR block covering the network's range,
+-- so that lookups are a longest prefix match over the blocks.
+create table if not exists network (
+    ip_version integer not null,
+    start_addr blob not null,
+    end_addr blob not null,
+    prefix_len integer not null,
+    response text not null,
+    cache_info text,
+    primary key (ip_version, start_addr, prefix_len)
+);
+
+create table if not exists srv_help (
+    host text primary key,
+    response text not null
+);
@@ -0,0 +1,519 @@
+use std::{net::IpAddr, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use axum::body::Bytes;
+use icann_rdap_common::response::{
+    domain::Domain, entity::Entity, nameserver::Nameserver, RdapResponse,
+};
+use ipnet::IpNet;
+use serde::de::DeserializeOwned;
+use sqlx::{
+    migrate::Migrator,
+    query, query_as, query_scalar,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode},
+    SqlitePool,
+};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::{debug, info};
+
+use crate::{
+    error::RdapServerError,
+    rdap::response::NOT_FOUND,
+    storage::{
+        search::{
+            domain_search_results, entity_search_results, nameserver_search_results,
+            reverse_search_results, RelatedEntitySearch, ReverseSearchType, SearchPattern,
+        },
+        sql::reverse_search_tables,
+        Lookup, ObjectCounts, SerializedObject, StoreOps, TxHandle,
+    },
+};
+
+use super::{
+    config::SqliteConfig,
+    tx::{addr_octets, SqliteTx},
+};
+
+/// The schema migrations, embedded from the `migrations` directory.
+pub(crate) static MIGRATOR: Migrator = sqlx::migrate!("src/storage/sqlite/migrations");
+
+#[derive(Clone)]
+pub struct Sqlite {
+    pub(crate) pool: SqlitePool,
+    // held by each transaction until it ends.
+    writer: Arc<Mutex<()>>,
+}
+
+impl Sqlite {
+    /// Opens the database file of the URL, creating it if it does not exist.
+    pub async fn new(config: SqliteConfig) -> Result<Self, RdapServerError> {
+        let options = SqliteConnectOptions::from_str(&config.db_url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePool::connect_with(options).await?;
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Uses an existing pool of connections, such as one to an in-memory database.
+    /// The schema is migrated by [StoreOps::init].
+    pub fn from_pool(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            writer: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Waits for the transaction being written, if any, to end, giving the lock which
+    /// the next transaction holds until it ends.
+    pub(crate) async fn lock_writer(&self) -> OwnedMutexGuard<()> {
+        Arc::clone(&self.writer).lock_owned().await
+    }
+}
+
+/// Parses the response column of an optional row into an [RdapResponse],
+/// using a 404 if there was no row.
+fn found_or_not(result: Option<String>) -> Result<RdapResponse, RdapServerError> {
+    match result {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(NOT_FOUND.clone()),
+    }
+}
+
+/// Parses the response columns of the rows found by a search.
+fn from_rows<T: DeserializeOwned>(rows: Vec<String>) -> Result<Vec<T>, RdapServerError> {
+    rows.iter()
+        .map(|json| Ok(serde_json::from_str(json)?))
+        .collect()
+}
+
+/// Gets the limit of a search, which is one more than the maximum number of results
+/// so that truncated results are known.
+fn search_limit(max_results: usize) -> i64 {
+    i64::try_from(max_results.saturating_add(1)).unwrap_or(i64::MAX)
+}
+
+impl Sqlite {
+    /// Gets the response and cache info columns of the object found by the lookup.
+    async fn lookup_row(
+        &self,
+        lookup: Lookup<'_>,
+    ) -> Result<Option<(String, Option<String>)>, RdapServerError> {
+        let result = match lookup {
+            Lookup::DomainByLdh(ldh) => {
+                query_as("select response, cache_info from domain where ldh_name = ?1")
+                    .bind(ldh)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            Lookup::EntityByHandle(handle) => {
+                query_as("select response, cache_info from entity where handle = ?1")
+                    .bind(handle)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            Lookup::NameserverByLdh(ldh) => {
+                query_as("select response, cache_info from nameserver where ldh_name = ?1")
+                    .bind(ldh)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            Lookup::AutnumByNum(num) => {
+                // the most specific (smallest) range containing the number wins, or of
+                // ranges of the same size, the one starting first.
+                query_as(
+                    "select response, cache_info from autnum \
+                     where start_autnum <= ?1 and end_autnum >= ?1 \
+                     order by end_autnum - start_autnum, start_autnum \
+                     limit 1",
+                )
+                .bind(i64::from(num))
+                .fetch_optional(&self.pool)
+                .await?
+            }
+            Lookup::NetworkByIpAddr(ipaddr) => {
+                let (ip_version, addr) = addr_octets(ipaddr.parse::<IpAddr>()?);
+                query_as(
+                    "select response, cache_info from network \
+                     where ip_version = ?1 and start_addr <= ?2 and end_addr >= ?2 \
+                     order by prefix_len desc \
+                     limit 1",
+                )
+                .bind(ip_version)
+                .bind(addr)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+            Lookup::NetworkByCidr(cidr) => {
+                let net = IpNet::from_str(cidr)?.trunc();
+                let (ip_version, start_addr) = addr_octets(net.network());
+                let (_, end_addr) = addr_octets(net.broadcast());
+                query_as(
+                    "select response, cache_info from network \
+                     where ip_version = ?1 and start_addr <= ?2 and end_addr >= ?3 \
+                     order by prefix_len desc \
+                     limit 1",
+                )
+                .bind(ip_version)
+                .bind(start_addr)
+                .bind(end_addr)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+        };
+        Ok(result)
+    }
+
+    /// Gets the object found by the lookup, using a 404 if there is none.
+    async fn lookup(&self, lookup: Lookup<'_>) -> Result<RdapResponse, RdapServerError> {
+        found_or_not(self.lookup_row(lookup).await?.map(|(json, _)| json))
+    }
+
+    /// Finds the objects of the search type with an embedded entity matching the search.
+    /// Like other searches, errors such as redirects are not found.
+    async fn reverse_search<T>(
+        &self,
+        search_type: ReverseSearchType,
+        related: &RelatedEntitySearch,
+        max_results: usize,
+    ) -> Result<Vec<T>, RdapServerError>
+    where
+        T: DeserializeOwned,
+    {
+        if related.is_empty() {
+            return Ok(vec![]);
+        }
+        let (table, key_column, related_table) = reverse_search_tables(search_type);
+        let object_class_name = match search_type {
+            ReverseSearchType::Domains => "domain",
+            ReverseSearchType::Nameservers => "nameserver",
+            ReverseSearchType::Entities => "entity",
+        };
+        let results: Vec<String> = query_scalar(&format!(
+            "select response from {table} \
+             where {key_column} in \
+             (select {key_column} from {related_table} \
+             where (?1 is null or lower(entity_handle) like ?1 escape '\\') \
+             and (?2 is null or lower(full_name) like ?2 escape '\\') \
+             and (?3 is null or exists \
+             (select 1 from json_each(emails) where lower(value) like lower(?3) escape '\\')) \
+             and (?4 is null or exists (select 1 from json_each(roles) where value = ?4))) \
+             and json_extract(response, '$.objectClassName') = '{object_class_name}' \
+             order by {key_column} \
+             limit ?5"
+        ))
+        .bind(related.handle.as_ref().map(SearchPattern::to_sql_like))
+        .bind(related.full_name.as_ref().map(SearchPattern::to_sql_like))
+        .bind(related.email.as_ref().map(SearchPattern::to_sql_like))
+        .bind(related.role.as_ref().map(|role| role.to_lowercase()))
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        from_rows(results)
+    }
+}
+
+#[async_trait]
+impl StoreOps for Sqlite {
+    async fn init(&self) -> Result<(), RdapServerError> {
+        debug!("Testing database connection.");
+        let mut conn = self.pool.acquire().await?;
+        query("select 1").fetch_one(&mut *conn).await?;
+        info!("Database connection test is successful.");
+        MIGRATOR.run_direct(&mut *conn).await?;
+        info!("Database schema is up to date.");
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<(), RdapServerError> {
+        query("select 1").fetch_one(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn new_tx(&self) -> Result<Box<dyn TxHandle>, RdapServerError> {
+        Ok(Box::new(SqliteTx::new(self).await?))
+    }
+
+    async fn new_truncate_tx(&self) -> Result<Box<dyn TxHandle>, RdapServerError> {
+        Ok(Box::new(SqliteTx::new_truncate(self).await?))
+    }
+
+    async fn get_domain_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError> {
+        self.lookup(Lookup::DomainByLdh(ldh)).await
+    }
+
+    async fn get_domain_by_unicode(&self, unicode: &str) -> Result<RdapResponse, RdapServerError> {
+        let result = query_scalar("select response from domain where unicode_name = ?1 limit 1")
+            .bind(unicode)
+            .fetch_optional(&self.pool)
+            .await?;
+        found_or_not(result)
+    }
+
+    async fn search_domains_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(name).to_sql_like();
+        // only domains are searched, not errors such as redirects.
+        let results: Vec<String> = query_scalar(
+            "select response from domain \
+             where (lower(ldh_name) like ?1 escape '\\' or lower(unicode_name) like ?1 escape '\\') \
+             and json_extract(response, '$.objectClassName') = 'domain' \
+             order by ldh_name \
+             limit ?2",
+        )
+        .bind(pattern)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(domain_search_results(
+            from_rows::<Domain>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn search_domains_by_ns_ldh_name(
+        &self,
+        ns_ldh_name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(ns_ldh_name).to_sql_like();
+        let results: Vec<String> = query_scalar(
+            "select response from domain \
+             where ldh_name in \
+             (select ldh_name from domain_ns_name where lower(ns_ldh_name) like ?1 escape '\\') \
+             and json_extract(response, '$.objectClassName') = 'domain' \
+             order by ldh_name \
+             limit ?2",
+        )
+        .bind(pattern)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(domain_search_results(
+            from_rows::<Domain>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn search_domains_by_ns_ip(
+        &self,
+        ns_ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let (_, addr) = addr_octets(ns_ip.parse::<IpAddr>()?);
+        let results: Vec<String> = query_scalar(
+            "select response from domain \
+             where ldh_name in \
+             (select ldh_name from domain_ns_ip where ns_ip = ?1) \
+             and json_extract(response, '$.objectClassName') = 'domain' \
+             order by ldh_name \
+             limit ?2",
+        )
+        .bind(addr)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(domain_search_results(
+            from_rows::<Domain>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn get_entity_by_handle(&self, handle: &str) -> Result<RdapResponse, RdapServerError> {
+        self.lookup(Lookup::EntityByHandle(handle)).await
+    }
+
+    async fn search_entities_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(name).to_sql_like();
+        // only entities are searched, not errors such as redirects.
+        let results: Vec<String> = query_scalar(
+            "select response from entity \
+             where lower(full_name) like ?1 escape '\\' \
+             and json_extract(response, '$.objectClassName') = 'entity' \
+             order by handle \
+             limit ?2",
+        )
+        .bind(pattern)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entity_search_results(
+            from_rows::<Entity>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn search_entities_by_handle(
+        &self,
+        handle: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(handle).to_sql_like();
+        let results: Vec<String> = query_scalar(
+            "select response from entity \
+             where lower(handle) like ?1 escape '\\' \
+             and json_extract(response, '$.objectClassName') = 'entity' \
+             order by handle \
+             limit ?2",
+        )
+        .bind(pattern)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entity_search_results(
+            from_rows::<Entity>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn get_nameserver_by_ldh(&self, ldh: &str) -> Result<RdapResponse, RdapServerError> {
+        self.lookup(Lookup::NameserverByLdh(ldh)).await
+    }
+
+    async fn search_nameservers_by_name(
+        &self,
+        name: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let pattern = SearchPattern::new(name).to_sql_like();
+        // only nameservers are searched, not errors such as redirects.
+        let results: Vec<String> = query_scalar(
+            "select response from nameserver \
+             where lower(ldh_name) like ?1 escape '\\' \
+             and json_extract(response, '$.objectClassName') = 'nameserver' \
+             order by ldh_name \
+             limit ?2",
+        )
+        .bind(pattern)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(nameserver_search_results(
+            from_rows::<Nameserver>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn search_nameservers_by_ip(
+        &self,
+        ip: &str,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let net = if ip.contains('/') {
+            IpNet::from_str(ip)?.trunc()
+        } else {
+            IpNet::from(ip.parse::<IpAddr>()?)
+        };
+        let (ip_version, start_addr) = addr_octets(net.network());
+        let (_, end_addr) = addr_octets(net.broadcast());
+        let results: Vec<String> = query_scalar(
+            "select response from nameserver \
+             where ldh_name in \
+             (select ldh_name from nameserver_ip \
+             where ip_version = ?1 and ip between ?2 and ?3) \
+             and json_extract(response, '$.objectClassName') = 'nameserver' \
+             order by ldh_name \
+             limit ?4",
+        )
+        .bind(ip_version)
+        .bind(start_addr)
+        .bind(end_addr)
+        .bind(search_limit(max_results))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(nameserver_search_results(
+            from_rows::<Nameserver>(results)?,
+            max_results,
+        ))
+    }
+
+    async fn reverse_search_by_entity(
+        &self,
+        search_type: ReverseSearchType,
+        related: &RelatedEntitySearch,
+        max_results: usize,
+    ) -> Result<RdapResponse, RdapServerError> {
+        let response = match search_type {
+            ReverseSearchType::Domains => domain_search_results(
+                self.reverse_search(search_type, related, max_results)
+                    .await?,
+                max_results,
+            ),
+            ReverseSearchType::Nameservers => nameserver_search_results(
+                self.reverse_search(search_type, related, max_results)
+                    .await?,
+                max_results,
+            ),
+            ReverseSearchType::Entities => entity_search_results(
+                self.reverse_search(search_type, related, max_results)
+                    .await?,
+                max_results,
+            ),
+        };
+        Ok(reverse_search_results(response))
+    }
+
+    async fn get_autnum_by_num(&self, num: u32) -> Result<RdapResponse, RdapServerError> {
+        self.lookup(Lookup::AutnumByNum(num)).await
+    }
+
+    async fn get_network_by_ipaddr(&self, ipaddr: &str) -> Result<RdapResponse, RdapServerError> {
+        self.lookup(Lookup::NetworkByIpAddr(ipaddr)).await
+    }
+
+    async fn get_network_by_cidr(&self, cidr: &str) -> Result<RdapResponse, RdapServerError> {
+        self.lookup(Lookup::NetworkByCidr(cidr)).await
+    }
+
+    async fn get_srv_help(&self, host: Option<&str>) -> Result<RdapResponse, RdapServerError> {
+        let host = host.unwrap_or("..default");
+        let result = query_scalar("select response from srv_help where host = ?1")
+            .bind(host)
+            .fetch_optional(&self.pool)
+            .await?;
+        found_or_not(result)
+    }
+
+    async fn get_serialized(
+        &self,
+        lookup: Lookup<'_>,
+    ) -> Result<Option<SerializedObject>, RdapServerError> {
+        // The stored JSON is served as is, except for errors such as redirects
+        // which have no cache info.
+        let Some((json, Some(cache_info))) = self.lookup_row(lookup).await? else {
+            return Ok(None);
+        };
+        Ok(Some(SerializedObject {
+            json: Bytes::from(json),
+            cache_info: serde_json::from_str(&cache_info)?,
+        }))
+    }
+
+    async fn get_object_counts(&self) -> Result<ObjectCounts, RdapServerError> {
+        // Networks are stored as a row for each CIDR block, so they are counted by response.
+        let (domains, entities, nameservers, autnums, networks): (i64, i64, i64, i64, i64) =
+            query_as(
+                r#"select
+                    (select count(*) from domain),
+                    (select count(*) from entity),
+                    (select count(*) from nameserver),
+                    (select count(*) from autnum),
+                    (select count(distinct response) from network)"#,
+            )
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(ObjectCounts {
+            domains: domains as usize,
+            entities: entities as usize,
+            nameservers: nameservers as usize,
+            autnums: autnums as usize,
+            networks: networks as usize,
+        })
+    }
+}
@@ -0,0 +1,563 @@
+use std::net::IpAddr;
+
+use async_trait::async_trait;
+use icann_rdap_common::response::{
+    autnum::Autnum, domain::Domain, entity::Entity, help::Help, nameserver::Nameserver,
+    network::Network, RdapResponse,
+};
+use ipnet::IpNet;
+use sqlx::query;
+use tokio::sync::OwnedMutexGuard;
+
+use crate::{
+    error::RdapServerError,
+    rdap::caching::CacheInfo,
+    storage::{
+        data::{AutnumId, DomainId, EntityId, NameserverId, NetworkId, NetworkIdType},
+        search::{entity_emails, entity_full_name, nameserver_ips, ReverseSearchType},
+        sql::{range_to_prefixes, reverse_search_tables},
+        TxHandle,
+    },
+};
+
+use super::ops::Sqlite;
+
+/// Gets the IP version and octets of an address, as stored in the tables.
+/// The octets of addresses of the same IP version sort in address order.
+pub(crate) fn addr_octets(addr: IpAddr) -> (i64, Vec<u8>) {
+    match addr {
+        IpAddr::V4(v4) => (4, v4.octets().to_vec()),
+        IpAddr::V6(v6) => (6, v6.octets().to_vec()),
+    }
+}
+
+/// Serializes an object, with the serialized cache headers of its JSON for objects
+/// which are served as stored (not errors such as redirects).
+fn serialize(response: &RdapResponse) -> Result<(String, Option<String>), RdapServerError> {
+    let json = serde_json::to_string(response)?;
+    let cache_info = match response {
+        RdapResponse::ErrorResponse(_) => None,
+        _ => Some(serde_json::to_string(&CacheInfo::new(
+            response,
+            json.as_bytes(),
+        ))?),
+    };
+    Ok((json, cache_info))
+}
+
+/// Gets the CIDR blocks stored for a network error.
+fn network_id_prefixes(network_id: &NetworkId) -> Result<Vec<IpNet>, RdapServerError> {
+    match &network_id.network_id {
+        NetworkIdType::Cidr(cidr) => Ok(vec![cidr.trunc()]),
+        NetworkIdType::Range {
+            start_address,
+            end_address,
+        } => range_to_prefixes(start_address, end_address),
+    }
+}
+
+pub struct SqliteTx<'a> {
+    db_tx: sqlx::Transaction<'a, sqlx::Sqlite>,
+    _writer: OwnedMutexGuard<()>,
+}
+
+impl<'a> SqliteTx<'a> {
+    /// Starts a transaction once the one being written, if any, has ended. SQLite writes
+    /// one transaction at a time, and waiting here rather than in the database means a long
+    /// reload does not make other writes fail as the database is busy.
+    pub async fn new(sqlite: &Sqlite) -> Result<SqliteTx<'a>, RdapServerError> {
+        let writer = sqlite.lock_writer().await;
+        let db_tx = sqlite.pool.begin().await?;
+        Ok(SqliteTx {
+            db_tx,
+            _writer: writer,
+        })
+    }
+
+    pub async fn new_truncate(sqlite: &Sqlite) -> Result<SqliteTx<'a>, RdapServerError> {
+        let writer = sqlite.lock_writer().await;
+        let mut db_tx = sqlite.pool.begin().await?;
+        // SQLite has no truncate, and the search indexes are deleted by cascade.
+        for table in [
+            "domain",
+            "entity",
+            "nameserver",
+            "autnum",
+            "network",
+            "srv_help",
+        ] {
+            query(&format!("delete from {table}"))
+                .execute(&mut *db_tx)
+                .await?;
+        }
+        Ok(SqliteTx {
+            db_tx,
+            _writer: writer,
+        })
+    }
+
+    async fn upsert_domain(
+        &mut self,
+        ldh_name: &str,
+        unicode_name: Option<&str>,
+        response: &RdapResponse,
+    ) -> Result<(), RdapServerError> {
+        let (json, cache_info) = serialize(response)?;
+        query(
+            "insert into domain (ldh_name, unicode_name, response, cache_info) \
+             values (?1, ?2, ?3, ?4) \
+             on conflict (ldh_name) do update \
+             set unicode_name = excluded.unicode_name, response = excluded.response, \
+             cache_info = excluded.cache_info",
+        )
+        .bind(ldh_name)
+        .bind(unicode_name)
+        .bind(json)
+        .bind(cache_info)
+        .execute(&mut *self.db_tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Replaces the nameserver names and IP addresses indexed for the domain.
+    async fn index_domain_nameservers(
+        &mut self,
+        ldh_name: &str,
+        nameservers: &[Nameserver],
+    ) -> Result<(), RdapServerError> {
+        query("delete from domain_ns_name where ldh_name = ?1")
+            .bind(ldh_name)
+            .execute(&mut *self.db_tx)
+            .await?;
+        query("delete from domain_ns_ip where ldh_name = ?1")
+            .bind(ldh_name)
+            .execute(&mut *self.db_tx)
+            .await?;
+        for nameserver in nameservers {
+            if let Some(ns_ldh_name) = nameserver.ldh_name.as_ref() {
+                query(
+                    "insert into domain_ns_name (ldh_name, ns_ldh_name) values (?1, ?2) \
+                     on conflict do nothing",
+                )
+                .bind(ldh_name)
+                .bind(ns_ldh_name)
+                .execute(&mut *self.db_tx)
+                .await?;
+            }
+            for addr in nameserver_ips(nameserver) {
+                let (_, octets) = addr_octets(addr);
+                query(
+                    "insert into domain_ns_ip (ldh_name, ns_ip) values (?1, ?2) \
+                     on conflict do nothing",
+                )
+                .bind(ldh_name)
+                .bind(octets)
+                .execute(&mut *self.db_tx)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert_entity(
+        &mut self,
+        handle: &str,
+        full_name: Option<&str>,
+        response: &RdapResponse,
+    ) -> Result<(), RdapServerError> {
+        let (json, cache_info) = serialize(response)?;
+        query(
+            "insert into entity (handle, full_name, response, cache_info) \
+             values (?1, ?2, ?3, ?4) \
+             on conflict (handle) do update \
+             set full_name = excluded.full_name, response = excluded.response, \
+             cache_info = excluded.cache_info",
+        )
+        .bind(handle)
+        .bind(full_name)
+        .bind(json)
+        .bind(cache_info)
+        .execute(&mut *self.db_tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn upsert_nameserver(
+        &mut self,
+        ldh_name: &str,
+        response: &RdapResponse,
+    ) -> Result<(), RdapServerError> {
+        let (json, cache_info) = serialize(response)?;
+        query(
+            "insert into nameserver (ldh_name, response, cache_info) values (?1, ?2, ?3) \
+             on conflict (ldh_name) do update \
+             set response = excluded.response, cache_info = excluded.cache_info",
+        )
+        .bind(ldh_name)
+        .bind(json)
+        .bind(cache_info)
+        .execute(&mut *self.db_tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Replaces the IP addresses indexed for the nameserver.
+    async fn index_nameserver_ips(
+        &mut self,
+        ldh_name: &str,
+        nameserver: Option<&Nameserver>,
+    ) -> Result<(), RdapServerError> {
+        query("delete from nameserver_ip where ldh_name = ?1")
+            .bind(ldh_name)
+            .execute(&mut *self.db_tx)
+            .await?;
+        for addr in nameserver.into_iter().flat_map(nameserver_ips) {
+            let (ip_version, octets) = addr_octets(addr);
+            query(
+                "insert into nameserver_ip (ldh_name, ip_version, ip) values (?1, ?2, ?3) \
+                 on conflict do nothing",
+            )
+            .bind(ldh_name)
+            .bind(ip_version)
+            .bind(octets)
+            .execute(&mut *self.db_tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the embedded entities indexed for the object with the key.
+    async fn index_related_entities(
+        &mut self,
+        search_type: ReverseSearchType,
+        key: &str,
+        entities: Option<&Vec<Entity>>,
+    ) -> Result<(), RdapServerError> {
+        let (_, key_column, related_table) = reverse_search_tables(search_type);
+        query(&format!(
+            "delete from {related_table} where {key_column} = ?1"
+        ))
+        .bind(key)
+        .execute(&mut *self.db_tx)
+        .await?;
+        for (position, entity) in entities.into_iter().flatten().enumerate() {
+            let emails = entity_emails(entity)
+                .iter()
+                .map(|email| email.to_lowercase())
+                .collect::<Vec<String>>();
+            let roles = entity
+                .roles
+                .iter()
+                .flatten()
+                .map(|role| role.to_lowercase())
+                .collect::<Vec<String>>();
+            query(&format!(
+                "insert into {related_table} \
+                 ({key_column}, position, entity_handle, full_name, emails, roles) \
+                 values (?1, ?2, ?3, ?4, ?5, ?6)"
+            ))
+            .bind(key)
+            .bind(i64::try_from(position).unwrap_or(i64::MAX))
+            .bind(entity.object_common.handle.as_deref())
+            .bind(entity_full_name(entity))
+            .bind(serde_json::to_string(&emails)?)
+            .bind(serde_json::to_string(&roles)?)
+            .execute(&mut *self.db_tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn upsert_autnum(
+        &mut self,
+        start_autnum: u32,
+        end_autnum: u32,
+        response: &RdapResponse,
+    ) -> Result<(), RdapServerError> {
+        let (json, cache_info) = serialize(response)?;
+        query(
+            "insert into autnum (start_autnum, end_autnum, response, cache_info) \
+             values (?1, ?2, ?3, ?4) \
+             on conflict (start_autnum, end_autnum) do update \
+             set response = excluded.response, cache_info = excluded.cache_info",
+        )
+        .bind(i64::from(start_autnum))
+        .bind(i64::from(end_autnum))
+        .bind(json)
+        .bind(cache_info)
+        .execute(&mut *self.db_tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn upsert_networks(
+        &mut self,
+        prefixes: Vec<IpNet>,
+        response: &RdapResponse,
+    ) -> Result<(), RdapServerError> {
+        let (json, cache_info) = serialize(response)?;
+        for prefix in prefixes {
+            let (ip_version, start_addr) = addr_octets(prefix.network());
+            let (_, end_addr) = addr_octets(prefix.broadcast());
+            query(
+                "insert into network \
+                 (ip_version, start_addr, end_addr, prefix_len, response, cache_info) \
+                 values (?1, ?2, ?3, ?4, ?5, ?6) \
+                 on conflict (ip_version, start_addr, prefix_len) do update \
+                 set response = excluded.response, cache_info = excluded.cache_info",
+            )
+            .bind(ip_version)
+            .bind(start_addr)
+            .bind(end_addr)
+            .bind(i64::from(prefix.prefix_len()))
+            .bind(&json)
+            .bind(&cache_info)
+            .execute(&mut *self.db_tx)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<'a> TxHandle for SqliteTx<'a> {
+    async fn add_entity(&mut self, entity: &Entity) -> Result<(), RdapServerError> {
+        let handle = entity
+            .object_common
+            .handle
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("handle".to_string()))?;
+        self.upsert_entity(
+            handle,
+            entity_full_name(entity).as_deref(),
+            &RdapResponse::Entity(entity.clone()),
+        )
+        .await?;
+        self.index_related_entities(
+            ReverseSearchType::Entities,
+            handle,
+            entity.object_common.entities.as_ref(),
+        )
+        .await
+    }
+
+    async fn add_entity_err(
+        &mut self,
+        entity_id: &EntityId,
+        error: &icann_rdap_common::response::error::Error,
+    ) -> Result<(), RdapServerError> {
+        self.upsert_entity(
+            &entity_id.handle,
+            None,
+            &RdapResponse::ErrorResponse(error.clone()),
+        )
+        .await?;
+        self.index_related_entities(ReverseSearchType::Entities, &entity_id.handle, None)
+            .await
+    }
+
+    async fn add_domain(&mut self, domain: &Domain) -> Result<(), RdapServerError> {
+        let ldh_name = domain
+            .ldh_name
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("ldhName".to_string()))?;
+        self.upsert_domain(
+            ldh_name,
+            domain.unicode_name.as_deref(),
+            &RdapResponse::Domain(domain.clone()),
+        )
+        .await?;
+        self.index_domain_nameservers(ldh_name, domain.nameservers.as_deref().unwrap_or_default())
+            .await?;
+        self.index_related_entities(
+            ReverseSearchType::Domains,
+            ldh_name,
+            domain.object_common.entities.as_ref(),
+        )
+        .await
+    }
+
+    async fn add_domain_err(
+        &mut self,
+        domain_id: &DomainId,
+        error: &icann_rdap_common::response::error::Error,
+    ) -> Result<(), RdapServerError> {
+        self.upsert_domain(
+            &domain_id.ldh_name,
+            None,
+            &RdapResponse::ErrorResponse(error.clone()),
+        )
+        .await?;
+        self.index_domain_nameservers(&domain_id.ldh_name, &[])
+            .await?;
+        self.index_related_entities(ReverseSearchType::Domains, &domain_id.ldh_name, None)
+            .await
+    }
+
+    async fn add_nameserver(&mut self, nameserver: &Nameserver) -> Result<(), RdapServerError> {
+        let ldh_name = nameserver
+            .ldh_name
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("ldhName".to_string()))?;
+        self.upsert_nameserver(ldh_name, &RdapResponse::Nameserver(nameserver.clone()))
+            .await?;
+        self.index_nameserver_ips(ldh_name, Some(nameserver))
+            .await?;
+        self.index_related_entities(
+            ReverseSearchType::Nameservers,
+            ldh_name,
+            nameserver.object_common.entities.as_ref(),
+        )
+        .await
+    }
+
+    async fn add_nameserver_err(
+        &mut self,
+        nameserver_id: &NameserverId,
+        error: &icann_rdap_common::response::error::Error,
+    ) -> Result<(), RdapServerError> {
+        self.upsert_nameserver(
+            &nameserver_id.ldh_name,
+            &RdapResponse::ErrorResponse(error.clone()),
+        )
+        .await?;
+        self.index_nameserver_ips(&nameserver_id.ldh_name, None)
+            .await?;
+        self.index_related_entities(
+            ReverseSearchType::Nameservers,
+            &nameserver_id.ldh_name,
+            None,
+        )
+        .await
+    }
+
+    async fn add_autnum(&mut self, autnum: &Autnum) -> Result<(), RdapServerError> {
+        let start_num = autnum
+            .start_autnum
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("startNum".to_string()))?;
+        let end_num = autnum
+            .end_autnum
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("endNum".to_string()))?;
+        self.upsert_autnum(*start_num, *end_num, &RdapResponse::Autnum(autnum.clone()))
+            .await
+    }
+
+    async fn add_autnum_err(
+        &mut self,
+        autnum_id: &AutnumId,
+        error: &icann_rdap_common::response::error::Error,
+    ) -> Result<(), RdapServerError> {
+        self.upsert_autnum(
+            autnum_id.start_autnum,
+            autnum_id.end_autnum,
+            &RdapResponse::ErrorResponse(error.clone()),
+        )
+        .await
+    }
+
+    async fn add_network(&mut self, network: &Network) -> Result<(), RdapServerError> {
+        let start_addr = network
+            .start_address
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("startAddress".to_string()))?;
+        let end_addr = network
+            .end_address
+            .as_ref()
+            .ok_or_else(|| RdapServerError::EmptyIndexData("endAddress".to_string()))?;
+        let prefixes = range_to_prefixes(start_addr, end_addr)?;
+        self.upsert_networks(prefixes, &RdapResponse::Network(network.clone()))
+            .await
+    }
+
+    async fn add_network_err(
+        &mut self,
+        network_id: &NetworkId,
+        error: &icann_rdap_common::response::error::Error,
+    ) -> Result<(), RdapServerError> {
+        let prefixes = network_id_prefixes(network_id)?;
+        self.upsert_networks(prefixes, &RdapResponse::ErrorResponse(error.clone()))
+            .await
+    }
+
+    async fn delete_domain(&mut self, domain_id: &DomainId) -> Result<(), RdapServerError> {
+        // The search indexes of the domain are deleted by cascade.
+        query("delete from domain where ldh_name = ?1")
+            .bind(&domain_id.ldh_name)
+            .execute(&mut *self.db_tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_entity(&mut self, entity_id: &EntityId) -> Result<(), RdapServerError> {
+        query("delete from entity where handle = ?1")
+            .bind(&entity_id.handle)
+            .execute(&mut *self.db_tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_nameserver(
+        &mut self,
+        nameserver_id: &NameserverId,
+    ) -> Result<(), RdapServerError> {
+        query("delete from nameserver where ldh_name = ?1")
+            .bind(&nameserver_id.ldh_name)
+            .execute(&mut *self.db_tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_autnum(&mut self, autnum_id: &AutnumId) -> Result<(), RdapServerError> {
+        query("delete from autnum where start_autnum = ?1 and end_autnum = ?2")
+            .bind(i64::from(autnum_id.start_autnum))
+            .bind(i64::from(autnum_id.end_autnum))
+            .execute(&mut *self.db_tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_network(&mut self, network_id: &NetworkId) -> Result<(), RdapServerError> {
+        for prefix in network_id_prefixes(network_id)? {
+            let (ip_version, start_addr) = addr_octets(prefix.network());
+            query(
+                "delete from network \
+                 where ip_version = ?1 and start_addr = ?2 and prefix_len = ?3",
+            )
+            .bind(ip_version)
+            .bind(start_addr)
+            .bind(i64::from(prefix.prefix_len()))
+            .execute(&mut *self.db_tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn add_srv_help(
+        &mut self,
+        help: &Help,
+        host: Option<&str>,
+    ) -> Result<(), RdapServerError> {
+        let host = host.unwrap_or("..default");
+        query(
+            "insert into srv_help (host, response) values (?1, ?2) \
+             on conflict (host) do update set response = excluded.response",
+        )
+        .bind(host)
+        .bind(serde_json::to_string(&RdapResponse::Help(help.clone()))?)
+        .execute(&mut *self.db_tx)
+        .await?;
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RdapServerError> {
+        self.db_tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RdapServerError> {
+        self.db_tx.rollback().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use notify::{
+    event::{AccessKind, AccessMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver},
+    time::timeout,
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::ServiceConfig,
+    error::RdapServerError,
+    health::Health,
+    metrics::record_reload,
+    storage::{
+        data::{load_changes, load_dir, FileIds, RELOAD, UPDATE},
+        StoreOps,
+    },
+};
+
+/// The kind of data load, as recorded in metrics, for loading the changed files.
+const CHANGE: &str = "change";
+
+/// How long the data directory must be quiet before changed files are loaded, so that
+/// the files of a bulk copy are loaded together.
+pub(crate) const QUIET_PERIOD: Duration = Duration::from_millis(500);
+
+/// The longest changed files wait to be loaded while the data directory is busy.
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// The changes to the data directory waiting to be loaded.
+#[derive(Default)]
+struct Pending {
+    file_names: HashSet<OsString>,
+    reload: bool,
+    rescan: bool,
+    since: Option<Instant>,
+}
+
+impl Pending {
+    fn add(&mut self, event: Event) {
+        if event.need_rescan() {
+            self.rescan = true;
+        }
+        if matches!(event.kind, EventKind::Any | EventKind::Other) {
+            self.rescan = true;
+        }
+        if !is_written(&event) {
+            return;
+        }
+        for path in event.paths {
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            if file_name == UPDATE || file_name == RELOAD {
+                // the marker files only count when they are touched, not removed.
+                // changes are loaded as they happen, so touching `update` is not needed.
+                if file_name == UPDATE || matches!(event.kind, EventKind::Remove(_)) {
+                    continue;
+                }
+                self.reload = true;
+            } else {
+                self.file_names.insert(file_name.to_owned());
+            }
+            self.since.get_or_insert_with(Instant::now);
+        }
+        if self.rescan {
+            self.since.get_or_insert_with(Instant::now);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.since.is_none()
+    }
+
+    /// Changes are loaded once the directory is quiet, or once they have waited long
+    /// enough while it is busy.
+    fn is_due(&self) -> bool {
+        self.since.is_some_and(|since| since.elapsed() >= MAX_DELAY)
+    }
+}
+
+/// Determines if an event is a file being created, written or removed, rather than
+/// only read.
+pub(crate) fn is_written(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Modify(_)
+            | EventKind::Remove(_)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
+}
+
+/// Watches the directories for changes to their files, giving the notifications of the
+/// changes until the watcher is dropped.
+pub(crate) fn watch_dirs(
+    dirs: &[&Path],
+) -> notify::Result<(RecommendedWatcher, UnboundedReceiver<notify::Result<Event>>)> {
+    let (sender, events) = unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        // the receiver is only gone once the watcher is no longer needed.
+        let _ = sender.send(event);
+    })?;
+    for dir in dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+    Ok((watcher, events))
+}
+
+/// Reloads all the data of the data directory, replacing the data in storage, for the
+/// admin API and SIGHUP. This is done whether or not the data directory is watched.
+pub(crate) async fn reload_data(
+    data_dir: &str,
+    store: &dyn StoreOps,
+    health: &Health,
+) -> Result<(), RdapServerError> {
+    info!("Data being reloaded.");
+    let start = Instant::now();
+    let result = load_dir(data_dir, store, true).await;
+    record_reload(RELOAD, start, &result);
+    result?;
+    health.count_objects(store).await?;
+    health.set_data_loaded();
+    Ok(())
+}
+
+/// Watches the data directory for changes using filesystem notifications.
+///
+/// Files that are added, changed or removed are loaded without reading the rest of the
+/// directory, and touching the `reload` file reloads all the data.
+pub(crate) struct DataWatcher {
+    // dropping the watcher stops the notifications.
+    _watcher: RecommendedWatcher,
+    events: UnboundedReceiver<notify::Result<Event>>,
+}
+
+impl DataWatcher {
+    /// Starts watching the data directory. Changes made from then on are kept until
+    /// [DataWatcher::run] is called, so that changes made during the first load are not lost.
+    pub(crate) fn new(data_dir: &str) -> Result<Self, RdapServerError> {
+        let (watcher, events) = watch_dirs(&[Path::new(data_dir)]).map_err(|e| {
+            RdapServerError::Config(format!("cannot watch data directory {data_dir}: {e}"))
+        })?;
+        debug!("watching {data_dir} for changes");
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Loads the changes to the data directory as they happen, with the IDs of the objects
+    /// loaded from each file by the first load. Loads that fail are logged, and the changes
+    /// after them are still loaded.
+    pub(crate) async fn run(
+        mut self,
+        store: Box<dyn StoreOps>,
+        config: ServiceConfig,
+        health: Health,
+        mut file_ids: FileIds,
+    ) -> Result<(), RdapServerError> {
+        let mut pending = Pending::default();
+        loop {
+            let event = if pending.is_empty() {
+                Ok(self.events.recv().await)
+            } else {
+                timeout(QUIET_PERIOD, self.events.recv()).await
+            };
+            let quiet = match event {
+                Ok(Some(Ok(event))) => {
+                    pending.add(event);
+                    false
+                }
+                Ok(Some(Err(e))) => {
+                    warn!("data directory watch error: {e}");
+                    pending.rescan = true;
+                    pending.since.get_or_insert_with(Instant::now);
+                    false
+                }
+                Ok(None) => {
+                    return Err(RdapServerError::Config(
+                        "data directory no longer watched".to_string(),
+                    ))
+                }
+                Err(_) => true,
+            };
+            if pending.is_empty() || !(quiet || pending.is_due()) {
+                continue;
+            }
+            let changes = std::mem::take(&mut pending);
+            let start = Instant::now();
+            if changes.reload || changes.rescan {
+                let (kind, truncate) = if changes.reload {
+                    info!("Data being reloaded.");
+                    (RELOAD, true)
+                } else {
+                    info!("Data being updated.");
+                    (UPDATE, false)
+                };
+                let result = load_dir(&config.data_dir, &*store, truncate).await;
+                record_reload(kind, start, &result);
+                let result = match result {
+                    Ok(ids) if truncate => {
+                        file_ids = ids;
+                        Ok(())
+                    }
+                    // the files removed without notice are found by what was loaded before.
+                    Ok(ids) => {
+                        let removed = file_ids
+                            .keys()
+                            .filter(|file_name| !ids.contains_key(*file_name))
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        let mut previous = std::mem::replace(&mut file_ids, ids);
+                        if removed.is_empty() {
+                            Ok(())
+                        } else {
+                            load_changes(&config.data_dir, &removed, &*store, &mut previous).await
+                        }
+                    }
+                    Err(e) => Err(e),
+                };
+                let result = match result {
+                    Ok(()) => health.count_objects(&*store).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => health.set_data_loaded(),
+                    Err(e) => warn!("Data not loaded: {e}"),
+                }
+            } else if !changes.file_names.is_empty() {
+                let file_names = changes.file_names.into_iter().collect::<Vec<_>>();
+                let result =
+                    load_changes(&config.data_dir, &file_names, &*store, &mut file_ids).await;
+                record_reload(CHANGE, start, &result);
+                let result = match result {
+                    Ok(()) => health.count_objects(&*store).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => health.set_data_loaded(),
+                    Err(e) => warn!("Data changes not loaded: {e}"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use std::time::Duration;
+
+    use icann_rdap_common::response::{domain::Domain, RdapResponse};
+    use test_dir::{DirBuilder, TestDir};
+
+    use crate::{
+        config::{ServiceConfig, StorageType},
+        health::Health,
+        storage::{
+            data::{load_dir, scan_dir},
+            mem::{config::MemConfig, ops::Mem},
+            StoreOps,
+        },
+    };
+
+    use super::DataWatcher;
+
+    async fn new_and_watch_mem(temp: &TestDir) -> Mem {
+        watch_mem(temp, false).await
+    }
+
+    /// Loads the data directory into memory and watches it, with the IDs of the objects in
+    /// each file found by scanning the directory when `scan`, as when restoring a snapshot.
+    async fn watch_mem(temp: &TestDir, scan: bool) -> Mem {
+        let mem_config = MemConfig::builder().build();
+        let mem = Mem::new(mem_config.clone());
+        mem.init().await.expect("initialzing memeory");
+        let config = ServiceConfig::non_server()
+            .data_dir(temp.root().to_string_lossy().to_string())
+            .storage_type(StorageType::Memory(mem_config))
+            .build()
+            .expect("building service config");
+        let watcher = DataWatcher::new(&config.data_dir).expect("watching data dir");
+        let mut file_ids = load_dir(&config.data_dir, &mem, false)
+            .await
+            .expect("loading data");
+        if scan {
+            file_ids = scan_dir(&config.data_dir).await.expect("scanning data");
+        }
+        tokio::spawn(watcher.run(Box::new(mem.clone()), config, Health::default(), file_ids));
+        mem
+    }
+
+    fn write_domain(temp: &TestDir, file_name: &str, ldh_name: &str) {
+        let domain = Domain::basic().ldh_name(ldh_name).build();
+        std::fs::write(
+            temp.path(file_name),
+            serde_json::to_string(&domain).expect("serializing domain"),
+        )
+        .expect("writing file");
+    }
+
+    /// Waits for the domain to be found, or not, as the changes are loaded.
+    async fn wait_for_domain(mem: &Mem, ldh_name: &str, found: bool) -> bool {
+        for _ in 0..100 {
+            let response = mem
+                .get_domain_by_ldh(ldh_name)
+                .await
+                .expect("getting domain by ldh");
+            if matches!(response, RdapResponse::Domain(_)) == found {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn GIVEN_watched_data_dir_WHEN_file_added_THEN_object_loaded() {
+        // GIVEN
+        let temp = TestDir::temp();
+        let mem = new_and_watch_mem(&temp).await;
+
+        // WHEN
+        write_domain(&temp, "foo_example.json", "foo.example");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "foo.example", true).await);
+    }
+
+    #[tokio::test]
+    async fn GIVEN_loaded_file_WHEN_file_removed_THEN_object_deleted() {
+        // GIVEN
+        let temp = TestDir::temp();
+        write_domain(&temp, "foo_example.json", "foo.example");
+        write_domain(&temp, "bar_example.json", "bar.example");
+        let mem = new_and_watch_mem(&temp).await;
+
+        // WHEN
+        std::fs::remove_file(temp.path("foo_example.json")).expect("removing file");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "foo.example", false).await);
+        assert!(wait_for_domain(&mem, "bar.example", true).await);
+    }
+
+    #[tokio::test]
+    async fn GIVEN_scanned_files_WHEN_file_removed_THEN_object_deleted() {
+        // GIVEN
+        let temp = TestDir::temp();
+        write_domain(&temp, "foo_example.json", "foo.example");
+        let lines = ["bar.example", "baz.example"].map(|ldh_name| {
+            serde_json::to_string(&Domain::basic().ldh_name(ldh_name).build())
+                .expect("serializing domain")
+        });
+        std::fs::write(temp.path("bulk.ndjson"), lines.join("\n")).expect("writing file");
+        let mem = watch_mem(&temp, true).await;
+
+        // WHEN
+        std::fs::remove_file(temp.path("foo_example.json")).expect("removing file");
+        std::fs::write(temp.path("bulk.ndjson"), &lines[1]).expect("writing file");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "foo.example", false).await);
+        assert!(wait_for_domain(&mem, "bar.example", false).await);
+        assert!(wait_for_domain(&mem, "baz.example", true).await);
+    }
+
+    #[tokio::test]
+    async fn GIVEN_loaded_file_WHEN_file_changed_THEN_previous_object_deleted() {
+        // GIVEN
+        let temp = TestDir::temp();
+        write_domain(&temp, "example.json", "foo.example");
+        let mem = new_and_watch_mem(&temp).await;
+
+        // WHEN
+        write_domain(&temp, "example.json", "bar.example");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "bar.example", true).await);
+        assert!(wait_for_domain(&mem, "foo.example", false).await);
+    }
+
+    #[tokio::test]
+    async fn GIVEN_loaded_ndjson_WHEN_line_removed_THEN_object_deleted() {
+        // GIVEN
+        let temp = TestDir::temp();
+        let lines = ["foo.example", "bar.example"].map(|ldh_name| {
+            serde_json::to_string(&Domain::basic().ldh_name(ldh_name).build())
+                .expect("serializing domain")
+        });
+        std::fs::write(temp.path("bulk.ndjson"), lines.join("\n")).expect("writing file");
+        let mem = new_and_watch_mem(&temp).await;
+
+        // WHEN
+        std::fs::write(temp.path("bulk.ndjson"), &lines[1]).expect("writing file");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "foo.example", false).await);
+        assert!(wait_for_domain(&mem, "bar.example", true).await);
+    }
+
+    #[tokio::test]
+    async fn GIVEN_loaded_file_WHEN_tombstone_added_THEN_object_deleted() {
+        // GIVEN
+        let temp = TestDir::temp();
+        write_domain(&temp, "foo_example.json", "foo.example");
+        let mem = new_and_watch_mem(&temp).await;
+
+        // WHEN
+        std::fs::write(
+            temp.path("foo_example.tombstone"),
+            r#"{"domains":[{"ldhName":"foo.example"}]}"#,
+        )
+        .expect("writing file");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "foo.example", false).await);
+    }
+
+    #[tokio::test]
+    async fn GIVEN_watched_data_dir_WHEN_reload_touched_THEN_data_reloaded() {
+        // GIVEN
+        let temp = TestDir::temp();
+        let mem = new_and_watch_mem(&temp).await;
+        let mut tx = mem.new_tx().await.expect("new transaction");
+        tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+            .await
+            .expect("adding domain");
+        tx.commit().await.expect("committing");
+
+        // WHEN
+        write_domain(&temp, "bar_example.json", "bar.example");
+        std::fs::write(temp.path(super::RELOAD), "").expect("touching reload");
+
+        // THEN
+        assert!(wait_for_domain(&mem, "foo.example", false).await);
+        assert!(wait_for_domain(&mem, "bar.example", true).await);
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use axum_server::tls_rustls::RustlsConfig;
+use notify::{Event, RecommendedWatcher};
+use tokio::{sync::mpsc::UnboundedReceiver, time::timeout};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::TlsConfig,
+    error::RdapServerError,
+    storage::watch::{is_written, watch_dirs, QUIET_PERIOD},
+};
+
+impl TlsConfig {
+    /// Loads the certificate and key, giving the [CertWatcher] that reloads them when
+    /// their files change.
+    pub(crate) async fn load(&self) -> Result<(RustlsConfig, CertWatcher), RdapServerError> {
+        let rustls_config = RustlsConfig::from_pem_file(&self.cert_file, &self.key_file)
+            .await
+            .map_err(|e| {
+                RdapServerError::Config(format!(
+                    "TLS certificate {} or key {} is invalid: {e}",
+                    self.cert_file, self.key_file
+                ))
+            })?;
+        let watcher = CertWatcher::new(self.clone(), rustls_config.clone())?;
+        Ok((rustls_config, watcher))
+    }
+
+    /// Gets the directories of the certificate and key files.
+    fn dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = [&self.cert_file, &self.key_file]
+            .into_iter()
+            .map(|file| match Path::new(file).parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => PathBuf::from("."),
+            })
+            .collect::<Vec<_>>();
+        dirs.dedup();
+        dirs
+    }
+
+    /// Determines if an event is the certificate or key files being changed, and not the
+    /// files being read as they are reloaded.
+    fn is_changed(&self, event: &Event) -> bool {
+        if !is_written(event) {
+            return false;
+        }
+        let file_names = [&self.cert_file, &self.key_file].map(|file| Path::new(file).file_name());
+        event
+            .paths
+            .iter()
+            .any(|path| file_names.contains(&path.file_name()))
+    }
+
+    /// Gets the latest modification time of the certificate and key files.
+    async fn modified(&self) -> Option<SystemTime> {
+        let cert = tokio::fs::metadata(&self.cert_file)
+            .await
+            .ok()?
+            .modified()
+            .ok()?;
+        let key = tokio::fs::metadata(&self.key_file)
+            .await
+            .ok()?
+            .modified()
+            .ok()?;
+        Some(cert.max(key))
+    }
+}
+
+/// Watches the directories of the certificate and key files using filesystem notifications,
+/// so that the files are also reloaded when they are replaced by renaming them or by
+/// changing a symbolic link to them.
+pub(crate) struct CertWatcher {
+    // dropping the watcher stops the notifications.
+    _watcher: RecommendedWatcher,
+    events: UnboundedReceiver<notify::Result<Event>>,
+    tls: TlsConfig,
+    rustls_config: RustlsConfig,
+}
+
+impl CertWatcher {
+    fn new(tls: TlsConfig, rustls_config: RustlsConfig) -> Result<Self, RdapServerError> {
+        let dirs = tls.dirs();
+        let (watcher, events) = watch_dirs(&dirs.iter().map(PathBuf::as_path).collect::<Vec<_>>())
+            .map_err(|e| {
+                RdapServerError::Config(format!(
+                    "cannot watch TLS certificate {} or key {}: {e}",
+                    tls.cert_file, tls.key_file
+                ))
+            })?;
+        debug!("watching {dirs:?} for TLS certificate changes");
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            tls,
+            rustls_config,
+        })
+    }
+
+    /// Reloads the certificate and key once their directories are quiet after they change.
+    /// If they cannot be loaded, such as when only one of them has been replaced, the
+    /// previous ones are kept and loading is tried again when the files next change.
+    pub(crate) async fn run(mut self) -> Result<(), RdapServerError> {
+        let mut last_time = self.tls.modified().await;
+        loop {
+            let mut changed = false;
+            let mut event = self.events.recv().await;
+            loop {
+                match event {
+                    Some(Ok(event)) => changed |= self.tls.is_changed(&event),
+                    Some(Err(e)) => warn!("TLS certificate watch error: {e}"),
+                    None => {
+                        return Err(RdapServerError::Config(
+                            "TLS certificate no longer watched".to_string(),
+                        ))
+                    }
+                }
+                match timeout(QUIET_PERIOD, self.events.recv()).await {
+                    Ok(next) => event = next,
+                    Err(_) => break,
+                }
+            }
+            // symbolic links swapped in the directories only show in the modification time.
+            let modified = self.tls.modified().await;
+            if modified.is_none() || (!changed && modified == last_time) {
+                continue;
+            }
+            last_time = modified;
+            match self
+                .rustls_config
+                .reload_from_pem_file(&self.tls.cert_file, &self.tls.key_file)
+                .await
+            {
+                Ok(()) => info!("TLS certificate reloaded."),
+                Err(e) => warn!("TLS certificate not reloaded: {e}"),
+            }
+        }
+    }
+}
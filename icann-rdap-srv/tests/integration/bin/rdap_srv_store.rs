@@ -1,8 +1,9 @@
 #![allow(non_snake_case)]
 
+use icann_rdap_common::response::domain::Domain;
 use test_dir::DirBuilder;
 
-use crate::test_jig::RdapSrvStoreTestJig;
+use crate::test_jig::{PgTestJig, RdapSrvStoreTestJig};
 
 #[test]
 fn GIVEN_source_dir_same_as_data_dir_WHEN_invoked_THEN_error() {
@@ -16,3 +17,94 @@ fn GIVEN_source_dir_same_as_data_dir_WHEN_invoked_THEN_error() {
     let assert = test_jig.cmd.assert();
     assert.failure();
 }
+
+#[test]
+fn GIVEN_memory_storage_WHEN_migrate_THEN_error() {
+    // GIVEN
+    let mut test_jig = RdapSrvStoreTestJig::new();
+
+    // WHEN
+    test_jig
+        .cmd
+        .env("RDAP_SRV_STORAGE", "memory")
+        .arg("migrate");
+
+    // THEN
+    let assert = test_jig.cmd.assert();
+    assert.failure();
+}
+
+#[tokio::test]
+async fn GIVEN_postgres_storage_WHEN_status_THEN_migrations_applied() {
+    // GIVEN
+    let Some(pg_jig) = PgTestJig::new().await else {
+        return;
+    };
+    let mut test_jig = RdapSrvStoreTestJig::new();
+
+    // WHEN
+    test_jig
+        .cmd
+        .env("RDAP_SRV_STORAGE", "postgres")
+        .env("RDAP_SRV_DB_URL", &pg_jig.db_url)
+        .arg("status");
+
+    // THEN
+    let output = test_jig.cmd.output().expect("running status");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("applied"));
+    assert!(!stdout.contains("pending"));
+
+    pg_jig.drop_db().await;
+}
+
+#[test]
+fn GIVEN_valid_ndjson_WHEN_invoked_THEN_file_copied() {
+    // GIVEN
+    let mut test_jig = RdapSrvStoreTestJig::new();
+    let lines = ["foo.example", "bar.example"]
+        .map(|ldh_name| {
+            serde_json::to_string(&Domain::basic().ldh_name(ldh_name).build())
+                .expect("serializing domain")
+        })
+        .join("\n");
+    std::fs::write(test_jig.source_dir.path("bulk.ndjson"), lines).expect("writing file");
+
+    // WHEN
+    test_jig
+        .cmd
+        .arg("-C")
+        .arg("spec-error")
+        .arg(test_jig.source_dir.root());
+
+    // THEN
+    let assert = test_jig.cmd.assert();
+    assert.success();
+    assert!(test_jig.data_dir.path("bulk.ndjson").exists());
+}
+
+#[test]
+fn GIVEN_ndjson_with_non_rdap_line_WHEN_invoked_THEN_error() {
+    // GIVEN
+    let mut test_jig = RdapSrvStoreTestJig::new();
+    let lines = [
+        serde_json::to_string(&Domain::basic().ldh_name("foo.example").build())
+            .expect("serializing domain"),
+        r#"{"foo":"bar"}"#.to_string(),
+    ]
+    .join("\n");
+    std::fs::write(test_jig.source_dir.path("bulk.ndjson"), lines).expect("writing file");
+
+    // WHEN
+    test_jig
+        .cmd
+        .arg("-C")
+        .arg("spec-error")
+        .arg(test_jig.source_dir.root());
+
+    // THEN
+    let assert = test_jig.cmd.assert();
+    assert.failure();
+    assert!(!test_jig.data_dir.path("bulk.ndjson").exists());
+}
@@ -0,0 +1,132 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_common::client::{create_client, ClientConfig};
+use icann_rdap_srv::{
+    config::{RateLimit, RateLimitConfig},
+    rdap::access::Credentials,
+};
+use test_dir::{DirBuilder, FileType, TestDir};
+
+use crate::test_jig::SrvTestJig;
+
+/// The SHA-256 digest of `registrar-token`.
+const REGISTRAR_TOKEN_SHA256: &str =
+    "92d5e410855c9ae525e65c3dd94e78c57a0de79e2a2ae4b972892b586c84aacc";
+
+fn credentials(token_sha256: &str) -> Credentials {
+    let test_dir = TestDir::temp().create("credentials.json", FileType::EmptyFile);
+    let path = test_dir.path("credentials.json");
+    std::fs::write(
+        &path,
+        format!(
+            r#"{{
+                "credentials": [
+                    {{ "type": "bearer", "token_sha256": "{token_sha256}", "tier": "registrar" }}
+                ]
+            }}"#
+        ),
+    )
+    .expect("writing credential file");
+    Credentials::load(path.to_str().expect("path not a string")).expect("loading credentials")
+}
+
+#[tokio::test]
+async fn GIVEN_credentials_WHEN_anonymous_lookup_THEN_not_unauthorized() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_with_credentials(credentials(REGISTRAR_TOKEN_SHA256)).await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_credentials_WHEN_lookup_with_unknown_token_THEN_status_code_401() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_with_credentials(credentials(REGISTRAR_TOKEN_SHA256)).await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .bearer_auth("not-a-token")
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(response.headers().contains_key("www-authenticate"));
+}
+
+#[tokio::test]
+async fn GIVEN_lookup_rate_limit_WHEN_unknown_tokens_exceed_burst_THEN_status_code_429() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_with_credentials_rate_limited(
+        credentials(REGISTRAR_TOKEN_SHA256),
+        RateLimitConfig::builder()
+            .lookup(RateLimit {
+                per_minute: 1,
+                burst: 2,
+            })
+            .build(),
+    )
+    .await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let mut statuses = vec![];
+    for n in 0..3 {
+        let response = client
+            .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+            .bearer_auth(format!("guess-{n}"))
+            .send()
+            .await
+            .expect("quering server");
+        statuses.push(response.status().as_u16());
+    }
+
+    // THEN
+    assert_eq!(statuses, [401, 401, 429]);
+}
+
+#[tokio::test]
+async fn GIVEN_credentials_WHEN_lookup_with_known_token_THEN_not_unauthorized() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_with_credentials(credentials(REGISTRAR_TOKEN_SHA256)).await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .bearer_auth("registrar-token")
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 404);
+}
@@ -0,0 +1,341 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, error::Error},
+};
+use icann_rdap_srv::admin::Admin;
+use reqwest::Client;
+use serde_json::{json, Value};
+use test_dir::{DirBuilder, TestDir};
+
+use crate::test_jig::SrvTestJig;
+
+/// The SHA-256 digest of "admin-token".
+const ADMIN_TOKEN_SHA256: &str = "10a4c7c9fc5206d6f36dc6944a81bb6f4a3cb0e25014ae3b12e6c3e52712292a";
+
+async fn new_server(data_dir: &TestDir) -> SrvTestJig {
+    new_server_with_auto_reload(data_dir, false).await
+}
+
+async fn new_server_with_auto_reload(data_dir: &TestDir, auto_reload: bool) -> SrvTestJig {
+    let admin = Admin::builder()
+        .token_sha256(ADMIN_TOKEN_SHA256)
+        .data_dir(data_dir.root().to_string_lossy())
+        .auto_reload(auto_reload)
+        .build();
+    SrvTestJig::new_with_admin(admin).await
+}
+
+fn client() -> Client {
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    create_client(&client_config).expect("creating client")
+}
+
+/// Gets the URL of an admin path, which is outside of the RDAP base.
+fn admin_url(test_srv: &SrvTestJig, path: &str) -> String {
+    let base = test_srv.rdap_base.trim_end_matches("/rdap");
+    format!("{base}/admin/{path}")
+}
+
+#[tokio::test]
+async fn GIVEN_no_admin_token_WHEN_put_object_THEN_unauthorized() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "objects"))
+        .json(&Domain::basic().ldh_name("foo.example").build())
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn GIVEN_wrong_admin_token_WHEN_put_object_THEN_unauthorized() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("not-the-admin-token")
+        .json(&Domain::basic().ldh_name("foo.example").build())
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn GIVEN_domain_WHEN_put_object_THEN_domain_served() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&Domain::basic().ldh_name("foo.example").build())
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 204);
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn GIVEN_auto_reload_WHEN_put_object_THEN_conflict() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server_with_auto_reload(&test_dir, true).await;
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&Domain::basic().ldh_name("foo.example").build())
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 409);
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_domain_failing_checks_WHEN_put_object_THEN_bad_request() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&Domain::basic().ldh_name("foo bar.example").build())
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+    let json = response.json::<Value>().await.expect("error json");
+    assert!(!json["description"]
+        .as_array()
+        .expect("check descriptions")
+        .is_empty());
+    let response = client()
+        .get(format!("{}/domain/foo%20bar.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_not_rdap_WHEN_put_object_THEN_bad_request() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&json!({ "foo": "bar" }))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn GIVEN_domain_redirect_WHEN_put_redirect_THEN_redirect_served() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+    let template = json!({
+        "domain": { "error": Error::redirect().url("https://example.net/").build() },
+        "ids": [ { "ldhName": "foo.example" } ]
+    });
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "redirects"))
+        .bearer_auth("admin-token")
+        .json(&template)
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 204);
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 307);
+}
+
+#[tokio::test]
+async fn GIVEN_object_template_WHEN_put_redirect_THEN_bad_request() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+    let template = json!({
+        "domain": { "object": Domain::basic().ldh_name("example").build() },
+        "ids": [ { "ldhName": "foo.example" } ]
+    });
+
+    // WHEN
+    let response = client()
+        .put(admin_url(&test_srv, "redirects"))
+        .bearer_auth("admin-token")
+        .json(&template)
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn GIVEN_changed_data_dir_WHEN_reload_THEN_data_reloaded() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+    client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&Domain::basic().ldh_name("foo.example").build())
+        .send()
+        .await
+        .expect("quering server");
+    let domain = Domain::basic().ldh_name("bar.example").build();
+    std::fs::write(
+        test_dir.path("bar.example.json"),
+        serde_json::to_string(&domain).expect("serializing domain"),
+    )
+    .expect("writing domain");
+
+    // WHEN
+    let response = client()
+        .post(admin_url(&test_srv, "reload"))
+        .bearer_auth("admin-token")
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 204);
+    let response = client()
+        .get(format!("{}/domain/bar.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 200);
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_domain_WHEN_delete_objects_THEN_domain_not_found() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+    client()
+        .put(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&Domain::basic().ldh_name("foo.example").build())
+        .send()
+        .await
+        .expect("quering server");
+
+    // WHEN
+    let response = client()
+        .delete(admin_url(&test_srv, "objects"))
+        .bearer_auth("admin-token")
+        .json(&json!({ "domains": [ { "ldhName": "foo.example" } ] }))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 204);
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_autnum_redirect_WHEN_delete_redirects_THEN_autnum_not_found() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let test_srv = new_server(&test_dir).await;
+    let template = json!({
+        "autnum": { "error": Error::redirect().url("https://example.net/").build() },
+        "ids": [ { "startAutnum": 700, "endAutnum": 710 } ]
+    });
+    client()
+        .put(admin_url(&test_srv, "redirects"))
+        .bearer_auth("admin-token")
+        .json(&template)
+        .send()
+        .await
+        .expect("quering server");
+
+    // WHEN
+    let response = client()
+        .delete(admin_url(&test_srv, "redirects"))
+        .bearer_auth("admin-token")
+        .json(&json!({ "autnums": [ { "startAutnum": 700, "endAutnum": 710 } ] }))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 204);
+    let response = client()
+        .get(format!("{}/autnum/705", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    assert_eq!(response.status().as_u16(), 404);
+}
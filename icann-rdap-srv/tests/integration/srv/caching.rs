@@ -0,0 +1,198 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, types::Event},
+};
+use icann_rdap_srv::{config::CacheConfig, storage::StoreOps};
+use reqwest::{
+    header::{CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    Client,
+};
+
+use crate::test_jig::SrvTestJig;
+
+async fn new_server(cache: CacheConfig) -> SrvTestJig {
+    let test_srv = SrvTestJig::new_with_cache(cache).await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .event(
+                Event::builder()
+                    .event_action("last changed")
+                    .event_date("2023-01-01T00:00:10Z")
+                    .build(),
+            )
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+    test_srv
+}
+
+fn client() -> Client {
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    create_client(&client_config).expect("creating client")
+}
+
+#[tokio::test]
+async fn GIVEN_domain_max_age_WHEN_query_domain_THEN_caching_headers() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::builder().domain(3600).build()).await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers()[CACHE_CONTROL], "public, max-age=3600");
+    assert!(response.headers().contains_key(ETAG));
+    assert_eq!(
+        response.headers()[LAST_MODIFIED],
+        "Sun, 01 Jan 2023 00:00:10 GMT"
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_no_max_age_WHEN_query_domain_THEN_no_cache_control() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::default()).await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.headers().contains_key(CACHE_CONTROL));
+    assert!(response.headers().contains_key(ETAG));
+}
+
+#[tokio::test]
+async fn GIVEN_matching_etag_WHEN_query_domain_THEN_status_code_304() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::default()).await;
+    let client = client();
+    let first = client
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+    let etag = first.headers()[ETAG].clone();
+
+    // WHEN
+    let response = client
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .header(IF_NONE_MATCH, etag)
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 304);
+    assert!(response.bytes().await.expect("reading body").is_empty());
+}
+
+#[tokio::test]
+async fn GIVEN_other_etag_WHEN_query_domain_THEN_status_code_200() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::default()).await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .header(IF_NONE_MATCH, r#""stale""#)
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn GIVEN_unmodified_since_WHEN_query_domain_THEN_status_code_304() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::default()).await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .header(IF_MODIFIED_SINCE, "Mon, 02 Jan 2023 00:00:00 GMT")
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 304);
+}
+
+#[tokio::test]
+async fn GIVEN_modified_since_WHEN_query_domain_THEN_status_code_200() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::default()).await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .header(IF_MODIFIED_SINCE, "Sat, 31 Dec 2022 00:00:00 GMT")
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn GIVEN_domain_WHEN_head_domain_THEN_headers_of_get_without_body() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::builder().domain(60).build()).await;
+    let client = client();
+    let get = client
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // WHEN
+    let head = client
+        .head(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(head.status().as_u16(), 200);
+    assert_eq!(head.headers()[ETAG], get.headers()[ETAG]);
+    assert_eq!(head.headers()[CACHE_CONTROL], "public, max-age=60");
+    assert!(head.bytes().await.expect("reading body").is_empty());
+}
+
+#[tokio::test]
+async fn GIVEN_no_domain_WHEN_head_domain_THEN_status_code_404() {
+    // GIVEN
+    let test_srv = new_server(CacheConfig::default()).await;
+
+    // WHEN
+    let response = client()
+        .head(format!("{}/domain/bar.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 404);
+}
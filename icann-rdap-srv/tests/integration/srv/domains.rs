@@ -0,0 +1,161 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{qtype::QueryType, request::rdap_request};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, nameserver::Nameserver, RdapResponse},
+};
+use icann_rdap_srv::storage::StoreOps;
+
+use crate::test_jig::SrvTestJig;
+
+#[tokio::test]
+async fn GIVEN_server_with_domains_WHEN_search_domains_by_name_THEN_matching_domains_returned() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    for ldh_name in ["foo.example", "bar.example", "foo.example.net"] {
+        tx.add_domain(&Domain::basic().ldh_name(ldh_name).build())
+            .await
+            .expect("add domain in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::DomainNameSearch("*.example".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::DomainSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 2);
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_search_domains_without_name_THEN_status_code_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!("{}/domains", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn GIVEN_server_with_domains_WHEN_search_domains_by_ns_name_THEN_matching_domains_returned() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .nameservers(vec![Nameserver::basic()
+                .ldh_name("ns1.foo.example")
+                .build()
+                .expect("building nameserver")])
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.add_domain(&Domain::basic().ldh_name("bar.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::DomainNsNameSearch("ns1.*".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::DomainSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+}
+
+#[tokio::test]
+async fn GIVEN_server_with_domains_WHEN_search_domains_by_ns_ip_THEN_matching_domains_returned() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .nameservers(vec![Nameserver::basic()
+                .ldh_name("ns1.foo.example")
+                .address("2001:db8::1".to_string())
+                .build()
+                .expect("building nameserver")])
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::DomainNsIpSearch("2001:db8::1".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::DomainSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_search_domains_by_invalid_ns_ip_THEN_status_code_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!("{}/domains?nsIp=not.an.ip", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -0,0 +1,79 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{qtype::QueryType, request::rdap_request};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    contact::Contact,
+    response::{entity::Entity, RdapResponse},
+};
+use icann_rdap_srv::storage::StoreOps;
+
+use crate::test_jig::SrvTestJig;
+
+#[tokio::test]
+async fn GIVEN_server_with_entities_WHEN_search_entities_by_name_THEN_matching_entities_returned() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    for (handle, full_name) in [("ALICE-1", "Alice Example"), ("BOB-1", "Bob Example")] {
+        tx.add_entity(
+            &Entity::basic()
+                .handle(handle)
+                .contact(Contact::builder().full_name(full_name).build())
+                .build(),
+        )
+        .await
+        .expect("add entity in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::EntityNameSearch("Alice*".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::EntitySearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+}
+
+#[tokio::test]
+async fn GIVEN_server_with_entities_WHEN_search_entities_by_handle_THEN_matching_entities_returned()
+{
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    for handle in ["ALICE-1", "ALICE-2", "BOB-1"] {
+        tx.add_entity(&Entity::basic().handle(handle).build())
+            .await
+            .expect("add entity in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::EntityHandleSearch("ALICE-*".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::EntitySearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 2);
+}
@@ -0,0 +1,131 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::domain::Domain,
+};
+use icann_rdap_srv::{health::Health, storage::StoreOps};
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::test_jig::SrvTestJig;
+
+fn client() -> Client {
+    let client_config = ClientConfig::builder().https_only(false).build();
+    create_client(&client_config).expect("creating client")
+}
+
+/// Gets the URL of a health check, which is outside of the RDAP base.
+fn health_url(test_srv: &SrvTestJig, check: &str) -> String {
+    let base = test_srv.rdap_base.trim_end_matches("/rdap");
+    format!("{base}/health/{check}")
+}
+
+#[tokio::test]
+async fn GIVEN_data_not_loaded_WHEN_live_THEN_ok() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_with_health(Health::default(), false).await;
+
+    // WHEN
+    let response = client()
+        .get(health_url(&test_srv, "live"))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn GIVEN_data_not_loaded_WHEN_ready_THEN_unavailable() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_with_health(Health::default(), false).await;
+
+    // WHEN
+    let response = client()
+        .get(health_url(&test_srv, "ready"))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 503);
+    let json = response.json::<Value>().await.expect("health json");
+    assert_eq!(json["data_loaded"], false);
+    assert!(json.get("last_reload").is_none());
+}
+
+#[tokio::test]
+async fn GIVEN_data_loaded_WHEN_ready_THEN_ok_with_object_counts() {
+    // GIVEN
+    let health = Health::default();
+    let test_srv = SrvTestJig::new_with_health(health.clone(), false).await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+    health.set_object_counts(
+        test_srv
+            .mem
+            .get_object_counts()
+            .await
+            .expect("counting objects"),
+    );
+    health.set_data_loaded();
+
+    // WHEN
+    let response = client()
+        .get(health_url(&test_srv, "ready"))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    let json = response.json::<Value>().await.expect("health json");
+    assert_eq!(json["ready"], true);
+    assert!(json["last_reload"].is_string());
+    assert_eq!(json["object_counts"]["domains"], 1);
+    assert!(json.get("bootstrapped").is_none());
+}
+
+#[tokio::test]
+async fn GIVEN_bootstrap_not_finished_WHEN_ready_THEN_unavailable() {
+    // GIVEN
+    let health = Health::default();
+    let test_srv = SrvTestJig::new_with_health(health.clone(), true).await;
+    health.set_data_loaded();
+
+    // WHEN
+    let response = client()
+        .get(health_url(&test_srv, "ready"))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 503);
+    let json = response.json::<Value>().await.expect("health json");
+    assert_eq!(json["bootstrapped"], false);
+}
+
+#[tokio::test]
+async fn GIVEN_bootstrap_finished_WHEN_ready_THEN_ok() {
+    // GIVEN
+    let health = Health::default();
+    let test_srv = SrvTestJig::new_with_health(health.clone(), true).await;
+    health.set_data_loaded();
+    health.set_bootstrapped();
+
+    // WHEN
+    let response = client()
+        .get(health_url(&test_srv, "ready"))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+}
@@ -0,0 +1,120 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, error::Error},
+};
+use icann_rdap_srv::{
+    config::{ListenConfig, ServiceConfig},
+    metrics::MetricsListener,
+    storage::{
+        data::{load_data, DomainId},
+        StoreOps,
+    },
+};
+use reqwest::Client;
+use test_dir::{DirBuilder, FileType, TestDir};
+
+use crate::test_jig::SrvTestJig;
+
+fn client() -> Client {
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    create_client(&client_config).expect("creating client")
+}
+
+async fn scrape() -> String {
+    let metrics_listener =
+        MetricsListener::listen(&ListenConfig::builder().ip_addr("127.0.0.1").build())
+            .await
+            .expect("listening for metrics");
+    let metrics_url = format!("http://{}/metrics", metrics_listener.local_addr);
+    tokio::spawn(metrics_listener.start());
+    client()
+        .get(metrics_url)
+        .send()
+        .await
+        .expect("scraping metrics")
+        .text()
+        .await
+        .expect("metrics text")
+}
+
+#[tokio::test]
+async fn GIVEN_domain_queries_WHEN_scrape_metrics_THEN_requests_counted_by_route_and_status() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+    for name in ["foo.example", "bar.example"] {
+        client()
+            .get(format!("{}/domain/{name}", test_srv.rdap_base))
+            .send()
+            .await
+            .expect("quering server");
+    }
+
+    // WHEN
+    let metrics = scrape().await;
+
+    // THEN
+    assert!(metrics.contains(r#"rdap_requests_total{route="domain",status="200"}"#));
+    assert!(metrics.contains(r#"rdap_requests_total{route="domain",status="404"}"#));
+    assert!(metrics.contains(r#"rdap_request_duration_seconds_count{route="domain"}"#));
+}
+
+#[tokio::test]
+async fn GIVEN_bootstrap_redirect_WHEN_scrape_metrics_THEN_redirect_counted() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_bootstrap().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain_err(
+        &DomainId::builder().ldh_name("example").build(),
+        &Error::redirect().url("https://example.net/").build(),
+    )
+    .await
+    .expect("add domain redirect");
+    tx.commit().await.expect("tx commit");
+    client()
+        .get(format!("{}/domain/foo.example", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // WHEN
+    let metrics = scrape().await;
+
+    // THEN
+    assert!(metrics.contains(r#"rdap_bootstrap_redirects_total{object="domain"}"#));
+    assert!(metrics.contains(r#"rdap_requests_total{route="domain",status="307"}"#));
+}
+
+#[tokio::test]
+async fn GIVEN_data_loaded_WHEN_scrape_metrics_THEN_object_counts() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let test_dir = TestDir::temp().create("foo.json", FileType::EmptyFile);
+    let domain = Domain::basic().ldh_name("foo.example").build();
+    std::fs::write(
+        test_dir.path("foo.json"),
+        serde_json::to_string(&domain).expect("serializing domain"),
+    )
+    .expect("writing domain");
+    let config = ServiceConfig::new_non_server(test_dir.root().to_string_lossy().to_string(), None)
+        .expect("service config");
+    load_data(&config, &test_srv.mem, false)
+        .await
+        .expect("loading data");
+
+    // WHEN
+    let metrics = scrape().await;
+
+    // THEN
+    assert!(metrics.contains(r#"rdap_objects{class="domain"}"#));
+    assert!(metrics.contains(r#"rdap_objects{class="ip network"}"#));
+}
@@ -1,4 +1,19 @@
+mod access;
+mod admin;
 mod bootstrap;
+mod caching;
 mod domain;
+mod domains;
+mod entities;
+mod health;
+mod metrics;
+mod nameservers;
+mod openidc;
+mod paging;
+mod rate_limit;
+mod redaction;
 mod redirect;
+mod reverse_search;
 mod srvhelp;
+mod subsetting;
+mod tls;
@@ -0,0 +1,113 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{qtype::QueryType, request::rdap_request};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{nameserver::Nameserver, RdapResponse},
+};
+use icann_rdap_srv::storage::StoreOps;
+
+use crate::test_jig::SrvTestJig;
+
+#[tokio::test]
+async fn GIVEN_server_with_nameservers_WHEN_search_nameservers_by_name_THEN_matching_nameservers_returned(
+) {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    for ldh_name in ["ns1.foo.example", "ns2.foo.example", "ns1.bar.example"] {
+        tx.add_nameserver(
+            &Nameserver::basic()
+                .ldh_name(ldh_name)
+                .build()
+                .expect("building nameserver"),
+        )
+        .await
+        .expect("add nameserver in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::NameserverNameSearch("*.foo.example".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::NameserverSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 2);
+}
+
+#[tokio::test]
+async fn GIVEN_server_with_nameservers_WHEN_search_nameservers_by_cidr_THEN_matching_nameservers_returned(
+) {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_nameserver(
+        &Nameserver::basic()
+            .ldh_name("ns1.foo.example")
+            .address("192.0.2.1".to_string())
+            .build()
+            .expect("building nameserver"),
+    )
+    .await
+    .expect("add nameserver in tx");
+    tx.add_nameserver(
+        &Nameserver::basic()
+            .ldh_name("ns1.bar.example")
+            .address("198.51.100.1".to_string())
+            .build()
+            .expect("building nameserver"),
+    )
+    .await
+    .expect("add nameserver in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::NameserverIpSearch("192.0.2.0/24".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::NameserverSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_search_nameservers_by_invalid_ip_THEN_status_code_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!("{}/nameservers?ip=192.0.2", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -0,0 +1,437 @@
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{
+        help::Help,
+        openidc::SessionResponse,
+        types::{Extension, Notice, NoticeOrRemark},
+    },
+};
+use icann_rdap_srv::{
+    rdap::{access::AccessTier, openidc::ProviderConfig},
+    storage::StoreOps,
+};
+use reqwest::{
+    header::{COOKIE, SET_COOKIE},
+    Client,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
+
+use crate::test_jig::SrvTestJig;
+
+/// Starts an OpenID provider that logs in every end-user as `alice`, giving its issuer
+/// identifier. The authorization code it gives is the nonce and the PKCE code challenge of
+/// the login, so that the token endpoint can put the nonce in the ID token and check the
+/// code verifier.
+async fn mock_provider() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("binding provider");
+    let issuer = format!(
+        "http://{}",
+        listener.local_addr().expect("provider address")
+    );
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/authorize", get(authorize))
+        .route("/token", post(token))
+        .with_state(issuer.clone());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("starting provider");
+    });
+    issuer
+}
+
+async fn discovery(State(issuer): State<String>) -> Response {
+    Json(json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{issuer}/authorize"),
+        "token_endpoint": format!("{issuer}/token"),
+    }))
+    .into_response()
+}
+
+async fn authorize(Query(params): Query<HashMap<String, String>>) -> Response {
+    assert_eq!(params["code_challenge_method"], "S256");
+    let redirect = format!(
+        "{}?code={}.{}&state={}",
+        params["redirect_uri"], params["nonce"], params["code_challenge"], params["state"]
+    );
+    Redirect::to(&redirect).into_response()
+}
+
+async fn token(
+    State(issuer): State<String>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let exp = chrono::Utc::now().timestamp() + 3600;
+    let mut claims = json!({
+        "iss": issuer,
+        "aud": "rdap",
+        "exp": exp,
+        "sub": "alice",
+        "name": "Alice Example",
+    });
+    let expires_in = match form["grant_type"].as_str() {
+        "authorization_code" => {
+            let (nonce, code_challenge) = form["code"].split_once('.').expect("code");
+            let code_verifier = form.get("code_verifier").map(String::as_bytes);
+            if code_verifier.map(|verifier| URL_SAFE_NO_PAD.encode(Sha256::digest(verifier)))
+                != Some(code_challenge.to_string())
+            {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": "invalid_grant" })),
+                )
+                    .into_response();
+            }
+            claims["nonce"] = json!(nonce);
+            300
+        }
+        _ => 600,
+    };
+    let id_token = format!(
+        "{}.{}.",
+        URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+    Json(json!({
+        "access_token": "access",
+        "token_type": "Bearer",
+        "id_token": id_token,
+        "refresh_token": "refresh",
+        "expires_in": expires_in,
+    }))
+    .into_response()
+}
+
+fn provider(issuer: &str) -> ProviderConfig {
+    ProviderConfig::builder()
+        .name("Mock Provider")
+        .issuer(issuer)
+        .client_id("rdap")
+        .client_secret("secret")
+        .default(true)
+        .tier(AccessTier::Registrar)
+        .build()
+}
+
+fn client() -> Client {
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    create_client(&client_config).expect("creating client")
+}
+
+/// Gets the name and value of the cookie set by a response with the name.
+fn cookie(response: &reqwest::Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|set_cookie| set_cookie.to_str().ok()?.split(';').next())
+        .find(|cookie| cookie.starts_with(&format!("{name}=")))
+        .map(str::to_string)
+}
+
+/// Starts a login and authorizes it at the provider, giving the login cookie and the
+/// URL the provider returns the end-user to.
+async fn authorize_login(client: &Client, rdap_base: &str) -> (String, String) {
+    let to_provider = client
+        .get(format!("{rdap_base}/login"))
+        .send()
+        .await
+        .expect("starting login");
+    assert_eq!(to_provider.status().as_u16(), 302);
+    let login_cookie = cookie(&to_provider, "login").expect("no login cookie");
+    let to_provider = to_provider.headers()["location"]
+        .to_str()
+        .expect("location")
+        .to_string();
+    let to_server = client.get(to_provider).send().await.expect("authorizing");
+    let to_server = to_server.headers()["location"]
+        .to_str()
+        .expect("location")
+        .to_string();
+    (login_cookie, to_server)
+}
+
+/// Logs in through the provider, giving the session cookie.
+async fn login(client: &Client, rdap_base: &str) -> (String, SessionResponse) {
+    let (login_cookie, to_server) = authorize_login(client, rdap_base).await;
+    let response = client
+        .get(to_server)
+        .header(COOKIE, login_cookie)
+        .send()
+        .await
+        .expect("finishing login");
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(cookie(&response, "login").as_deref(), Some("login="));
+    let cookie = cookie(&response, "id").expect("no session cookie");
+    let session = response
+        .json::<SessionResponse>()
+        .await
+        .expect("parsing login response");
+    (cookie, session)
+}
+
+#[tokio::test]
+async fn GIVEN_provider_WHEN_login_THEN_session_with_user_claims() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+
+    // WHEN
+    let (cookie, actual) = login(&client(), &test_srv.rdap_base).await;
+
+    // THEN
+    assert!(cookie.starts_with("id="));
+    assert!(actual
+        .common
+        .rdap_conformance
+        .expect("no rdap conformance")
+        .contains(&Extension::from("roidc1")));
+    let session = actual.session.expect("no session");
+    assert_eq!(session.user_claims["sub"], "alice");
+    let session_info = session.session_info.expect("no session info");
+    assert!(session_info.token_refresh);
+    assert!(session_info.token_expiration <= 300);
+}
+
+#[tokio::test]
+async fn GIVEN_unknown_issuer_WHEN_login_THEN_status_code_400() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+
+    // WHEN
+    let response = client()
+        .get(format!(
+            "{}/login?roidc1_iss=https://other.example.com",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("starting login");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn GIVEN_unknown_login_state_WHEN_login_returns_THEN_status_code_401() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+
+    // WHEN
+    let response = client()
+        .get(format!(
+            "{}/login?code=abc&state=forged",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("finishing login");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn GIVEN_login_started_by_other_browser_WHEN_login_returns_THEN_status_code_401() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+    let client = client();
+    let (_, to_server) = authorize_login(&client, &test_srv.rdap_base).await;
+    let (other_login_cookie, _) = authorize_login(&client, &test_srv.rdap_base).await;
+
+    // WHEN
+    let response = client
+        .get(to_server)
+        .header(COOKIE, other_login_cookie)
+        .send()
+        .await
+        .expect("finishing login");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(cookie(&response, "id").is_none());
+}
+
+#[tokio::test]
+async fn GIVEN_session_WHEN_session_status_THEN_session() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+    let client = client();
+    let (cookie, _) = login(&client, &test_srv.rdap_base).await;
+
+    // WHEN
+    let response = client
+        .get(format!("{}/session/status", test_srv.rdap_base))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .expect("getting session status");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    let actual = response
+        .json::<SessionResponse>()
+        .await
+        .expect("parsing session response");
+    assert_eq!(
+        actual.session.expect("no session").user_claims["name"],
+        "Alice Example"
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_session_WHEN_session_refresh_THEN_expiration_extended() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+    let client = client();
+    let (cookie, _) = login(&client, &test_srv.rdap_base).await;
+
+    // WHEN
+    let response = client
+        .get(format!("{}/session/refresh", test_srv.rdap_base))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .expect("refreshing session");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    let actual = response
+        .json::<SessionResponse>()
+        .await
+        .expect("parsing session response");
+    let session_info = actual
+        .session
+        .expect("no session")
+        .session_info
+        .expect("no session info");
+    assert!(session_info.token_expiration > 300);
+}
+
+#[tokio::test]
+async fn GIVEN_session_WHEN_logout_THEN_session_ended() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+    let client = client();
+    let (cookie, _) = login(&client, &test_srv.rdap_base).await;
+
+    // WHEN
+    let response = client
+        .get(format!("{}/logout", test_srv.rdap_base))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .expect("logging out");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    let status = client
+        .get(format!("{}/session/status", test_srv.rdap_base))
+        .header(COOKIE, &cookie)
+        .send()
+        .await
+        .expect("getting session status");
+    assert_eq!(status.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_no_session_WHEN_session_status_THEN_status_code_404() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/session/status", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("getting session status");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn GIVEN_no_openidc_WHEN_login_THEN_status_code_501() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/login", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("starting login");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 501);
+}
+
+#[tokio::test]
+async fn GIVEN_provider_WHEN_query_help_THEN_providers_listed() {
+    // GIVEN
+    let issuer = mock_provider().await;
+    let test_srv = SrvTestJig::new_with_openidc(vec![provider(&issuer)]).await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    let srvhelp = Help::basic()
+        .notice(Notice(
+            NoticeOrRemark::builder()
+                .description_entry("foo".to_string())
+                .build(),
+        ))
+        .build()
+        .expect("building help");
+    tx.add_srv_help(&srvhelp, None)
+        .await
+        .expect("adding srv help");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let response = client()
+        .get(format!("{}/help", test_srv.rdap_base))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    let actual = response.json::<Help>().await.expect("parsing help");
+    assert!(actual
+        .common
+        .rdap_conformance
+        .expect("no rdap conformance")
+        .contains(&Extension::from("roidc1")));
+    assert!(actual
+        .openidc_configuration
+        .expect("no openidc configuration")
+        .issuer_identifier_supported
+        .expect("no issuer identifier support"));
+    let providers = actual.openidc_providers.expect("no providers");
+    assert_eq!(providers.len(), 1);
+    assert_eq!(providers[0].iss, issuer);
+    assert_eq!(providers[0].default, Some(true));
+}
@@ -0,0 +1,234 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{
+    qtype::QueryType,
+    request::{rdap_paged_request, rdap_url_request},
+};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, types::Extension, RdapResponse},
+};
+use icann_rdap_srv::{config::DEFAULT_SEARCH_PAGE_SIZE, storage::StoreOps};
+
+use crate::test_jig::SrvTestJig;
+
+async fn new_srv_with_domains(count: usize) -> SrvTestJig {
+    let test_srv = SrvTestJig::new().await;
+    add_domains(&test_srv, count).await;
+    test_srv
+}
+
+async fn add_domains(test_srv: &SrvTestJig, count: usize) {
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    for n in 0..count {
+        tx.add_domain(
+            &Domain::basic()
+                .ldh_name(format!("domain{n:02}.example"))
+                .build(),
+        )
+        .await
+        .expect("add domain in tx");
+    }
+    tx.commit().await.expect("tx commit");
+}
+
+#[tokio::test]
+async fn GIVEN_more_domains_than_page_size_WHEN_paged_search_THEN_all_pages_returned() {
+    // GIVEN
+    let count = DEFAULT_SEARCH_PAGE_SIZE + 5;
+    let test_srv = new_srv_with_domains(count).await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::DomainNameSearch("*.example".to_string());
+    let pages = rdap_paged_request(&test_srv.rdap_base, &query, None, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(pages.len(), 2);
+    let mut actual = vec![];
+    for page in pages {
+        let RdapResponse::DomainSearchResults(results) = page.rdap else {
+            panic!()
+        };
+        assert!(results
+            .common
+            .rdap_conformance
+            .expect("no rdap conformance")
+            .contains(&Extension("paging".to_string())));
+        let paging_metadata = results.paging_metadata.expect("no paging metadata");
+        assert_eq!(
+            paging_metadata.page_size,
+            Some(DEFAULT_SEARCH_PAGE_SIZE as u64)
+        );
+        actual.extend(results.results.into_iter().map(|domain| domain.ldh_name));
+    }
+    let expected = (0..count)
+        .map(|n| Some(format!("domain{n:02}.example")))
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+}
+
+#[tokio::test]
+async fn GIVEN_domains_WHEN_search_with_count_and_descending_sort_THEN_sorted_and_counted() {
+    // GIVEN
+    let test_srv = new_srv_with_domains(3).await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = rdap_url_request(
+        &format!(
+            "{}/domains?name=*.example&count=true&sort=name:d",
+            test_srv.rdap_base
+        ),
+        &client,
+    )
+    .await
+    .expect("quering server");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(
+        results
+            .paging_metadata
+            .expect("no paging metadata")
+            .total_count,
+        Some(3)
+    );
+    assert_eq!(
+        results
+            .sorting_metadata
+            .expect("no sorting metadata")
+            .current_sort
+            .as_deref(),
+        Some("name:d")
+    );
+    let actual = results
+        .results
+        .iter()
+        .map(|domain| domain.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        ["domain02.example", "domain01.example", "domain00.example"]
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_search_with_unknown_sort_THEN_status_code_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!(
+            "{}/domains?name=*.example&sort=color",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn GIVEN_more_domains_than_max_results_WHEN_descending_sort_THEN_all_pages_sorted() {
+    // GIVEN
+    let max_results = 10;
+    let count = 3 * max_results;
+    let test_srv = SrvTestJig::new_with_max_search_results(max_results).await;
+    add_domains(&test_srv, count).await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let origin = test_srv.rdap_base.trim_end_matches("/rdap");
+    let mut url = Some(format!(
+        "{}/domains?name=*.example&count=true&sort=name:d",
+        test_srv.rdap_base
+    ));
+    let mut pages = vec![];
+    while let Some(page_url) = url {
+        let response = rdap_url_request(&page_url, &client)
+            .await
+            .expect("quering server");
+        let RdapResponse::DomainSearchResults(results) = response.rdap else {
+            panic!()
+        };
+        let paging_metadata = results.paging_metadata.expect("no paging metadata");
+        url = paging_metadata
+            .links
+            .iter()
+            .flatten()
+            .find(|link| link.rel.as_deref() == Some("next"))
+            .map(|link| format!("{origin}{}", link.href));
+        pages.push((paging_metadata.total_count, results.results));
+    }
+
+    // THEN
+    assert_eq!(pages.len(), 3);
+    assert!(pages
+        .iter()
+        .all(|(total_count, results)| *total_count == Some(count as u64)
+            && results.len() == max_results));
+    let actual = pages
+        .into_iter()
+        .flat_map(|(_, results)| results)
+        .map(|domain| domain.ldh_name)
+        .collect::<Vec<_>>();
+    let expected = (0..count)
+        .rev()
+        .map(|n| Some(format!("domain{n:02}.example")))
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_search_with_invalid_count_THEN_rdap_error_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!(
+            "{}/domains?name=*.example&count=maybe",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+    let json = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("rdap error");
+    assert_eq!(json["errorCode"], 400);
+}
@@ -0,0 +1,88 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{error::Error, RdapResponse},
+};
+use icann_rdap_srv::config::{RateLimit, RateLimitConfig};
+
+use crate::test_jig::SrvTestJig;
+
+#[tokio::test]
+async fn GIVEN_search_rate_limit_WHEN_searches_exceed_burst_THEN_status_code_429() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_rate_limited(
+        RateLimitConfig::builder()
+            .search(RateLimit {
+                per_minute: 1,
+                burst: 2,
+            })
+            .build(),
+    )
+    .await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let url = format!("{}/domains?name=*.example", test_srv.rdap_base);
+    let mut statuses = vec![];
+    for _ in 0..3 {
+        let response = client.get(&url).send().await.expect("quering server");
+        statuses.push(response.status().as_u16());
+    }
+    let response = client.get(&url).send().await.expect("quering server");
+
+    // THEN
+    assert_eq!(statuses, [200, 200, 429]);
+    assert_eq!(response.status().as_u16(), 429);
+    let retry_after = response
+        .headers()
+        .get("retry-after")
+        .expect("no retry-after header")
+        .to_str()
+        .expect("retry-after not a string")
+        .parse::<u64>()
+        .expect("retry-after not seconds");
+    assert!(retry_after > 0);
+    let rdap = response
+        .json::<RdapResponse>()
+        .await
+        .expect("error body not RDAP");
+    let RdapResponse::ErrorResponse(Error { error_code, .. }) = rdap else {
+        panic!("not an RDAP error")
+    };
+    assert_eq!(error_code, 429);
+}
+
+#[tokio::test]
+async fn GIVEN_search_rate_limit_WHEN_lookups_exceed_search_burst_THEN_not_limited() {
+    // GIVEN
+    let test_srv = SrvTestJig::new_rate_limited(
+        RateLimitConfig::builder()
+            .search(RateLimit {
+                per_minute: 1,
+                burst: 1,
+            })
+            .build(),
+    )
+    .await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let url = format!("{}/domain/foo.example", test_srv.rdap_base);
+    let mut statuses = vec![];
+    for _ in 0..3 {
+        let response = client.get(&url).send().await.expect("quering server");
+        statuses.push(response.status().as_u16());
+    }
+
+    // THEN
+    assert_eq!(statuses, [404, 404, 404]);
+}
@@ -0,0 +1,146 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{qtype::QueryType, request::rdap_request};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    contact::{Contact, Email},
+    response::{domain::Domain, entity::Entity, types::Extension, RdapResponse},
+};
+use icann_rdap_srv::{rdap::redaction::RedactionPolicy, storage::StoreOps};
+use test_dir::{DirBuilder, FileType, TestDir};
+
+use crate::test_jig::SrvTestJig;
+
+fn redaction_policy() -> RedactionPolicy {
+    let test_dir = TestDir::temp().create("policy.json", FileType::EmptyFile);
+    let path = test_dir.path("policy.json");
+    std::fs::write(
+        &path,
+        r#"{
+            "rules": [
+                {
+                    "name": "Registrant Email",
+                    "tiers": ["public"],
+                    "role": "registrant",
+                    "property": "email",
+                    "method": "removal",
+                    "reason": "Server policy"
+                }
+            ]
+        }"#,
+    )
+    .expect("writing redaction policy file");
+    RedactionPolicy::load(path.to_str().expect("path not a string"))
+        .expect("loading redaction policy")
+}
+
+/// Starts a server with the redaction policy and a domain with a registrant.
+async fn new_server_with_domain() -> SrvTestJig {
+    let test_srv = SrvTestJig::new_with_redaction_policy(redaction_policy()).await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .entity(
+                Entity::basic()
+                    .handle("alice")
+                    .role("registrant")
+                    .contact(
+                        Contact::builder()
+                            .full_name("Alice")
+                            .emails(vec![Email::builder().email("alice@example.com").build()])
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+    test_srv
+}
+
+fn client() -> reqwest::Client {
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    create_client(&client_config).expect("creating client")
+}
+
+#[tokio::test]
+async fn GIVEN_redaction_policy_WHEN_anonymous_domain_lookup_THEN_registrant_email_redacted() {
+    // GIVEN
+    let test_srv = new_server_with_domain().await;
+
+    // WHEN
+    let client = client();
+    let query = QueryType::Domain("foo.example".to_string());
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    let RdapResponse::Domain(domain) = response.rdap else {
+        panic!("not a domain")
+    };
+    assert!(domain
+        .common
+        .rdap_conformance
+        .expect("no rdap conformance")
+        .contains(&Extension("redacted".to_string())));
+    let redacted = domain.object_common.redacted.expect("no redacted");
+    assert_eq!(
+        redacted[0].name.type_field.as_deref(),
+        Some("Registrant Email")
+    );
+    let contact = domain.object_common.entities.expect("no entities")[0]
+        .contact()
+        .expect("no contact");
+    assert_eq!(contact.full_name.as_deref(), Some("Alice"));
+    assert!(contact.emails.is_none());
+}
+
+#[tokio::test]
+async fn GIVEN_redacted_email_WHEN_anonymous_reverse_search_by_email_THEN_forbidden() {
+    // GIVEN
+    let test_srv = new_server_with_domain().await;
+
+    // WHEN
+    let response = client()
+        .get(format!(
+            "{}/domains/reverse_search/entity?email=alice*",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 403);
+    let body = response.text().await.expect("response body");
+    assert!(!body.contains("foo.example"));
+}
+
+#[tokio::test]
+async fn GIVEN_redacted_email_WHEN_anonymous_reverse_search_by_name_THEN_domain_found() {
+    // GIVEN
+    let test_srv = new_server_with_domain().await;
+
+    // WHEN
+    let response = client()
+        .get(format!(
+            "{}/domains/reverse_search/entity?fn=Ali*",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.text().await.expect("response body");
+    assert!(body.contains("foo.example"));
+    assert!(!body.contains("alice@example.com"));
+}
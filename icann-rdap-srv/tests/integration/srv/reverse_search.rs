@@ -0,0 +1,88 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{
+    qtype::{QueryType, RelatedEntity},
+    request::rdap_request,
+};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, entity::Entity, types::Extension, RdapResponse},
+};
+use icann_rdap_srv::storage::StoreOps;
+
+use crate::test_jig::SrvTestJig;
+
+#[tokio::test]
+async fn GIVEN_server_with_domains_WHEN_reverse_search_domains_by_entity_THEN_related_domains_returned(
+) {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    for (ldh_name, role) in [("foo.example", "registrant"), ("bar.example", "technical")] {
+        tx.add_domain(
+            &Domain::basic()
+                .ldh_name(ldh_name)
+                .entity(
+                    Entity::basic()
+                        .handle("ALICE-1")
+                        .role(role.to_string())
+                        .build(),
+                )
+                .build(),
+        )
+        .await
+        .expect("add domain in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::DomainsByEntity(RelatedEntity {
+        handle: Some("ALICE-1".to_string()),
+        role: Some("registrant".to_string()),
+        ..Default::default()
+    });
+    let response = rdap_request(&test_srv.rdap_base, &query, &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.http_data.status_code, 200);
+    let RdapResponse::DomainSearchResults(results) = response.rdap else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+    assert!(results
+        .common
+        .rdap_conformance
+        .expect("no rdap conformance")
+        .contains(&Extension("reverse_search".to_string())));
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_reverse_search_without_properties_THEN_status_code_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!(
+            "{}/nameservers/reverse_search/entity",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
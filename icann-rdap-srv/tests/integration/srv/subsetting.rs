@@ -0,0 +1,87 @@
+#![allow(non_snake_case)]
+
+use icann_rdap_client::query::{
+    qtype::{FieldSet, QueryType},
+    request::rdap_paged_request,
+};
+use icann_rdap_common::{
+    client::{create_client, ClientConfig},
+    response::{domain::Domain, types::Extension, RdapResponse},
+};
+use icann_rdap_srv::storage::StoreOps;
+
+use crate::test_jig::SrvTestJig;
+
+#[tokio::test]
+async fn GIVEN_domain_WHEN_search_with_id_field_set_THEN_only_names_returned() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .handle("foo-example-1")
+            .status("active")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let query = QueryType::DomainNameSearch("*.example".to_string());
+    let mut pages = rdap_paged_request(&test_srv.rdap_base, &query, Some(FieldSet::Id), &client)
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(pages.len(), 1);
+    let RdapResponse::DomainSearchResults(results) = pages.remove(0).rdap else {
+        panic!()
+    };
+    assert!(results
+        .common
+        .rdap_conformance
+        .expect("no rdap conformance")
+        .contains(&Extension("subsetting".to_string())));
+    assert_eq!(
+        results
+            .subsetting_metadata
+            .expect("no subsetting metadata")
+            .current_field_set,
+        "id"
+    );
+    let domain = results.results.first().expect("no domains");
+    assert_eq!(domain.ldh_name.as_deref(), Some("foo.example"));
+    assert!(domain.object_common.handle.is_none());
+    assert!(domain.object_common.status.is_none());
+}
+
+#[tokio::test]
+async fn GIVEN_server_WHEN_search_with_unknown_field_set_THEN_status_code_400() {
+    // GIVEN
+    let test_srv = SrvTestJig::new().await;
+
+    // WHEN
+    let client_config = ClientConfig::builder()
+        .https_only(false)
+        .follow_redirects(false)
+        .build();
+    let client = create_client(&client_config).expect("creating client");
+    let response = client
+        .get(format!(
+            "{}/domains?name=*.example&fieldSet=tiny",
+            test_srv.rdap_base
+        ))
+        .send()
+        .await
+        .expect("quering server");
+
+    // THEN
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -0,0 +1,141 @@
+#![allow(non_snake_case)]
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
+
+use icann_rdap_common::response::domain::Domain;
+use icann_rdap_srv::{
+    config::{ListenConfig, TlsConfig},
+    server::Listener,
+    storage::StoreOps,
+};
+use rcgen::{Certificate, CertificateParams, SanType};
+use test_dir::{DirBuilder, TestDir};
+
+use crate::test_jig::SrvTestJig;
+
+/// Creates a self-signed certificate for 127.0.0.1, giving the certificate and key PEM.
+fn self_signed() -> (String, String) {
+    let mut params = CertificateParams::default();
+    params.subject_alt_names = vec![SanType::IpAddress(IpAddr::V4(Ipv4Addr::LOCALHOST))];
+    let cert = Certificate::from_params(params).expect("creating certificate");
+    (
+        cert.serialize_pem().expect("serializing certificate"),
+        cert.serialize_private_key_pem(),
+    )
+}
+
+fn write_cert(test_dir: &TestDir, (cert, key): &(String, String)) {
+    std::fs::write(test_dir.path("cert.pem"), cert).expect("writing certificate");
+    std::fs::write(test_dir.path("key.pem"), key).expect("writing key");
+}
+
+fn tls_config(test_dir: &TestDir) -> TlsConfig {
+    TlsConfig::builder()
+        .cert_file(test_dir.path("cert.pem").to_string_lossy())
+        .key_file(test_dir.path("key.pem").to_string_lossy())
+        .build()
+}
+
+/// Gets a domain using a client trusting only the certificate.
+async fn get_domain(rdap_base: &str, cert: &str) -> Result<u16, reqwest::Error> {
+    let client = reqwest::Client::builder()
+        .tls_built_in_root_certs(false)
+        .add_root_certificate(reqwest::Certificate::from_pem(cert.as_bytes())?)
+        .build()?;
+    let response = client
+        .get(format!("{rdap_base}/domain/foo.example"))
+        .send()
+        .await?;
+    Ok(response.status().as_u16())
+}
+
+#[tokio::test]
+async fn GIVEN_tls_WHEN_query_domain_THEN_served_over_https() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let cert = self_signed();
+    write_cert(&test_dir, &cert);
+    let test_srv = SrvTestJig::new_with_tls(tls_config(&test_dir)).await;
+    let mut tx = test_srv.mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = get_domain(&test_srv.rdap_base, &cert.0).await;
+
+    // THEN
+    assert!(test_srv.rdap_base.starts_with("https://"));
+    assert_eq!(actual.expect("quering server"), 200);
+}
+
+#[tokio::test]
+async fn GIVEN_tls_WHEN_certificate_replaced_THEN_new_certificate_served() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let old_cert = self_signed();
+    write_cert(&test_dir, &old_cert);
+    let test_srv = SrvTestJig::new_with_tls(tls_config(&test_dir)).await;
+    let new_cert = self_signed();
+
+    // WHEN
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    write_cert(&test_dir, &new_cert);
+
+    // THEN
+    let mut reloaded = false;
+    for _ in 0..50 {
+        if get_domain(&test_srv.rdap_base, &new_cert.0).await.is_ok() {
+            reloaded = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(reloaded);
+    assert!(get_domain(&test_srv.rdap_base, &old_cert.0).await.is_err());
+}
+
+#[tokio::test]
+async fn GIVEN_tls_WHEN_certificate_renamed_over_THEN_new_certificate_served() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+    let old_cert = self_signed();
+    write_cert(&test_dir, &old_cert);
+    let test_srv = SrvTestJig::new_with_tls(tls_config(&test_dir)).await;
+    let new_cert = self_signed();
+
+    // WHEN
+    std::fs::write(test_dir.path("cert.pem.new"), &new_cert.0).expect("writing certificate");
+    std::fs::write(test_dir.path("key.pem.new"), &new_cert.1).expect("writing key");
+    std::fs::rename(test_dir.path("key.pem.new"), test_dir.path("key.pem")).expect("renaming key");
+    std::fs::rename(test_dir.path("cert.pem.new"), test_dir.path("cert.pem"))
+        .expect("renaming certificate");
+
+    // THEN
+    let mut reloaded = false;
+    for _ in 0..50 {
+        if get_domain(&test_srv.rdap_base, &new_cert.0).await.is_ok() {
+            reloaded = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(reloaded);
+}
+
+#[tokio::test]
+async fn GIVEN_missing_certificate_WHEN_listen_THEN_error() {
+    // GIVEN
+    let test_dir = TestDir::temp();
+
+    // WHEN
+    let actual =
+        Listener::listen(&ListenConfig::builder().tls(tls_config(&test_dir)).build()).await;
+
+    // THEN
+    assert!(actual.is_err());
+}
@@ -12,11 +12,12 @@ use icann_rdap_common::response::{
 };
 use icann_rdap_srv::{
     config::{ServiceConfig, StorageType},
+    error::RdapServerError,
     storage::{
         data::{
             load_data, AutnumId, AutnumOrError::AutnumObject, DomainId, DomainOrError, EntityId,
             EntityOrError::EntityObject, NameserverId, NameserverOrError::NameserverObject,
-            NetworkId, NetworkIdType, NetworkOrError::NetworkObject, Template,
+            NetworkId, NetworkIdType, NetworkOrError::NetworkObject, ObjectIds, Template,
         },
         mem::{config::MemConfig, ops::Mem},
         StoreOps,
@@ -574,3 +575,209 @@ async fn GIVEN_data_dir_with_host_help_WHEN_mem_init_THEN_host_help_is_loaded()
         "bar"
     );
 }
+
+#[tokio::test]
+async fn GIVEN_data_dir_with_tombstone_WHEN_mem_init_THEN_tombstoned_domain_is_not_loaded() {
+    // GIVEN
+    let temp = TestDir::temp();
+    for ldh_name in ["foo.example", "bar.example"] {
+        let domain = Domain::basic().ldh_name(ldh_name).build();
+        std::fs::write(
+            temp.path(&format!("{ldh_name}.json")),
+            serde_json::to_string(&domain).expect("serializing domain"),
+        )
+        .expect("writing file");
+    }
+    let tombstone = ObjectIds {
+        domains: vec![DomainId::builder().ldh_name("foo.example").build()],
+        ..Default::default()
+    };
+    std::fs::write(
+        temp.path("foo_example.tombstone"),
+        serde_json::to_string(&tombstone).expect("serializing tombstone"),
+    )
+    .expect("writing file");
+
+    // WHEN
+    let mem = new_and_init_mem(temp.root().to_string_lossy().to_string()).await;
+
+    // THEN
+    let actual = mem
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404);
+    let actual = mem
+        .get_domain_by_ldh("bar.example")
+        .await
+        .expect("getting domain by ldh");
+    assert!(matches!(actual, RdapResponse::Domain(_)));
+}
+
+#[tokio::test]
+async fn GIVEN_loaded_domain_and_tombstone_WHEN_update_THEN_domain_is_deleted() {
+    // GIVEN
+    let temp = TestDir::temp();
+    let domain = Domain::basic().ldh_name("foo.example").build();
+    std::fs::write(
+        temp.path("foo_example.json"),
+        serde_json::to_string(&domain).expect("serializing domain"),
+    )
+    .expect("writing file");
+    let data_dir = temp.root().to_string_lossy().to_string();
+    let mem = new_and_init_mem(data_dir.clone()).await;
+    std::fs::remove_file(temp.path("foo_example.json")).expect("removing file");
+    std::fs::write(
+        temp.path("foo_example.tombstone"),
+        r#"{"domains":[{"ldhName":"foo.example"}]}"#,
+    )
+    .expect("writing file");
+
+    // WHEN
+    load_data(
+        &ServiceConfig::non_server()
+            .data_dir(data_dir)
+            .storage_type(StorageType::Memory(MemConfig::builder().build()))
+            .build()
+            .expect("building service config"),
+        &mem,
+        false,
+    )
+    .await
+    .expect("updating data");
+
+    // THEN
+    let actual = mem
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404);
+}
+
+fn mem_config(data_dir: &TestDir) -> ServiceConfig {
+    ServiceConfig::non_server()
+        .data_dir(data_dir.root().to_string_lossy().to_string())
+        .storage_type(StorageType::Memory(MemConfig::builder().build()))
+        .build()
+        .expect("building service config")
+}
+
+#[tokio::test]
+async fn GIVEN_data_dir_with_ndjson_WHEN_mem_init_THEN_objects_and_templates_are_loaded() {
+    // GIVEN
+    let temp = TestDir::temp();
+    let template = Template::Domain {
+        domain: DomainOrError::DomainObject(Domain::basic().ldh_name("example").build()),
+        ids: vec![DomainId::builder().ldh_name("bar.example").build()],
+    };
+    let lines = [
+        serde_json::to_string(&Domain::basic().ldh_name("foo.example").build())
+            .expect("serializing domain"),
+        String::new(),
+        serde_json::to_string(&Entity::basic().handle("foo").build()).expect("serializing entity"),
+        serde_json::to_string(&template).expect("serializing template"),
+    ];
+    std::fs::write(temp.path("bulk.ndjson"), lines.join("\n")).expect("writing file");
+
+    // WHEN
+    let mem = new_and_init_mem(temp.root().to_string_lossy().to_string()).await;
+
+    // THEN
+    for ldh_name in ["foo.example", "bar.example"] {
+        let actual = mem
+            .get_domain_by_ldh(ldh_name)
+            .await
+            .expect("getting domain by ldh");
+        let RdapResponse::Domain(domain) = actual else {
+            panic!()
+        };
+        assert_eq!(domain.ldh_name.as_deref(), Some(ldh_name));
+    }
+    let actual = mem
+        .get_entity_by_handle("foo")
+        .await
+        .expect("getting entity by handle");
+    assert!(matches!(actual, RdapResponse::Entity(_)));
+}
+
+#[tokio::test]
+async fn GIVEN_ndjson_with_many_lines_WHEN_mem_init_THEN_later_lines_replace_earlier_lines() {
+    // GIVEN
+    let temp = TestDir::temp();
+    let mut lines = (0..2500)
+        .map(|n| {
+            serde_json::to_string(&Entity::basic().handle(format!("entity-{n}")).build())
+                .expect("serializing entity")
+        })
+        .collect::<Vec<_>>();
+    let last = Entity::basic()
+        .handle("entity-0")
+        .status("inactive")
+        .build();
+    lines.push(serde_json::to_string(&last).expect("serializing entity"));
+    std::fs::write(temp.path("bulk.ndjson"), lines.join("\n")).expect("writing file");
+
+    // WHEN
+    let mem = new_and_init_mem(temp.root().to_string_lossy().to_string()).await;
+
+    // THEN
+    let actual = mem
+        .get_entity_by_handle("entity-2499")
+        .await
+        .expect("getting entity by handle");
+    assert!(matches!(actual, RdapResponse::Entity(_)));
+    let actual = mem
+        .get_entity_by_handle("entity-0")
+        .await
+        .expect("getting entity by handle");
+    let RdapResponse::Entity(entity) = actual else {
+        panic!()
+    };
+    assert!(entity.object_common.status.is_some());
+}
+
+#[tokio::test]
+async fn GIVEN_ndjson_with_non_rdap_line_WHEN_load_THEN_error_names_line() {
+    // GIVEN
+    let temp = TestDir::temp();
+    let lines = [
+        serde_json::to_string(&Domain::basic().ldh_name("foo.example").build())
+            .expect("serializing domain"),
+        r#"{"foo":"bar"}"#.to_string(),
+    ];
+    std::fs::write(temp.path("bulk.ndjson"), lines.join("\n")).expect("writing file");
+    let mem = Mem::new(MemConfig::builder().build());
+
+    // WHEN
+    let actual = load_data(&mem_config(&temp), &mem, false).await;
+
+    // THEN
+    let Err(RdapServerError::NonRdapJsonFile(line_name)) = actual else {
+        panic!("non RDAP line loaded")
+    };
+    assert!(line_name.ends_with("bulk.ndjson:2"));
+}
+
+#[tokio::test]
+async fn GIVEN_ndjson_with_spec_error_WHEN_load_THEN_error_on_checks() {
+    // GIVEN
+    let temp = TestDir::temp();
+    std::fs::write(
+        temp.path("bulk.ndjson"),
+        serde_json::to_string(&Domain::basic().ldh_name("_.").build()).expect("serializing domain"),
+    )
+    .expect("writing file");
+    let mem = Mem::new(MemConfig::builder().build());
+
+    // WHEN
+    let actual = load_data(&mem_config(&temp), &mem, false).await;
+
+    // THEN
+    assert!(matches!(actual, Err(RdapServerError::ErrorOnChecks)));
+}
@@ -0,0 +1,2006 @@
+#![allow(non_snake_case)]
+
+use std::collections::BTreeSet;
+
+use icann_rdap_common::{
+    contact::{Contact, Email},
+    response::{
+        autnum::Autnum,
+        domain::Domain,
+        entity::Entity,
+        error::Error,
+        help::Help,
+        nameserver::Nameserver,
+        network::Network,
+        types::{Common, Extension, Notice, NoticeOrRemark, ObjectCommon},
+        RdapResponse,
+    },
+};
+use icann_rdap_srv::storage::{
+    data::{AutnumId, DomainId, EntityId, NameserverId, NetworkId, NetworkIdType},
+    search::{RelatedEntitySearch, ReverseSearchType, SearchPattern},
+    Lookup, StoreOps,
+};
+use rstest::rstest;
+
+// the tests are skipped when `new_store` gives no store, as when there is no test database.
+use super::{new_store, KEEPS_SERIALIZED};
+
+#[tokio::test]
+async fn GIVEN_store_WHEN_ping_THEN_ok() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem.ping().await;
+
+    // THEN
+    assert!(actual.is_ok());
+}
+
+#[tokio::test]
+async fn GIVEN_domain_in_mem_WHEN_new_truncate_tx_THEN_no_domain_in_mem() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let tx = mem.new_truncate_tx().await.expect("new truncate tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    let actual = mem
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+#[tokio::test]
+async fn GIVEN_concurrent_txs_WHEN_commit_THEN_no_changes_lost() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let add_domain = |ldh_name: &'static str| {
+        let mem = &mem;
+        async move {
+            let mut tx = mem.new_tx().await.expect("new transaction");
+            tx.add_domain(&Domain::basic().ldh_name(ldh_name).build())
+                .await
+                .expect("add domain in tx");
+            // lets the other transaction start before this one is committed.
+            tokio::task::yield_now().await;
+            tx.commit().await.expect("tx commit");
+        }
+    };
+
+    // WHEN
+    tokio::join!(add_domain("foo.example"), add_domain("bar.example"));
+
+    // THEN
+    for ldh_name in ["foo.example", "bar.example"] {
+        let actual = mem
+            .get_domain_by_ldh(ldh_name)
+            .await
+            .expect("getting domain by ldh");
+        assert!(matches!(actual, RdapResponse::Domain(_)));
+    }
+}
+
+#[tokio::test]
+async fn GIVEN_domain_in_mem_WHEN_lookup_domain_by_ldh_THEN_domain_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+
+    // THEN
+    let RdapResponse::Domain(domain) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        domain.ldh_name.as_ref().expect("ldhName is none"),
+        "foo.example"
+    )
+}
+
+#[tokio::test]
+async fn GIVEN_domain_in_mem_WHEN_lookup_domain_by_unicode_THEN_domain_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::idn()
+            .unicode_name("foo.example")
+            .ldh_name("foo.example")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_domain_by_unicode("foo.example")
+        .await
+        .expect("getting domain by unicode");
+
+    // THEN
+    let RdapResponse::Domain(domain) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        domain.unicode_name.as_ref().expect("unicodeName is none"),
+        "foo.example"
+    )
+}
+
+#[tokio::test]
+async fn GIVEN_no_domain_in_mem_WHEN_lookup_domain_by_ldh_THEN_404_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+#[rstest]
+#[case("foo.example", &["foo.example"])]
+#[case("FOO.EXAMPLE", &["foo.example"])]
+#[case("foo*", &["foo.example", "foo.example.net"])]
+#[case("*.example", &["bar.example", "foo.example"])]
+#[case("*.net", &["foo.example.net"])]
+#[case("baz*", &[])]
+#[tokio::test]
+async fn GIVEN_domains_in_mem_WHEN_search_domains_by_name_THEN_matching_domains_returned(
+    #[case] name: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for ldh_name in ["foo.example", "bar.example", "foo.example.net"] {
+        tx.add_domain(&Domain::basic().ldh_name(ldh_name).build())
+            .await
+            .expect("add domain in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_name(name, 100)
+        .await
+        .expect("searching domains by name");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|domain| domain.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[tokio::test]
+async fn GIVEN_idn_in_mem_WHEN_search_domains_by_unicode_name_THEN_domain_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::idn()
+            .unicode_name("café.example")
+            .ldh_name("xn--caf-dma.example")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_name("caf*", 100)
+        .await
+        .expect("searching domains by name");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+    assert_eq!(
+        results.results[0]
+            .ldh_name
+            .as_ref()
+            .expect("ldhName is none"),
+        "xn--caf-dma.example"
+    )
+}
+
+#[tokio::test]
+async fn GIVEN_idn_replaced_in_mem_WHEN_search_domains_by_old_unicode_name_THEN_no_domain_returned()
+{
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::idn()
+            .unicode_name("café.example")
+            .ldh_name("xn--caf-dma.example")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.add_domain(&Domain::basic().ldh_name("xn--caf-dma.example").build())
+        .await
+        .expect("replace domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_name("café*", 100)
+        .await
+        .expect("searching domains by name");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_domain_redirect_in_mem_WHEN_search_domains_by_name_THEN_no_domain_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain_err(
+        &DomainId::builder().ldh_name("foo.example").build(),
+        &Error::redirect().url("https://other.example").build(),
+    )
+    .await
+    .expect("add domain err in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_name("foo*", 100)
+        .await
+        .expect("searching domains by name");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_more_domains_than_max_results_in_mem_WHEN_search_domains_by_name_THEN_results_truncated(
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for ldh_name in ["a.example", "b.example", "c.example"] {
+        tx.add_domain(&Domain::basic().ldh_name(ldh_name).build())
+            .await
+            .expect("add domain in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_name("*.example", 2)
+        .await
+        .expect("searching domains by name");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 2);
+    let notices = results.common.notices.expect("no notices");
+    assert_eq!(
+        notices[0].0.title.as_deref().expect("no title"),
+        "Search Results Truncated"
+    )
+}
+
+fn domain_with_nameservers(ldh_name: &str, nameservers: &[(&str, &str)]) -> Domain {
+    Domain::basic()
+        .ldh_name(ldh_name)
+        .nameservers(
+            nameservers
+                .iter()
+                .map(|(ns_ldh_name, addr)| {
+                    Nameserver::basic()
+                        .ldh_name(*ns_ldh_name)
+                        .address(addr.to_string())
+                        .build()
+                        .expect("building nameserver")
+                })
+                .collect(),
+        )
+        .build()
+}
+
+#[rstest]
+#[case("ns1.foo.example", &["foo.example"])]
+#[case("NS1.FOO.EXAMPLE", &["foo.example"])]
+#[case("ns1.*", &["bar.example", "foo.example"])]
+#[case("*.bar.example", &["bar.example"])]
+#[case("ns3.example", &[])]
+#[tokio::test]
+async fn GIVEN_domains_in_mem_WHEN_search_domains_by_ns_ldh_name_THEN_matching_domains_returned(
+    #[case] ns_ldh_name: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&domain_with_nameservers(
+        "foo.example",
+        &[("ns1.foo.example", "192.0.2.1")],
+    ))
+    .await
+    .expect("add domain in tx");
+    tx.add_domain(&domain_with_nameservers(
+        "bar.example",
+        &[
+            ("ns1.bar.example", "192.0.2.2"),
+            ("ns2.bar.example", "2001:db8::2"),
+        ],
+    ))
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_ns_ldh_name(ns_ldh_name, 100)
+        .await
+        .expect("searching domains by nameserver name");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|domain| domain.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[rstest]
+#[case("192.0.2.1", &["foo.example"])]
+#[case("2001:db8::2", &["bar.example"])]
+#[case("2001:DB8:0:0::2", &["bar.example"])]
+#[case("192.0.2.3", &[])]
+#[tokio::test]
+async fn GIVEN_domains_in_mem_WHEN_search_domains_by_ns_ip_THEN_matching_domains_returned(
+    #[case] ns_ip: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&domain_with_nameservers(
+        "foo.example",
+        &[("ns1.foo.example", "192.0.2.1")],
+    ))
+    .await
+    .expect("add domain in tx");
+    tx.add_domain(&domain_with_nameservers(
+        "bar.example",
+        &[
+            ("ns1.bar.example", "192.0.2.2"),
+            ("ns2.bar.example", "2001:db8::2"),
+        ],
+    ))
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_domains_by_ns_ip(ns_ip, 100)
+        .await
+        .expect("searching domains by nameserver ip");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|domain| domain.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[tokio::test]
+async fn GIVEN_domain_nameservers_replaced_in_mem_WHEN_search_domains_by_old_ns_THEN_no_domain_returned(
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&domain_with_nameservers(
+        "foo.example",
+        &[("ns1.foo.example", "192.0.2.1")],
+    ))
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&domain_with_nameservers(
+        "foo.example",
+        &[("ns2.foo.example", "192.0.2.2")],
+    ))
+    .await
+    .expect("replace domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let by_name = mem
+        .search_domains_by_ns_ldh_name("ns1.foo.example", 100)
+        .await
+        .expect("searching domains by nameserver name");
+    let by_ip = mem
+        .search_domains_by_ns_ip("192.0.2.1", 100)
+        .await
+        .expect("searching domains by nameserver ip");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(by_name) = by_name else {
+        panic!()
+    };
+    assert!(by_name.results.is_empty());
+    let RdapResponse::DomainSearchResults(by_ip) = by_ip else {
+        panic!()
+    };
+    assert!(by_ip.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_entity_in_mem_WHEN_lookup_entity_by_handle_THEN_entity_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_entity(&Entity::basic().handle("foo").build())
+        .await
+        .expect("add entity in tx");
+    tx.commit().await.expect("entity tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_entity_by_handle("foo")
+        .await
+        .expect("getting entity by handle");
+
+    // THEN
+    let RdapResponse::Entity(entity) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        entity
+            .object_common
+            .handle
+            .as_ref()
+            .expect("handle is none"),
+        "foo"
+    )
+}
+
+#[tokio::test]
+async fn GIVEN_no_entity_in_mem_WHEN_lookup_entity_by_handle_THEN_404_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem
+        .get_entity_by_handle("foo")
+        .await
+        .expect("getting entity by handle");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+fn entity_with_full_name(handle: &str, full_name: &str) -> Entity {
+    Entity::basic()
+        .handle(handle)
+        .contact(Contact::builder().full_name(full_name).build())
+        .build()
+}
+
+#[rstest]
+#[case("Alice Example", &["ALICE-1"])]
+#[case("alice example", &["ALICE-1"])]
+#[case("Alice*", &["ALICE-1", "ALICE-2"])]
+#[case("*Example", &["ALICE-1", "BOB-1"])]
+#[case("Carol*", &[])]
+#[tokio::test]
+async fn GIVEN_entities_in_mem_WHEN_search_entities_by_name_THEN_matching_entities_returned(
+    #[case] name: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for entity in [
+        entity_with_full_name("ALICE-1", "Alice Example"),
+        entity_with_full_name("ALICE-2", "Alice Other"),
+        entity_with_full_name("BOB-1", "Bob Example"),
+    ] {
+        tx.add_entity(&entity).await.expect("add entity in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_entities_by_name(name, 100)
+        .await
+        .expect("searching entities by name");
+
+    // THEN
+    let RdapResponse::EntitySearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|entity| {
+            entity
+                .object_common
+                .handle
+                .as_deref()
+                .expect("handle is none")
+        })
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[rstest]
+#[case("ALICE-1", &["ALICE-1"])]
+#[case("alice-*", &["ALICE-1", "ALICE-2"])]
+#[case("*-1", &["ALICE-1", "BOB-1"])]
+#[case("CAROL-*", &[])]
+#[tokio::test]
+async fn GIVEN_entities_in_mem_WHEN_search_entities_by_handle_THEN_matching_entities_returned(
+    #[case] handle: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for entity in [
+        entity_with_full_name("ALICE-1", "Alice Example"),
+        entity_with_full_name("ALICE-2", "Alice Other"),
+        entity_with_full_name("BOB-1", "Bob Example"),
+    ] {
+        tx.add_entity(&entity).await.expect("add entity in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_entities_by_handle(handle, 100)
+        .await
+        .expect("searching entities by handle");
+
+    // THEN
+    let RdapResponse::EntitySearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|entity| {
+            entity
+                .object_common
+                .handle
+                .as_deref()
+                .expect("handle is none")
+        })
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[tokio::test]
+async fn GIVEN_entity_renamed_in_mem_WHEN_search_entities_by_old_name_THEN_no_entity_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_entity(&entity_with_full_name("ALICE-1", "Alice Example"))
+        .await
+        .expect("add entity in tx");
+    tx.add_entity(&entity_with_full_name("ALICE-1", "Alice Renamed"))
+        .await
+        .expect("replace entity in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_entities_by_name("Alice Example", 100)
+        .await
+        .expect("searching entities by name");
+
+    // THEN
+    let RdapResponse::EntitySearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_nameserver_in_mem_WHEN_lookup_nameserver_by_ldh_THEN_nameserver_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_nameserver(
+        &Nameserver::basic()
+            .ldh_name("ns.foo.example")
+            .build()
+            .unwrap(),
+    )
+    .await
+    .expect("add nameserver in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_nameserver_by_ldh("ns.foo.example")
+        .await
+        .expect("getting nameserver by ldh");
+
+    // THEN
+    let RdapResponse::Nameserver(nameserver) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        nameserver.ldh_name.as_ref().expect("ldhName is none"),
+        "ns.foo.example"
+    )
+}
+
+#[tokio::test]
+async fn GIVEN_no_nameserver_in_mem_WHEN_lookup_nameserver_by_ldh_THEN_404_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem
+        .get_nameserver_by_ldh("ns.foo.example")
+        .await
+        .expect("getting nameserver by ldh");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+fn nameserver_with_addresses(ldh_name: &str, addresses: &[&str]) -> Nameserver {
+    Nameserver::basic()
+        .ldh_name(ldh_name)
+        .addresses(addresses.iter().map(|addr| addr.to_string()).collect())
+        .build()
+        .expect("building nameserver")
+}
+
+#[rstest]
+#[case("ns1.foo.example", &["ns1.foo.example"])]
+#[case("NS1.FOO.EXAMPLE", &["ns1.foo.example"])]
+#[case("ns1.*", &["ns1.foo.example", "ns1.bar.example"])]
+#[case("*.bar.example", &["ns1.bar.example", "ns2.bar.example"])]
+#[case("ns3.*", &[])]
+#[tokio::test]
+async fn GIVEN_nameservers_in_mem_WHEN_search_nameservers_by_name_THEN_matching_nameservers_returned(
+    #[case] name: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for nameserver in [
+        nameserver_with_addresses("ns1.foo.example", &["192.0.2.1"]),
+        nameserver_with_addresses("ns1.bar.example", &["192.0.2.129", "2001:db8:1::1"]),
+        nameserver_with_addresses("ns2.bar.example", &["2001:db8:2::1"]),
+    ] {
+        tx.add_nameserver(&nameserver)
+            .await
+            .expect("add nameserver in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_nameservers_by_name(name, 100)
+        .await
+        .expect("searching nameservers by name");
+
+    // THEN
+    let RdapResponse::NameserverSearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|nameserver| nameserver.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[rstest]
+#[case("192.0.2.1", &["ns1.foo.example"])]
+#[case("192.0.2.0/24", &["ns1.foo.example", "ns1.bar.example"])]
+#[case("192.0.2.128/25", &["ns1.bar.example"])]
+#[case("2001:db8:1::1", &["ns1.bar.example"])]
+#[case("2001:db8::/32", &["ns1.bar.example", "ns2.bar.example"])]
+#[case("198.51.100.0/24", &[])]
+#[tokio::test]
+async fn GIVEN_nameservers_in_mem_WHEN_search_nameservers_by_ip_THEN_matching_nameservers_returned(
+    #[case] ip: &str,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for nameserver in [
+        nameserver_with_addresses("ns1.foo.example", &["192.0.2.1"]),
+        nameserver_with_addresses("ns1.bar.example", &["192.0.2.129", "2001:db8:1::1"]),
+        nameserver_with_addresses("ns2.bar.example", &["2001:db8:2::1"]),
+    ] {
+        tx.add_nameserver(&nameserver)
+            .await
+            .expect("add nameserver in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_nameservers_by_ip(ip, 100)
+        .await
+        .expect("searching nameservers by ip");
+
+    // THEN
+    let RdapResponse::NameserverSearchResults(results) = actual else {
+        panic!()
+    };
+    let actual = results
+        .results
+        .iter()
+        .map(|nameserver| nameserver.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<BTreeSet<&str>>();
+    assert_eq!(actual, expected.iter().copied().collect())
+}
+
+#[tokio::test]
+async fn GIVEN_nameserver_replaced_in_mem_WHEN_search_nameservers_by_old_ip_THEN_no_nameserver_returned(
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_nameserver(&nameserver_with_addresses(
+        "ns1.foo.example",
+        &["192.0.2.1"],
+    ))
+    .await
+    .expect("add nameserver in tx");
+    tx.commit().await.expect("tx commit");
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_nameserver(&nameserver_with_addresses(
+        "ns1.foo.example",
+        &["192.0.2.2"],
+    ))
+    .await
+    .expect("replace nameserver in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .search_nameservers_by_ip("192.0.2.1", 100)
+        .await
+        .expect("searching nameservers by ip");
+
+    // THEN
+    let RdapResponse::NameserverSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_autnum_in_mem_WHEN_lookup_autnum_by_start_autnum_THEN_autnum_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_autnum(&Autnum::basic().autnum_range(700..710).build())
+        .await
+        .expect("add autnum in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_autnum_by_num(700)
+        .await
+        .expect("getting autnum by num");
+
+    // THEN
+    let RdapResponse::Autnum(autnum) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *autnum.start_autnum.as_ref().expect("startNum is none"),
+        700
+    );
+    assert_eq!(*autnum.end_autnum.as_ref().expect("startNum is none"), 710);
+}
+
+#[tokio::test]
+async fn GIVEN_autnum_in_mem_WHEN_lookup_autnum_by_end_autnum_THEN_autnum_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_autnum(&Autnum::basic().autnum_range(700..710).build())
+        .await
+        .expect("add autnum in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_autnum_by_num(710)
+        .await
+        .expect("getting autnum by num");
+
+    // THEN
+    let RdapResponse::Autnum(autnum) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *autnum.start_autnum.as_ref().expect("startNum is none"),
+        700
+    );
+    assert_eq!(*autnum.end_autnum.as_ref().expect("startNum is none"), 710);
+}
+
+#[tokio::test]
+async fn GIVEN_contained_autnums_in_mem_WHEN_lookup_autnum_by_num_THEN_most_specific_autnum_returned(
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_autnum(&Autnum::basic().autnum_range(700..799).build())
+        .await
+        .expect("add autnum in tx");
+    tx.add_autnum(&Autnum::basic().autnum_range(710..720).build())
+        .await
+        .expect("add autnum in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_autnum_by_num(715)
+        .await
+        .expect("getting autnum by num");
+
+    // THEN
+    let RdapResponse::Autnum(autnum) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *autnum.start_autnum.as_ref().expect("startNum is none"),
+        710
+    );
+    assert_eq!(*autnum.end_autnum.as_ref().expect("startNum is none"), 720);
+}
+
+#[rstest]
+#[case(715, 710, 720)]
+#[case(705, 700, 799)]
+#[case(725, 720, 730)]
+#[case(750, 700, 799)]
+#[tokio::test]
+async fn GIVEN_overlapping_autnums_in_mem_WHEN_lookup_autnum_by_num_THEN_smallest_autnum_returned(
+    #[case] num: u32,
+    #[case] expected_start: u32,
+    #[case] expected_end: u32,
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for range in [710..720, 700..799, 720..730] {
+        tx.add_autnum(&Autnum::basic().autnum_range(range).build())
+            .await
+            .expect("add autnum in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_autnum_by_num(num)
+        .await
+        .expect("getting autnum by num");
+
+    // THEN
+    let RdapResponse::Autnum(autnum) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *autnum.start_autnum.as_ref().expect("startNum is none"),
+        expected_start
+    );
+    assert_eq!(
+        *autnum.end_autnum.as_ref().expect("endNum is none"),
+        expected_end
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_no_autnum_in_mem_WHEN_lookup_autnum_by_num_THEN_404_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem
+        .get_autnum_by_num(700)
+        .await
+        .expect("getting autnum by num");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+#[rstest]
+#[case("192.168.0.0/24", "192.168.0.1", "192.168.0.0", "192.168.0.255")]
+#[case("192.168.0.0/24", "192.168.0.0", "192.168.0.0", "192.168.0.255")]
+#[case("192.168.0.0/24", "192.168.0.254", "192.168.0.0", "192.168.0.255")]
+#[case("192.168.0.0/24", "192.168.0.255", "192.168.0.0", "192.168.0.255")]
+#[tokio::test]
+async fn GIVEN_network_in_mem_WHEN_lookup_network_by_address_THEN_network_returned(
+    #[case] cidr: &str,
+    #[case] addr: &str,
+    #[case] start: &str,
+    #[case] end: &str,
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_network(&Network::basic().cidr(cidr).build().expect("cidr parsing"))
+        .await
+        .expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_network_by_ipaddr(addr)
+        .await
+        .expect("getting network by num");
+
+    // THEN
+    let RdapResponse::Network(network) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *network
+            .start_address
+            .as_ref()
+            .expect("startAddress is none"),
+        start
+    );
+    assert_eq!(
+        *network.end_address.as_ref().expect("endAddress is none"),
+        end
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_network_err_in_mem_WHEN_lookup_network_by_address_THEN_error_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_network_err(
+        &NetworkId::builder()
+            .network_id(NetworkIdType::Range {
+                start_address: "10.0.0.0".to_string(),
+                end_address: "10.0.1.255".to_string(),
+            })
+            .build(),
+        &Error::redirect().url("https://other.example.com/").build(),
+    )
+    .await
+    .expect("add network error in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_network_by_ipaddr("10.0.1.1")
+        .await
+        .expect("getting network by address");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 307);
+}
+
+#[tokio::test]
+async fn GIVEN_no_network_in_mem_WHEN_lookup_network_by_address_THEN_404_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem
+        .get_network_by_ipaddr("192.168.0.1")
+        .await
+        .expect("getting network by address");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+#[rstest]
+#[case(&["192.168.0.0/16", "192.168.0.0/8", "192.168.0.0/24"], "192.168.0.1", "192.168.0.0", "192.168.0.255")]
+#[case(&["2001::/64", "2001::/56", "2001::/20"], "2001::1", "2001::", "2001::ffff:ffff:ffff:ffff")]
+#[tokio::test]
+async fn GIVEN_contained_networks_in_mem_WHEN_lookup_network_by_address_THEN_most_specific_network_returned(
+    #[case] cidrs: &[&str],
+    #[case] addr: &str,
+    #[case] start: &str,
+    #[case] end: &str,
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for cidr in cidrs {
+        tx.add_network(&Network::basic().cidr(*cidr).build().expect("cidr parsing"))
+            .await
+            .expect("add network in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_network_by_ipaddr(addr)
+        .await
+        .expect("getting network by num");
+
+    // THEN
+    let RdapResponse::Network(network) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *network
+            .start_address
+            .as_ref()
+            .expect("startAddress is none"),
+        start
+    );
+    assert_eq!(
+        *network.end_address.as_ref().expect("endAddress is none"),
+        end
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_offbit_network_in_mem_WHEN_lookup_network_by_first_address_THEN_network_returned() {
+    // GIVEN
+    let start = "10.0.0.0";
+    let end = "10.0.1.255";
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_network(
+        &Network::builder()
+            .start_address(start)
+            .end_address(end)
+            .ip_version("v4")
+            .object_common(ObjectCommon::ip_network().build())
+            .common(Common::builder().build())
+            .build(),
+    )
+    .await
+    .expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_network_by_ipaddr(start)
+        .await
+        .expect("getting network by num");
+
+    // THEN
+    let RdapResponse::Network(network) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *network
+            .start_address
+            .as_ref()
+            .expect("startAddress is none"),
+        start
+    );
+    assert_eq!(
+        *network.end_address.as_ref().expect("endAddress is none"),
+        end
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_offbit_network_in_mem_WHEN_lookup_network_by_last_address_THEN_network_returned() {
+    // GIVEN
+    let start = "10.0.0.0";
+    let end = "10.0.1.255";
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_network(
+        &Network::builder()
+            .start_address(start)
+            .end_address(end)
+            .ip_version("v4")
+            .object_common(ObjectCommon::ip_network().build())
+            .common(Common::builder().build())
+            .build(),
+    )
+    .await
+    .expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_network_by_ipaddr(end)
+        .await
+        .expect("getting network by num");
+
+    // THEN
+    let RdapResponse::Network(network) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *network
+            .start_address
+            .as_ref()
+            .expect("startAddress is none"),
+        start
+    );
+    assert_eq!(
+        *network.end_address.as_ref().expect("endAddress is none"),
+        end
+    );
+}
+
+#[rstest]
+#[case("192.168.0.0/16", "192.168.0.0/24", "192.168.0.0", "192.168.255.255")]
+#[case("192.168.0.0/16", "192.168.0.0/16", "192.168.0.0", "192.168.255.255")]
+#[tokio::test]
+async fn GIVEN_network_in_mem_WHEN_lookup_network_by_cidr_THEN_network_returned(
+    #[case] cidr: &str,
+    #[case] lookup: &str,
+    #[case] start: &str,
+    #[case] end: &str,
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_network(&Network::basic().cidr(cidr).build().expect("cidr parsing"))
+        .await
+        .expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_network_by_cidr(lookup)
+        .await
+        .expect("getting network by cidr");
+
+    // THEN
+    let RdapResponse::Network(network) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *network
+            .start_address
+            .as_ref()
+            .expect("startAddress is none"),
+        start
+    );
+    assert_eq!(
+        *network.end_address.as_ref().expect("endAddress is none"),
+        end
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_no_network_in_mem_WHEN_lookup_network_by_cidr_THEN_404_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+
+    // WHEN
+    let actual = mem
+        .get_network_by_cidr("192.168.0.0/24")
+        .await
+        .expect("getting network by address");
+
+    // THEN
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+#[tokio::test]
+async fn GIVEN_default_help_in_mem_WHEN_lookup_help_with_no_host_THEN_get_default_help() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_srv_help(
+        &Help::basic()
+            .notice(Notice(
+                NoticeOrRemark::builder()
+                    .description_entry("foo".to_string())
+                    .build(),
+            ))
+            .build()
+            .expect("building help"),
+        None,
+    )
+    .await
+    .expect("adding srv help");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem.get_srv_help(None).await.expect("getting srv helf");
+
+    // THEN
+    let RdapResponse::Help(srvhelp) = actual else {
+        panic!()
+    };
+    let notice = srvhelp
+        .common
+        .notices
+        .expect("no notices in srvhelp")
+        .first()
+        .expect("notices empty")
+        .to_owned();
+    assert_eq!(
+        notice
+            .description
+            .first()
+            .expect("no description in notice"),
+        "foo"
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_help_in_mem_WHEN_lookup_help_with_host_THEN_get_host_help() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_srv_help(
+        &Help::basic()
+            .notice(Notice(
+                NoticeOrRemark::builder()
+                    .description_entry("bar".to_string())
+                    .build(),
+            ))
+            .build()
+            .expect("building help"),
+        Some("bar.example.com"),
+    )
+    .await
+    .expect("adding srv help");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_srv_help(Some("bar.example.com"))
+        .await
+        .expect("getting srv helf");
+
+    // THEN
+    let RdapResponse::Help(srvhelp) = actual else {
+        panic!()
+    };
+    let notice = srvhelp
+        .common
+        .notices
+        .expect("no notices in srvhelp")
+        .first()
+        .expect("notices empty")
+        .to_owned();
+    assert_eq!(
+        notice
+            .description
+            .first()
+            .expect("no description in notice"),
+        "bar"
+    );
+}
+
+fn related_entity(handle: &str, full_name: &str, email: &str, role: &str) -> Entity {
+    Entity::basic()
+        .handle(handle)
+        .contact(
+            Contact::builder()
+                .full_name(full_name)
+                .emails(vec![Email::builder().email(email).build()])
+                .build(),
+        )
+        .role(role.to_string())
+        .build()
+}
+
+fn related_entity_search(
+    handle: Option<&str>,
+    full_name: Option<&str>,
+    email: Option<&str>,
+    role: Option<&str>,
+) -> RelatedEntitySearch {
+    RelatedEntitySearch {
+        handle: handle.map(SearchPattern::new),
+        full_name: full_name.map(SearchPattern::new),
+        email: email.map(SearchPattern::new),
+        role: role.map(str::to_string),
+    }
+}
+
+#[rstest]
+#[case(Some("ALICE-1"), None, None, None, &["foo.example"])]
+#[case(Some("bob-*"), None, None, None, &["bar.example", "foo.example"])]
+#[case(Some("BOB-1"), None, None, Some("registrant"), &["bar.example"])]
+#[case(Some("ALICE-1"), None, None, Some("technical"), &[])]
+#[case(None, Some("alice*"), None, None, &["foo.example"])]
+#[case(None, None, Some("*@example.com"), None, &["bar.example", "foo.example"])]
+#[case(None, None, Some("ALICE@Example.COM"), None, &["foo.example"])]
+#[case(None, None, None, Some("Registrant"), &["bar.example", "foo.example"])]
+#[tokio::test]
+async fn GIVEN_domains_with_entities_in_mem_WHEN_reverse_search_domains_THEN_related_domains_returned(
+    #[case] handle: Option<&str>,
+    #[case] full_name: Option<&str>,
+    #[case] email: Option<&str>,
+    #[case] role: Option<&str>,
+    #[case] expected: &[&str],
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .entity(related_entity(
+                "ALICE-1",
+                "Alice Example",
+                "alice@example.com",
+                "registrant",
+            ))
+            .entity(related_entity(
+                "BOB-1",
+                "Bob Example",
+                "bob@example.com",
+                "technical",
+            ))
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("bar.example")
+            .entity(related_entity(
+                "BOB-1",
+                "Bob Example",
+                "bob@example.com",
+                "registrant",
+            ))
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let related = related_entity_search(handle, full_name, email, role);
+    let actual = mem
+        .reverse_search_by_entity(ReverseSearchType::Domains, &related, 100)
+        .await
+        .expect("reverse searching domains");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results
+        .common
+        .rdap_conformance
+        .expect("no rdap conformance")
+        .contains(&Extension("reverse_search".to_string())));
+    let actual = results
+        .results
+        .iter()
+        .map(|domain| domain.ldh_name.as_deref().expect("ldhName is none"))
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected)
+}
+
+#[tokio::test]
+async fn GIVEN_nameserver_with_entity_in_mem_WHEN_reverse_search_nameservers_THEN_nameserver_returned(
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_nameserver(
+        &Nameserver::basic()
+            .ldh_name("ns1.foo.example")
+            .entity(related_entity(
+                "ALICE-1",
+                "Alice Example",
+                "alice@example.com",
+                "technical",
+            ))
+            .build()
+            .unwrap(),
+    )
+    .await
+    .expect("add nameserver in tx");
+    tx.add_nameserver(
+        &Nameserver::basic()
+            .ldh_name("ns2.foo.example")
+            .build()
+            .unwrap(),
+    )
+    .await
+    .expect("add nameserver in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let related = related_entity_search(Some("ALICE-1"), None, None, None);
+    let actual = mem
+        .reverse_search_by_entity(ReverseSearchType::Nameservers, &related, 100)
+        .await
+        .expect("reverse searching nameservers");
+
+    // THEN
+    let RdapResponse::NameserverSearchResults(results) = actual else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+    assert_eq!(
+        results.results[0].ldh_name.as_deref(),
+        Some("ns1.foo.example")
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_entity_with_entity_in_mem_WHEN_reverse_search_entities_THEN_entity_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_entity(
+        &Entity::basic()
+            .handle("REGISTRAR-1")
+            .entity(related_entity(
+                "ALICE-1",
+                "Alice Example",
+                "alice@example.com",
+                "abuse",
+            ))
+            .build(),
+    )
+    .await
+    .expect("add entity in tx");
+    tx.add_entity(&Entity::basic().handle("REGISTRAR-2").build())
+        .await
+        .expect("add entity in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let related = related_entity_search(None, None, Some("alice@example.com"), Some("abuse"));
+    let actual = mem
+        .reverse_search_by_entity(ReverseSearchType::Entities, &related, 100)
+        .await
+        .expect("reverse searching entities");
+
+    // THEN
+    let RdapResponse::EntitySearchResults(results) = actual else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+    assert_eq!(
+        results.results[0].object_common.handle.as_deref(),
+        Some("REGISTRAR-1")
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_domain_replaced_without_entity_in_mem_WHEN_reverse_search_domains_THEN_not_found() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::basic()
+            .ldh_name("foo.example")
+            .entity(related_entity(
+                "ALICE-1",
+                "Alice Example",
+                "alice@example.com",
+                "registrant",
+            ))
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("replace domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let related = related_entity_search(Some("ALICE-1"), None, None, None);
+    let actual = mem
+        .reverse_search_by_entity(ReverseSearchType::Domains, &related, 100)
+        .await
+        .expect("reverse searching domains");
+
+    // THEN
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_domain_in_mem_WHEN_delete_domain_THEN_domain_not_found_or_searched() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::idn()
+            .ldh_name("xn--caf-dma.example")
+            .unicode_name("café.example")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.delete_domain(&DomainId::builder().ldh_name("xn--caf-dma.example").build())
+        .await
+        .expect("delete domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    let actual = mem
+        .get_domain_by_ldh("xn--caf-dma.example")
+        .await
+        .expect("getting domain by ldh");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404);
+    let actual = mem
+        .get_domain_by_unicode("café.example")
+        .await
+        .expect("getting domain by unicode");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404);
+    let actual = mem
+        .search_domains_by_name("caf*", 100)
+        .await
+        .expect("searching domains by name");
+    let RdapResponse::DomainSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_domain_redirect_in_mem_WHEN_delete_domain_THEN_domain_not_found() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain_err(
+        &DomainId::builder().ldh_name("foo.example").build(),
+        &Error::redirect().url("https://example.net/").build(),
+    )
+    .await
+    .expect("add domain redirect in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.delete_domain(&DomainId::builder().ldh_name("foo.example").build())
+        .await
+        .expect("delete domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    let actual = mem
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404)
+}
+
+#[tokio::test]
+async fn GIVEN_entity_in_mem_WHEN_delete_entity_THEN_entity_not_found_or_searched() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_entity(&entity_with_full_name("foo", "Foo Bar"))
+        .await
+        .expect("add entity in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.delete_entity(&EntityId::builder().handle("foo").build())
+        .await
+        .expect("delete entity in tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    let actual = mem
+        .get_entity_by_handle("foo")
+        .await
+        .expect("getting entity by handle");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404);
+    let actual = mem
+        .search_entities_by_name("Foo*", 100)
+        .await
+        .expect("searching entities by name");
+    let RdapResponse::EntitySearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_nameserver_in_mem_WHEN_delete_nameserver_THEN_nameserver_not_found_or_searched() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_nameserver(&nameserver_with_addresses("ns.foo.example", &["192.0.2.1"]))
+        .await
+        .expect("add nameserver in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.delete_nameserver(&NameserverId::builder().ldh_name("ns.foo.example").build())
+        .await
+        .expect("delete nameserver in tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    let actual = mem
+        .get_nameserver_by_ldh("ns.foo.example")
+        .await
+        .expect("getting nameserver by ldh");
+    let RdapResponse::ErrorResponse(error) = actual else {
+        panic!()
+    };
+    assert_eq!(error.error_code, 404);
+    let actual = mem
+        .search_nameservers_by_ip("192.0.2.1", 100)
+        .await
+        .expect("searching nameservers by ip");
+    let RdapResponse::NameserverSearchResults(results) = actual else {
+        panic!()
+    };
+    assert!(results.results.is_empty())
+}
+
+#[tokio::test]
+async fn GIVEN_autnums_in_mem_WHEN_delete_autnum_THEN_only_its_range_removed() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for range in [700..710, 711..720] {
+        tx.add_autnum(&Autnum::basic().autnum_range(range).build())
+            .await
+            .expect("add autnum in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.delete_autnum(
+        &AutnumId::builder()
+            .start_autnum(700)
+            .end_autnum(710)
+            .build(),
+    )
+    .await
+    .expect("delete autnum in tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    for num in [700, 705, 710] {
+        let actual = mem
+            .get_autnum_by_num(num)
+            .await
+            .expect("getting autnum by num");
+        let RdapResponse::ErrorResponse(error) = actual else {
+            panic!()
+        };
+        assert_eq!(error.error_code, 404);
+    }
+    let actual = mem
+        .get_autnum_by_num(711)
+        .await
+        .expect("getting autnum by num");
+    assert!(matches!(actual, RdapResponse::Autnum(_)));
+}
+
+#[rstest]
+#[case(NetworkIdType::Cidr("192.168.0.0/24".parse().expect("cidr parsing")))]
+#[case(NetworkIdType::Range {
+    start_address: "192.168.0.0".to_string(),
+    end_address: "192.168.0.255".to_string(),
+})]
+#[tokio::test]
+async fn GIVEN_contained_networks_in_mem_WHEN_delete_network_THEN_less_specific_network_returned(
+    #[case] network_id: NetworkIdType,
+) {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    for cidr in ["192.168.0.0/16", "192.168.0.0/24"] {
+        tx.add_network(&Network::basic().cidr(cidr).build().expect("cidr parsing"))
+            .await
+            .expect("add network in tx");
+    }
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.delete_network(&NetworkId::builder().network_id(network_id).build())
+        .await
+        .expect("delete network in tx");
+    tx.commit().await.expect("tx commit");
+
+    // THEN
+    let actual = mem
+        .get_network_by_ipaddr("192.168.0.1")
+        .await
+        .expect("getting network by address");
+    let RdapResponse::Network(network) = actual else {
+        panic!()
+    };
+    assert_eq!(
+        *network.end_address.as_ref().expect("endAddress is none"),
+        "192.168.255.255"
+    );
+}
+
+#[tokio::test]
+async fn GIVEN_domain_in_mem_WHEN_get_serialized_THEN_domain_json_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    let domain = Domain::basic().ldh_name("foo.example").build();
+    tx.add_domain(&domain).await.expect("add domain in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_serialized(Lookup::DomainByLdh("foo.example"))
+        .await
+        .expect("getting serialized domain");
+
+    // THEN
+    if !KEEPS_SERIALIZED {
+        assert!(actual.is_none());
+        return;
+    }
+    let serialized = actual.expect("serialized domain is none");
+    let expected = serde_json::to_vec(&RdapResponse::Domain(domain)).expect("serializing domain");
+    assert_eq!(serialized.json.as_ref(), expected.as_slice());
+}
+
+#[tokio::test]
+async fn GIVEN_network_in_mem_WHEN_get_serialized_by_address_THEN_network_json_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    let network = Network::basic()
+        .cidr("192.168.0.0/24")
+        .build()
+        .expect("cidr parsing");
+    tx.add_network(&network).await.expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_serialized(Lookup::NetworkByIpAddr("192.168.0.1"))
+        .await
+        .expect("getting serialized network");
+
+    // THEN
+    if !KEEPS_SERIALIZED {
+        assert!(actual.is_none());
+        return;
+    }
+    let serialized = actual.expect("serialized network is none");
+    let expected =
+        serde_json::to_vec(&RdapResponse::Network(network)).expect("serializing network");
+    assert_eq!(serialized.json.as_ref(), expected.as_slice());
+}
+
+#[tokio::test]
+async fn GIVEN_domain_redirect_in_mem_WHEN_get_serialized_THEN_none_returned() {
+    // GIVEN
+    let Some(mem) = new_store().await else {
+        return;
+    };
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain_err(
+        &DomainId::builder().ldh_name("foo.example").build(),
+        &Error::redirect().url("https://other.example/").build(),
+    )
+    .await
+    .expect("add domain error in tx");
+    tx.commit().await.expect("tx commit");
+
+    // WHEN
+    let actual = mem
+        .get_serialized(Lookup::DomainByLdh("foo.example"))
+        .await
+        .expect("getting serialized domain");
+
+    // THEN
+    assert!(actual.is_none());
+}
@@ -0,0 +1,137 @@
+use std::{path::Path, time::Duration};
+
+use icann_rdap_common::response::{domain::Domain, network::Network, RdapResponse};
+use icann_rdap_srv::storage::{
+    mem::{config::MemConfig, ops::Mem},
+    Lookup, StoreOps,
+};
+use test_dir::{DirBuilder, FileType, TestDir};
+
+fn snapshot_mem(temp: &TestDir) -> Mem {
+    Mem::new(
+        MemConfig::builder()
+            .snapshot_file(temp.path("snapshot").to_string_lossy())
+            .build(),
+    )
+}
+
+/// Commits a domain and a network, and waits for the snapshot of them to be written.
+async fn commit_and_snapshot(temp: &TestDir) {
+    // the snapshot must be later than the data directory by more than the file times' resolution.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let mem = snapshot_mem(temp);
+    let mut tx = mem.new_tx().await.expect("new transaction");
+    tx.add_domain(
+        &Domain::idn()
+            .unicode_name("café.example")
+            .ldh_name("xn--caf-dma.example")
+            .build(),
+    )
+    .await
+    .expect("add domain in tx");
+    tx.add_network(
+        &Network::basic()
+            .cidr("10.0.0.0/15")
+            .build()
+            .expect("cidr parsing"),
+    )
+    .await
+    .expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+    let snapshot = temp.path("snapshot");
+    for _ in 0..100 {
+        if snapshot.exists() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("snapshot not written");
+}
+
+fn data_dir(temp: &TestDir) -> String {
+    temp.path("data").to_string_lossy().to_string()
+}
+
+#[tokio::test]
+async fn GIVEN_snapshot_WHEN_restore_snapshot_THEN_objects_and_indexes_restored() {
+    // GIVEN
+    let temp = TestDir::temp().create("data", FileType::Dir);
+    commit_and_snapshot(&temp).await;
+    let mem = snapshot_mem(&temp);
+
+    // WHEN
+    let actual = mem
+        .restore_snapshot(&data_dir(&temp))
+        .await
+        .expect("restoring snapshot");
+
+    // THEN
+    assert!(actual);
+    let domain = mem
+        .get_domain_by_unicode("café.example")
+        .await
+        .expect("getting domain by unicode");
+    assert!(matches!(domain, RdapResponse::Domain(_)));
+    let RdapResponse::DomainSearchResults(results) = mem
+        .search_domains_by_name("caf*", 10)
+        .await
+        .expect("searching domains")
+    else {
+        panic!()
+    };
+    assert_eq!(results.results.len(), 1);
+    let network = mem
+        .get_serialized(Lookup::NetworkByIpAddr("10.1.0.1"))
+        .await
+        .expect("getting serialized network");
+    assert!(network.is_some());
+    let counts = mem.get_object_counts().await.expect("counting objects");
+    assert_eq!(counts.domains, 1);
+    assert_eq!(counts.networks, 1);
+}
+
+#[tokio::test]
+async fn GIVEN_data_dir_changed_after_snapshot_WHEN_restore_snapshot_THEN_not_restored() {
+    // GIVEN
+    let temp = TestDir::temp().create("data", FileType::Dir);
+    commit_and_snapshot(&temp).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    std::fs::write(Path::new(&data_dir(&temp)).join("foo.json"), "{}").expect("writing file");
+    let mem = snapshot_mem(&temp);
+
+    // WHEN
+    let actual = mem
+        .restore_snapshot(&data_dir(&temp))
+        .await
+        .expect("restoring snapshot");
+
+    // THEN
+    assert!(!actual);
+}
+
+#[tokio::test]
+async fn GIVEN_corrupt_snapshot_WHEN_restore_snapshot_THEN_not_restored() {
+    // GIVEN
+    let temp = TestDir::temp().create("data", FileType::Dir);
+    commit_and_snapshot(&temp).await;
+    let snapshot = temp.path("snapshot");
+    let mut bytes = std::fs::read(&snapshot).expect("reading snapshot");
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(&snapshot, bytes).expect("writing snapshot");
+    let mem = snapshot_mem(&temp);
+
+    // WHEN
+    let actual = mem
+        .restore_snapshot(&data_dir(&temp))
+        .await
+        .expect("restoring snapshot");
+
+    // THEN
+    assert!(!actual);
+    let domain = mem
+        .get_domain_by_ldh("xn--caf-dma.example")
+        .await
+        .expect("getting domain by ldh");
+    assert!(matches!(domain, RdapResponse::ErrorResponse(_)));
+}
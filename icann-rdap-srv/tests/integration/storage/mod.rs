@@ -1,2 +1,4 @@
 mod data;
 mod mem;
+mod pg;
+mod sqlite;
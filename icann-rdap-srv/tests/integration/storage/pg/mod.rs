@@ -0,0 +1,68 @@
+#![allow(non_snake_case)]
+
+use std::ops::Deref;
+
+use icann_rdap_srv::storage::{
+    pg::{migrate::MigrationState, ops::Pg},
+    StoreOps,
+};
+
+use crate::test_jig::PgTestJig;
+
+// The memory storage tests are run unchanged against PostgreSQL storage, when there is a
+// test database. Loading the file again is intended, as each loading uses the `new_store`
+// of its own parent module.
+#[allow(clippy::duplicate_mod)]
+#[path = "../mem/ops.rs"]
+mod ops;
+
+/// The store of the shared storage tests, whose throwaway database is dropped with it.
+struct PgStore(Option<PgTestJig>);
+
+impl Deref for PgStore {
+    type Target = Pg;
+
+    fn deref(&self) -> &Pg {
+        &self.0.as_ref().expect("test database dropped").pg
+    }
+}
+
+impl Drop for PgStore {
+    fn drop(&mut self) {
+        if let Some(jig) = self.0.take() {
+            jig.drop_db_blocking();
+        }
+    }
+}
+
+/// If the store keeps the JSON of objects serialized when they are stored.
+const KEEPS_SERIALIZED: bool = false;
+
+/// Creates the store used by the shared storage tests, which uses a new throwaway database.
+async fn new_store() -> Option<PgStore> {
+    PgTestJig::new().await.map(|jig| PgStore(Some(jig)))
+}
+
+#[tokio::test]
+async fn GIVEN_new_database_WHEN_init_THEN_all_migrations_applied() {
+    // GIVEN
+    let Some(jig) = PgTestJig::new().await else {
+        return;
+    };
+    let pg = &jig.pg;
+
+    // WHEN
+    pg.init().await.expect("initializing again");
+
+    // THEN
+    let statuses = pg
+        .migration_status()
+        .await
+        .expect("getting migration status");
+    assert!(!statuses.is_empty());
+    assert!(statuses
+        .iter()
+        .all(|status| status.state == MigrationState::Applied));
+
+    jig.drop_db().await;
+}
@@ -0,0 +1,67 @@
+use icann_rdap_common::response::{domain::Domain, network::Network, RdapResponse};
+use icann_rdap_srv::storage::{
+    sqlite::{config::SqliteConfig, ops::Sqlite},
+    StoreOps,
+};
+use test_dir::{DirBuilder, TestDir};
+
+async fn file_sqlite(temp: &TestDir) -> Sqlite {
+    let db_url = format!("sqlite://{}", temp.path("rdap.db").to_string_lossy());
+    let sqlite = Sqlite::new(SqliteConfig::builder().db_url(db_url).build())
+        .await
+        .expect("opening database");
+    sqlite.init().await.expect("initializing database");
+    sqlite
+}
+
+#[tokio::test]
+async fn GIVEN_objects_committed_to_file_WHEN_reopened_THEN_objects_returned() {
+    // GIVEN
+    let temp = TestDir::temp();
+    let sqlite = file_sqlite(&temp).await;
+    let mut tx = sqlite.new_tx().await.expect("new transaction");
+    tx.add_domain(&Domain::basic().ldh_name("foo.example").build())
+        .await
+        .expect("add domain in tx");
+    tx.add_network(
+        &Network::basic()
+            .cidr("10.0.0.0/15")
+            .build()
+            .expect("cidr parsing"),
+    )
+    .await
+    .expect("add network in tx");
+    tx.commit().await.expect("tx commit");
+    drop(sqlite);
+
+    // WHEN
+    let sqlite = file_sqlite(&temp).await;
+
+    // THEN
+    let actual = sqlite
+        .get_domain_by_ldh("foo.example")
+        .await
+        .expect("getting domain by ldh");
+    let RdapResponse::Domain(domain) = actual else {
+        panic!()
+    };
+    assert_eq!(domain.ldh_name.as_deref(), Some("foo.example"));
+    let actual = sqlite
+        .get_network_by_ipaddr("10.1.255.255")
+        .await
+        .expect("getting network by address");
+    assert!(matches!(actual, RdapResponse::Network(_)));
+}
+
+#[tokio::test]
+async fn GIVEN_new_file_WHEN_init_twice_THEN_no_error() {
+    // GIVEN
+    let temp = TestDir::temp();
+    let sqlite = file_sqlite(&temp).await;
+
+    // WHEN
+    let actual = sqlite.init().await;
+
+    // THEN
+    assert!(actual.is_ok());
+}
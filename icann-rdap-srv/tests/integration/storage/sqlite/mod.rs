@@ -0,0 +1,34 @@
+#![allow(non_snake_case)]
+
+use std::str::FromStr;
+
+use icann_rdap_srv::storage::{sqlite::ops::Sqlite, StoreOps};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+mod file;
+// The memory storage tests are run unchanged against SQLite storage. Loading the file again
+// is intended, as each loading uses the `new_store` of its own parent module.
+#[allow(clippy::duplicate_mod)]
+#[path = "../mem/ops.rs"]
+mod ops;
+
+/// If the store keeps the JSON of objects serialized when they are stored.
+const KEEPS_SERIALIZED: bool = true;
+
+/// Creates the store used by the shared storage tests, which is a new in-memory database.
+/// The database lives only as long as its connection, so the pool has a single connection
+/// which is never closed.
+async fn new_store() -> Option<Sqlite> {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").expect("in-memory database URL");
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .expect("connecting to in-memory database");
+    let sqlite = Sqlite::from_pool(pool);
+    sqlite.init().await.expect("initializing database");
+    Some(sqlite)
+}
@@ -1,9 +1,25 @@
 use assert_cmd::Command;
-use icann_rdap_srv::config::ListenConfig;
+use icann_rdap_srv::admin::Admin;
+use icann_rdap_srv::config::{
+    CacheConfig, RateLimitConfig, DEFAULT_MAX_SEARCH_RESULTS, DEFAULT_SEARCH_PAGE_SIZE,
+};
+use icann_rdap_srv::config::{ListenConfig, TlsConfig};
+use icann_rdap_srv::health::Health;
+use icann_rdap_srv::rdap::access::Credentials;
+use icann_rdap_srv::rdap::openidc::{OpenIdc, ProviderConfig};
+use icann_rdap_srv::rdap::redaction::RedactionPolicy;
 use icann_rdap_srv::server::AppState;
 use icann_rdap_srv::server::Listener;
 use icann_rdap_srv::storage::mem::ops::Mem;
+use icann_rdap_srv::storage::pg::config::PgConfig;
+use icann_rdap_srv::storage::pg::ops::Pg;
+use icann_rdap_srv::storage::StoreOps;
+use sqlx::Executor;
+use sqlx::PgPool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use std::time::SystemTime;
 use test_dir::DirBuilder;
 use test_dir::TestDir;
 
@@ -76,35 +92,142 @@ pub struct SrvTestJig {
 
 impl SrvTestJig {
     pub async fn new() -> SrvTestJig {
-        let mem = Mem::default();
+        SrvTestJig::new_with_state(SrvTestJig::app_state()).await
+    }
+
+    pub async fn new_rate_limited(rate_limit: RateLimitConfig) -> SrvTestJig {
         let app_state = AppState {
-            storage: mem.clone(),
-            bootstrap: false,
+            rate_limit,
+            ..SrvTestJig::app_state()
         };
-        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
-        let listener = Listener::listen(&ListenConfig::default())
-            .await
-            .expect("listening on interface");
-        let rdap_base = listener.rdap_base();
-        tokio::spawn(async move {
-            listener
-                .start_with_state(app_state)
-                .await
-                .expect("starting server");
-        });
-        SrvTestJig { mem, rdap_base }
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_max_search_results(max_search_results: usize) -> SrvTestJig {
+        let app_state = AppState {
+            max_search_results,
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_cache(cache: CacheConfig) -> SrvTestJig {
+        let app_state = AppState {
+            cache,
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_credentials(credentials: Credentials) -> SrvTestJig {
+        let app_state = AppState {
+            credentials: Some(credentials),
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_credentials_rate_limited(
+        credentials: Credentials,
+        rate_limit: RateLimitConfig,
+    ) -> SrvTestJig {
+        let app_state = AppState {
+            credentials: Some(credentials),
+            rate_limit,
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_redaction_policy(redaction_policy: RedactionPolicy) -> SrvTestJig {
+        let app_state = AppState {
+            redaction_policy: Some(redaction_policy),
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    /// Creates a server logging in end-users with the providers, using the `login`
+    /// path of the server as the redirect URI.
+    pub async fn new_with_openidc(providers: Vec<ProviderConfig>) -> SrvTestJig {
+        let listener = SrvTestJig::listen(&ListenConfig::default()).await;
+        let openidc = OpenIdc::builder()
+            .redirect_uri(format!("{}/login", listener.rdap_base()))
+            .providers(providers)
+            .build();
+        let app_state = AppState {
+            openidc: Some(openidc),
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::start(listener, app_state)
     }
 
     pub async fn new_bootstrap() -> SrvTestJig {
-        let mem = Mem::default();
         let app_state = AppState {
-            storage: mem.clone(),
             bootstrap: true,
+            ..SrvTestJig::app_state()
         };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_health(health: Health, bootstrap: bool) -> SrvTestJig {
+        let app_state = AppState {
+            health,
+            bootstrap,
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    pub async fn new_with_admin(admin: Admin) -> SrvTestJig {
+        let app_state = AppState {
+            admin: Some(admin),
+            ..SrvTestJig::app_state()
+        };
+        SrvTestJig::new_with_state(app_state).await
+    }
+
+    fn app_state() -> AppState<Mem> {
+        AppState {
+            storage: Mem::default(),
+            bootstrap: false,
+            max_search_results: DEFAULT_MAX_SEARCH_RESULTS,
+            search_page_size: DEFAULT_SEARCH_PAGE_SIZE,
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            credentials: None,
+            redaction_policy: None,
+            openidc: None,
+            health: Health::default(),
+            admin: None,
+        }
+    }
+
+    async fn new_with_state(app_state: AppState<Mem>) -> SrvTestJig {
+        SrvTestJig::start(
+            SrvTestJig::listen(&ListenConfig::default()).await,
+            app_state,
+        )
+    }
+
+    pub async fn new_with_tls(tls: TlsConfig) -> SrvTestJig {
+        let listen_config = ListenConfig::builder()
+            .ip_addr("127.0.0.1")
+            .tls(tls)
+            .build();
+        let listener = SrvTestJig::listen(&listen_config).await;
+        SrvTestJig::start(listener, SrvTestJig::app_state())
+    }
+
+    async fn listen(config: &ListenConfig) -> Listener {
         let _ = tracing_subscriber::fmt().with_test_writer().try_init();
-        let listener = Listener::listen(&ListenConfig::default())
+        Listener::listen(config)
             .await
-            .expect("listening on interface");
+            .expect("listening on interface")
+    }
+
+    fn start(listener: Listener, app_state: AppState<Mem>) -> SrvTestJig {
+        let mem = app_state.storage.clone();
         let rdap_base = listener.rdap_base();
         tokio::spawn(async move {
             listener
@@ -115,3 +238,96 @@ impl SrvTestJig {
         SrvTestJig { mem, rdap_base }
     }
 }
+
+/// Environment variable with the URL of a PostgreSQL server the PostgreSQL storage
+/// tests may create and drop databases in, such as `postgresql://postgres@127.0.0.1`.
+/// If it is not set, those tests do nothing.
+pub const TEST_DB_URL: &str = "RDAP_SRV_TEST_DB_URL";
+
+static PG_TEST_DB_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub struct PgTestJig {
+    pub pg: Pg,
+    pub db_url: String,
+    server_url: String,
+    admin_pool: PgPool,
+    db_name: String,
+}
+
+impl PgTestJig {
+    /// Creates a throwaway database and an initialized [Pg] store using it.
+    pub async fn new() -> Option<PgTestJig> {
+        let Ok(server_url) = std::env::var(TEST_DB_URL) else {
+            eprintln!("{TEST_DB_URL} is not set, skipping PostgreSQL test");
+            return None;
+        };
+        let admin_pool = PgPool::connect(&server_url)
+            .await
+            .expect("connecting to test database server");
+        let db_name = format!(
+            "rdap_srv_test_{}_{}_{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("system time")
+                .as_millis(),
+            PG_TEST_DB_COUNT.fetch_add(1, Ordering::SeqCst)
+        );
+        admin_pool
+            .execute(format!("create database {db_name}").as_str())
+            .await
+            .expect("creating test database");
+        let mut db_url = reqwest::Url::parse(&server_url).expect("parsing test database url");
+        db_url.set_path(&db_name);
+        let db_url = db_url.to_string();
+        let pg = Pg::new(PgConfig::builder().db_url(db_url.clone()).build())
+            .await
+            .expect("connecting to test database");
+        pg.init().await.expect("initializing test database");
+        Some(PgTestJig {
+            pg,
+            db_url,
+            server_url,
+            admin_pool,
+            db_name,
+        })
+    }
+
+    /// Drops the throwaway database.
+    pub async fn drop_db(self) {
+        drop(self.pg);
+        self.admin_pool
+            .execute(format!("drop database {} with (force)", self.db_name).as_str())
+            .await
+            .expect("dropping test database");
+    }
+
+    /// Drops the throwaway database where it cannot be awaited, such as when a store is
+    /// dropped. The connections of the jig belong to the runtime of the test, so the database
+    /// is dropped using a runtime and connection of its own.
+    pub fn drop_db_blocking(self) {
+        let PgTestJig {
+            pg,
+            server_url,
+            db_name,
+            ..
+        } = self;
+        drop(pg);
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("creating runtime")
+                .block_on(async {
+                    PgPool::connect(&server_url)
+                        .await
+                        .expect("connecting to test database server")
+                        .execute(format!("drop database {db_name} with (force)").as_str())
+                        .await
+                        .expect("dropping test database");
+                })
+        })
+        .join()
+        .expect("dropping test database");
+    }
+}